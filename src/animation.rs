@@ -0,0 +1,227 @@
+//! Sprite sheet animations, whose clips are defined in `.anim` asset files.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use bevy::{
+    asset::{AssetLoader, AssetPath, BoxedFuture, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use serde::Deserialize;
+
+use crate::clock::GameClock;
+
+/// Plugin loading animation sheets and playing the animations.
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_asset::<AnimationSheet>()
+            .init_asset_loader::<AnimationSheetLoader>()
+            .add_system(animate_system.system());
+    }
+}
+
+/// A sequence of frames of a sprite sheet.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Clip {
+    /// Indexes of the frames in the sprite sheet.
+    pub frames: Vec<u32>,
+    /// Number of frames displayed per second.
+    pub fps: f32,
+}
+
+/// A sprite sheet and its named clips.
+#[derive(Debug, TypeUuid)]
+#[uuid = "5f0ac0b3-61e8-4c39-9a8e-5a4f5c1d2b7e"]
+pub struct AnimationSheet {
+    /// Texture atlas cutting the sprite sheet in frames.
+    pub atlas: Handle<TextureAtlas>,
+    /// Clips of the sheet, by name.
+    pub clips: HashMap<String, Clip>,
+}
+
+/// Content of an `.anim` file.
+#[derive(Debug, Deserialize)]
+struct AnimationSheetDefinition {
+    /// Path of the sprite sheet in the assets.
+    texture: String,
+    /// Size of a frame in the sprite sheet.
+    tile_size: (f32, f32),
+    /// Number of frames in a row of the sprite sheet.
+    columns: usize,
+    /// Number of rows of the sprite sheet.
+    rows: usize,
+    /// Clips of the sheet, by name.
+    clips: HashMap<String, Clip>,
+}
+
+/// Loads `.anim` files, with the texture atlas of their sprite sheet.
+#[derive(Default)]
+struct AnimationSheetLoader;
+
+impl AssetLoader for AnimationSheetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let definition: AnimationSheetDefinition = ron::de::from_bytes(bytes)?;
+
+            let texture_path = AssetPath::new(definition.texture.into(), None);
+            let atlas = TextureAtlas::from_grid(
+                load_context.get_handle(texture_path.clone()),
+                Vec2::new(definition.tile_size.0, definition.tile_size.1),
+                definition.columns,
+                definition.rows,
+            );
+            load_context.set_labeled_asset(
+                "atlas",
+                LoadedAsset::new(atlas).with_dependency(texture_path),
+            );
+
+            let atlas_path = AssetPath::new_ref(load_context.path(), Some("atlas"));
+            let sheet = AnimationSheet {
+                atlas: load_context.get_handle(atlas_path),
+                clips: definition.clips,
+            };
+            load_context.set_default_asset(LoadedAsset::new(sheet));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim"]
+    }
+}
+
+/// Component playing a clip of an animation sheet on the sprite of the entity.
+#[derive(Debug)]
+pub struct Animation {
+    /// Sheet containing the clips.
+    pub sheet: Handle<AnimationSheet>,
+    /// Name of the clip playing.
+    clip: String,
+    /// Position of the current frame in the clip.
+    frame: usize,
+    /// Time spent on the current frame, in seconds.
+    elapsed: f32,
+}
+
+impl Animation {
+    /// Returns the animation playing the given clip of the sheet.
+    pub fn new(sheet: Handle<AnimationSheet>, clip: &str) -> Self {
+        Self {
+            sheet,
+            clip: clip.to_string(),
+            frame: 0,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Returns the name of the clip playing.
+    pub fn clip(&self) -> &str {
+        &self.clip
+    }
+
+    /// Plays the given clip from its first frame, unless it is already playing.
+    pub fn play(&mut self, clip: &str) {
+        if self.clip != clip {
+            self.clip = clip.to_string();
+            self.frame = 0;
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// Advances the clip by the given duration, looping at its end, and
+    /// returns the index of the frame to display.
+    pub fn advance(&mut self, clip: &Clip, delta: Duration) -> u32 {
+        if clip.frames.is_empty() {
+            return 0;
+        }
+        if clip.fps > 0.0 {
+            let frame_duration = 1.0 / clip.fps;
+            self.elapsed += delta.as_secs_f32();
+            let frames = (self.elapsed / frame_duration) as usize;
+            self.elapsed -= frames as f32 * frame_duration;
+            self.frame += frames;
+        }
+        self.frame %= clip.frames.len();
+        clip.frames[self.frame]
+    }
+}
+
+/// Displays the current frame of the animations, once their sheet is loaded,
+/// warning once about each unknown clip.
+fn animate_system(
+    clock: Res<GameClock>,
+    mut unknown_clips: Local<HashSet<String>>,
+    sheets: Res<Assets<AnimationSheet>>,
+    mut animations: Query<(
+        &mut Animation,
+        &mut TextureAtlasSprite,
+        &mut Handle<TextureAtlas>,
+    )>,
+) {
+    for (mut animation, mut sprite, mut atlas) in animations.iter_mut() {
+        let sheet = match sheets.get(&animation.sheet) {
+            Some(sheet) => sheet,
+            None => continue,
+        };
+        if *atlas != sheet.atlas {
+            *atlas = sheet.atlas.clone();
+        }
+
+        match sheet.clips.get(animation.clip()) {
+            Some(clip) => sprite.index = animation.advance(clip, clock.delta()),
+            None => {
+                if unknown_clips.insert(animation.clip().to_string()) {
+                    warn!("Unknown animation clip {}", animation.clip());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use bevy::prelude::Handle;
+
+    use super::{Animation, AnimationSheetDefinition, Clip};
+
+    #[test]
+    fn test_animation_loops_over_clip() {
+        let walk = Clip {
+            frames: vec![4, 5, 6],
+            fps: 10.0,
+        };
+        let mut animation = Animation::new(Handle::default(), "walk");
+
+        assert_eq!(animation.advance(&walk, Duration::from_millis(50)), 4);
+        assert_eq!(animation.advance(&walk, Duration::from_millis(100)), 5);
+        assert_eq!(animation.advance(&walk, Duration::from_millis(200)), 4);
+
+        animation.play("walk");
+        assert_eq!(animation.advance(&walk, Duration::from_millis(100)), 5);
+        animation.play("idle");
+        assert_eq!(animation.clip(), "idle");
+        assert_eq!(animation.advance(&walk, Duration::ZERO), 4);
+    }
+
+    #[test]
+    fn test_animation_files() {
+        for path in &[
+            "assets/animations/didi.anim",
+            "assets/animations/baobei.anim",
+        ] {
+            let content = fs::read_to_string(path).unwrap();
+            assert!(ron::from_str::<AnimationSheetDefinition>(&content).is_ok());
+        }
+    }
+}
@@ -0,0 +1,363 @@
+//! Sound effects and music, played by a swappable audio backend.
+//!
+//! The gameplay only asks the `Mixer` to play sounds, the backend plugin
+//! added to the app decides how to output them. The music track of the
+//! current screen loops and crossfades with the previous one.
+
+use std::{collections::HashMap, io::Cursor, vec::Drain};
+
+use bevy::prelude::*;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+
+use crate::{clock::GameClock, constants::GameState, settings::Settings};
+
+/// Duration of the crossfade between two music tracks, in seconds.
+const CROSSFADE_DURATION: f32 = 2.0;
+
+/// Plugin mixing the sound effects and the music of the game.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Mixer>()
+            .add_system(crossfade_music_system.system())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Menu).with_system(play_menu_music_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame).with_system(play_game_music_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame).with_system(play_menu_music_system.system()),
+            );
+    }
+}
+
+/// A sound effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    /// An item is taken in a producer.
+    Take,
+    /// An item is put away in a producer.
+    PutAway,
+    /// An item is dropped on the ground.
+    Drop,
+    /// An item is picked up from the ground.
+    PickUp,
+    /// Baobei receives the item it asked for.
+    GiveCorrect,
+    /// Baobei receives another item.
+    GiveWrong,
+    /// Didi comes in front of a producer.
+    Producer,
+    /// Baobei is getting sadder.
+    Crying,
+    /// A mess is cleaned.
+    Clean,
+    /// The last item of a producer is taken.
+    Empty,
+}
+
+impl Sound {
+    /// All the sound effects.
+    pub const ALL: [Self; 10] = [
+        Self::Take,
+        Self::PutAway,
+        Self::Drop,
+        Self::PickUp,
+        Self::GiveCorrect,
+        Self::GiveWrong,
+        Self::Producer,
+        Self::Crying,
+        Self::Clean,
+        Self::Empty,
+    ];
+
+    /// Returns the path of the sound in the assets.
+    const fn path(self) -> &'static str {
+        match self {
+            Self::Take => "sounds/take.wav",
+            Self::PutAway => "sounds/put_away.wav",
+            Self::Drop => "sounds/drop.wav",
+            Self::PickUp => "sounds/pick_up.wav",
+            Self::GiveCorrect => "sounds/give_correct.wav",
+            Self::GiveWrong => "sounds/give_wrong.wav",
+            Self::Producer => "sounds/producer.wav",
+            Self::Crying => "sounds/crying.wav",
+            Self::Clean => "sounds/clean.wav",
+            Self::Empty => "sounds/empty.wav",
+        }
+    }
+}
+
+/// A looping music track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Music {
+    /// Music of the menu screens.
+    Menu,
+    /// Music during the rounds.
+    Game,
+}
+
+impl Music {
+    /// All the music tracks.
+    pub const ALL: [Self; 2] = [Self::Menu, Self::Game];
+
+    /// Returns the path of the track in the assets.
+    const fn path(self) -> &'static str {
+        match self {
+            Self::Menu => "music/menu.wav",
+            Self::Game => "music/game.wav",
+        }
+    }
+}
+
+/// Sounds waiting to be played and fading of the music tracks.
+#[derive(Debug, Default)]
+pub struct Mixer {
+    /// Sound effects asked since the backend last played them.
+    sounds: Vec<Sound>,
+    /// Track fading in, the others fading out.
+    music: Option<Music>,
+    /// Fading of each music track, between 0 (silent) and 1.
+    fades: HashMap<Music, f32>,
+}
+
+impl Mixer {
+    /// Asks to play the sound effect.
+    pub fn play(&mut self, sound: Sound) {
+        self.sounds.push(sound);
+    }
+
+    /// Removes the sound effects waiting to be played.
+    pub fn drain_sounds(&mut self) -> Drain<'_, Sound> {
+        self.sounds.drain(..)
+    }
+
+    /// Crossfades from the current music track to the given one.
+    pub fn play_music(&mut self, music: Music) {
+        self.music = Some(music);
+    }
+
+    /// Returns the track fading in, if any.
+    pub const fn music(&self) -> Option<Music> {
+        self.music
+    }
+
+    /// Returns the volume of the music track, faded in or out.
+    pub fn music_volume(&self, music: Music, settings: &Settings) -> f32 {
+        self.fades.get(&music).copied().unwrap_or_default() * settings.music_volume
+    }
+
+    /// Fades in the current track and fades out the others by the given step.
+    fn crossfade(&mut self, step: f32) {
+        for &music in &Music::ALL {
+            let fade = self.fades.entry(music).or_default();
+            *fade = if Some(music) == self.music {
+                (*fade + step).min(1.0)
+            } else {
+                (*fade - step).max(0.0)
+            };
+        }
+    }
+}
+
+/// Plays the music of the menu screens.
+fn play_menu_music_system(mut mixer: ResMut<Mixer>) {
+    mixer.play_music(Music::Menu);
+}
+
+/// Plays the music of the rounds.
+fn play_game_music_system(mut mixer: ResMut<Mixer>) {
+    mixer.play_music(Music::Game);
+}
+
+/// Crossfades the music tracks with the time of the frame.
+fn crossfade_music_system(clock: Res<GameClock>, mut mixer: ResMut<Mixer>) {
+    mixer.crossfade(clock.delta().as_secs_f32() / CROSSFADE_DURATION);
+}
+
+/// Backend playing the sounds with rodio, the library behind Bevy audio,
+/// which can loop the music and change the volume of the playing tracks.
+pub struct RodioAudioBackendPlugin;
+
+impl Plugin for RodioAudioBackendPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<AudioHandles>()
+            .init_non_send_resource::<RodioOutput>()
+            .add_system_to_stage(CoreStage::Last, rodio_audio_backend_system.system());
+    }
+}
+
+/// Handles of the sounds and music tracks.
+struct AudioHandles {
+    /// Handles of the sound effects.
+    sounds: HashMap<Sound, Handle<AudioSource>>,
+    /// Handles of the music tracks.
+    music: HashMap<Music, Handle<AudioSource>>,
+}
+
+impl FromWorld for AudioHandles {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.get_resource::<AssetServer>().unwrap();
+
+        Self {
+            sounds: Sound::ALL
+                .iter()
+                .map(|&sound| (sound, asset_server.load(sound.path())))
+                .collect(),
+            music: Music::ALL
+                .iter()
+                .map(|&music| (music, asset_server.load(music.path())))
+                .collect(),
+        }
+    }
+}
+
+/// Output to the audio device, with a sink looping each music track.
+struct RodioOutput {
+    /// The stream to the device, kept open while the game runs, and its
+    /// handle. None when there is no audio device.
+    stream: Option<(OutputStream, OutputStreamHandle)>,
+    /// Sinks of the music tracks, started once their source is loaded.
+    music_sinks: HashMap<Music, Sink>,
+}
+
+impl Default for RodioOutput {
+    fn default() -> Self {
+        let stream = match OutputStream::try_default() {
+            Ok(stream) => Some(stream),
+            Err(error) => {
+                warn!("No audio device, the game is muted: {}", error);
+                None
+            }
+        };
+        Self {
+            stream,
+            music_sinks: HashMap::new(),
+        }
+    }
+}
+
+/// Plays the sounds asked to the mixer, and sets the volume of the music
+/// tracks to their fading and to the volumes of the settings.
+fn rodio_audio_backend_system(
+    mut output: NonSendMut<RodioOutput>,
+    handles: Res<AudioHandles>,
+    sources: Res<Assets<AudioSource>>,
+    settings: Res<Settings>,
+    mut mixer: ResMut<Mixer>,
+) {
+    let stream_handle = match &output.stream {
+        Some((_, stream_handle)) => stream_handle.clone(),
+        None => {
+            mixer.drain_sounds();
+            return;
+        }
+    };
+
+    for sound in mixer.drain_sounds() {
+        if settings.effects_volume <= 0.0 {
+            continue;
+        }
+        if let Some(source) = sources.get(&handles.sounds[&sound]) {
+            match play_source(&stream_handle, source, settings.effects_volume, false) {
+                Ok(sink) => sink.detach(),
+                Err(error) => error!("Fail to play {:?}: {}", sound, error),
+            }
+        }
+    }
+
+    for &music in &Music::ALL {
+        if !output.music_sinks.contains_key(&music) {
+            if let Some(source) = sources.get(&handles.music[&music]) {
+                match play_source(&stream_handle, source, 0.0, true) {
+                    Ok(sink) => {
+                        output.music_sinks.insert(music, sink);
+                    }
+                    Err(error) => error!("Fail to play {:?}: {}", music, error),
+                }
+            }
+        }
+        if let Some(sink) = output.music_sinks.get(&music) {
+            sink.set_volume(mixer.music_volume(music, &settings));
+        }
+    }
+}
+
+/// Returns a new sink playing the source at the given volume, once or in a
+/// loop.
+fn play_source(
+    stream_handle: &OutputStreamHandle,
+    source: &AudioSource,
+    volume: f32,
+    looping: bool,
+) -> anyhow::Result<Sink> {
+    let sink = Sink::try_new(stream_handle)?;
+    sink.set_volume(volume);
+    let decoder = Decoder::new(Cursor::new(source.clone()))?;
+    if looping {
+        sink.append(decoder.repeat_infinite());
+    } else {
+        sink.append(decoder);
+    }
+    Ok(sink)
+}
+
+/// Backend playing nothing, for headless apps. It keeps the sounds asked to
+/// the mixer in `PlayedSounds`.
+pub struct NullAudioBackendPlugin;
+
+impl Plugin for NullAudioBackendPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<PlayedSounds>()
+            .add_system_to_stage(CoreStage::Last, null_audio_backend_system.system());
+    }
+}
+
+/// Sounds "played" by the null backend.
+#[derive(Debug, Default)]
+pub struct PlayedSounds(pub Vec<Sound>);
+
+/// Records the sounds asked to the mixer without playing them.
+fn null_audio_backend_system(mut mixer: ResMut<Mixer>, mut played: ResMut<PlayedSounds>) {
+    played.0.extend(mixer.drain_sounds());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{Mixer, Music, Sound};
+    use crate::settings::Settings;
+
+    #[test]
+    fn test_sound_assets_exist() {
+        let paths = Sound::ALL
+            .iter()
+            .map(|sound| sound.path())
+            .chain(Music::ALL.iter().map(|music| music.path()));
+        for path in paths {
+            assert!(Path::new("assets").join(path).is_file(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_crossfade_music() {
+        let settings = Settings {
+            music_volume: 0.5,
+            ..Settings::default()
+        };
+        let mut mixer = Mixer::default();
+
+        mixer.play_music(Music::Menu);
+        mixer.crossfade(1.0);
+        assert!((mixer.music_volume(Music::Menu, &settings) - 0.5).abs() < 1e-6);
+        assert!(mixer.music_volume(Music::Game, &settings).abs() < 1e-6);
+
+        mixer.play_music(Music::Game);
+        mixer.crossfade(0.25);
+        assert!((mixer.music_volume(Music::Menu, &settings) - 0.375).abs() < 1e-6);
+        assert!((mixer.music_volume(Music::Game, &settings) - 0.125).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,123 @@
+//! Arguments given to the game on the command line.
+
+use std::{env, path::PathBuf, str::FromStr};
+
+use bevy::log::warn;
+
+use crate::high_scores::today;
+
+/// Environment variable giving the seed of the round, when not given as argument.
+const SEED_VARIABLE: &str = "BAOBEI_SEED";
+
+/// Arguments given to the game on the command line.
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    /// Seed of the random generator (`--seed <number>`).
+    pub seed: Option<u64>,
+    /// Whether to play the round of the day (`--daily`).
+    pub daily: bool,
+    /// File where the inputs of the first round are recorded (`--record <file>`).
+    pub record: Option<PathBuf>,
+    /// File of a recorded round to replay (`--replay <file>`).
+    pub replay: Option<PathBuf>,
+    /// Factor of the speed of the game (`--speed <factor>`).
+    pub speed: Option<f32>,
+    /// Arguments not understood, warned once the logs are set up.
+    pub unknown: Vec<String>,
+}
+
+impl Args {
+    /// Parses the arguments of the running process.
+    pub fn from_env() -> Self {
+        Self::parse(env::args().skip(1))
+    }
+
+    /// Parses the given arguments, keeping the unknown ones and the ones with
+    /// an invalid value to warn about them.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => parsed.seed = parsed.value(&arg, args.next(), |_| true),
+                "--daily" => parsed.daily = true,
+                "--record" => parsed.record = args.next().map(PathBuf::from),
+                "--replay" => parsed.replay = args.next().map(PathBuf::from),
+                "--speed" => {
+                    parsed.speed = parsed.value(&arg, args.next(), |speed: &f32| *speed > 0.0);
+                }
+                _ => parsed.unknown.push(arg),
+            }
+        }
+        parsed
+    }
+
+    /// Parses the value of the argument, keeping the argument as not
+    /// understood when its value is missing or invalid.
+    fn value<T: FromStr>(
+        &mut self,
+        arg: &str,
+        value: Option<String>,
+        valid: impl Fn(&T) -> bool,
+    ) -> Option<T> {
+        let parsed = value
+            .as_deref()
+            .and_then(|value| value.parse().ok())
+            .filter(valid);
+        if parsed.is_none() {
+            self.unknown.push(match value {
+                Some(value) => format!("{} {}", arg, value),
+                None => arg.to_string(),
+            });
+        }
+        parsed
+    }
+
+    /// Returns the seed of the round, by order of priority: the `--seed`
+    /// argument, the seed of the day, the `BAOBEI_SEED` variable, the seed of
+    /// the settings or a random one.
+    pub fn round_seed(&self, settings_seed: Option<u64>) -> u64 {
+        self.seed
+            .or_else(|| self.daily.then(today))
+            .or_else(|| env::var(SEED_VARIABLE).ok()?.parse().ok())
+            .or(settings_seed)
+            .unwrap_or_else(rand::random)
+    }
+
+    /// Warns about the arguments not understood.
+    pub fn warn_unknown(&self) {
+        for arg in &self.unknown {
+            warn!("Argument not understood: {}", arg);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Args;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::parse(args.iter().map(|arg| (*arg).to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]), Args::default());
+        assert_eq!(parse(&["--seed", "42"]).seed, Some(42));
+        assert_eq!(parse(&["--seed", "abc"]).seed, None);
+        assert_eq!(parse(&["--seed", "abc"]).unknown, vec!["--seed abc"]);
+        assert_eq!(parse(&["--seed"]).unknown, vec!["--seed"]);
+        assert_eq!(parse(&["--daily"]).daily, true);
+        assert_eq!(parse(&["--daily", "--seed", "42"]).round_seed(None), 42);
+        assert_eq!(parse(&["--seed", "42"]).round_seed(Some(7)), 42);
+        assert_eq!(parse(&["--play", "fast"]).unknown, vec!["--play", "fast"]);
+        assert_eq!(
+            parse(&["--record", "round.replay"]).record,
+            Some("round.replay".into())
+        );
+        assert_eq!(parse(&["--speed", "0.5"]).speed, Some(0.5));
+        assert_eq!(parse(&["--speed", "-1"]).speed, None);
+        assert_eq!(parse(&["--speed", "-1"]).unknown, vec!["--speed -1"]);
+    }
+}
@@ -0,0 +1,142 @@
+//! Clock driving the gameplay simulation with a fixed timestep.
+//!
+//! The gameplay runs in the `FixedUpdateStage`, repeated as many times as
+//! there are whole steps in the time elapsed, so that the simulation gives the
+//! same results whatever the frame rate.
+
+use std::time::Duration;
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+
+use crate::constants::GameState;
+
+/// Duration of a simulation step (60 steps per second).
+pub const STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Maximum number of steps simulated in a frame. The time beyond is dropped,
+/// slowing down the game rather than freezing it after a stall or at a high
+/// time scale.
+const MAX_STEPS_PER_FRAME: u32 = 10;
+
+/// Label for clock systems
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct ClockSystems;
+
+/// Stage running the gameplay simulation once per step.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub struct FixedUpdateStage;
+
+/// Plugin advancing the `GameClock` and running the fixed update stage.
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<GameClock>()
+            .add_stage_after(
+                CoreStage::Update,
+                FixedUpdateStage,
+                SystemStage::parallel().with_run_criteria(fixed_step_criteria.system()),
+            )
+            // Game states also drive the systems of the fixed stage.
+            .add_system_set_to_stage(FixedUpdateStage, State::<GameState>::get_driver())
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                sync_clock_system.system().label(ClockSystems),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame).with_system(reset_clock_system.system()),
+            );
+    }
+}
+
+/// Time of the game, advanced once per frame and consumed by steps.
+#[derive(Debug)]
+pub struct GameClock {
+    /// Time elapsed during the last frame.
+    delta: Duration,
+    /// Time elapsed but not simulated yet.
+    accumulator: Duration,
+    /// Whether the clock follows the real time or is advanced manually.
+    real_time: bool,
+    /// Factor applied to the real time, to speed up or slow down the game.
+    time_scale: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            delta: Duration::default(),
+            accumulator: Duration::default(),
+            real_time: true,
+            time_scale: 1.0,
+        }
+    }
+}
+
+impl GameClock {
+    /// Creates a clock following the real time scaled by the given factor.
+    pub fn with_time_scale(time_scale: f32) -> Self {
+        Self {
+            time_scale,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a clock only advanced by `advance`, such as in tests.
+    #[cfg(test)]
+    pub fn manual() -> Self {
+        Self {
+            real_time: false,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the duration of the current frame.
+    pub fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+    }
+
+    /// Returns the time elapsed during the last frame.
+    pub const fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Returns the fraction of a step elapsed since the last simulated step,
+    /// used to interpolate the rendering between two steps.
+    pub fn overstep(&self) -> f32 {
+        self.accumulator.as_secs_f32() / STEP.as_secs_f32()
+    }
+}
+
+/// Advances the game clock with the real time elapsed since the last frame,
+/// scaled by the time scale of the clock.
+fn sync_clock_system(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    if clock.real_time {
+        let delta = time.delta().mul_f32(clock.time_scale);
+        clock.advance(delta);
+    }
+}
+
+/// Drops the time left from before the round, so that the steps of a round
+/// are the same when it is replayed.
+fn reset_clock_system(mut clock: ResMut<GameClock>) {
+    clock.accumulator = Duration::default();
+}
+
+/// Runs the fixed update stage once per whole step in the accumulated time.
+fn fixed_step_criteria(mut clock: ResMut<GameClock>, mut stepping: Local<bool>) -> ShouldRun {
+    if !*stepping {
+        // First check of the frame
+        let accumulator = clock.accumulator + clock.delta;
+        clock.accumulator = accumulator.min(STEP * MAX_STEPS_PER_FRAME);
+        *stepping = true;
+    }
+
+    if clock.accumulator >= STEP {
+        clock.accumulator -= STEP;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *stepping = false;
+        ShouldRun::No
+    }
+}
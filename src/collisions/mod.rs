@@ -10,7 +10,7 @@ use std::{
 use bevy::{prelude::*, sprite::collide_aabb::collide};
 use debug_collisions::DebugCollisionPlugin;
 
-use crate::constants::GameState;
+use crate::{clock::FixedUpdateStage, constants::GameState};
 
 mod debug_collisions;
 
@@ -26,7 +26,9 @@ impl Plugin for CollisionPlugin {
         app.add_event::<ContactEvent>()
             .register_type::<Position>()
             .register_type::<BoxCollider>()
-            .add_system_set(
+            .register_type::<TriggerArea>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
                 SystemSet::on_update(GameState::InGame)
                     .label(CollisionSystems)
                     .with_system(collision_system.system())
@@ -40,7 +42,7 @@ impl Plugin for CollisionPlugin {
 }
 
 /// Absolute position of the entity.
-#[derive(Clone, Copy, Debug, Default, Reflect)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
 #[reflect(Component)]
 pub struct Position(pub Vec3);
 
@@ -70,7 +72,7 @@ impl BoxCollider {
 }
 
 /// A rectangle area that can be contacted without collision.
-#[derive(Clone, Debug, Default, Reflect)]
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
 #[reflect(Component)]
 pub struct TriggerArea {
     /// The width and height of the box.
@@ -8,8 +8,8 @@ pub const WINDOW_HEIGHT: f32 = 720.0;
 /// Movement speed of the player
 pub const SPEED: f32 = 750.0;
 
-/// Happiness decrease per second
-pub const HAPPINESS_DECREASE: f32 = 0.05; // 5%
+/// Name of the level, the only one for now
+pub const LEVEL_NAME: &str = "Living room";
 
 /// States of the game
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -18,4 +18,10 @@ pub enum GameState {
     Menu,
     /// The game phase
     InGame,
+    /// The settings screen
+    Settings,
+    /// The end of a round, when Baobei is too sad
+    RoundOver,
+    /// The table of the best scores
+    HighScores,
 }
@@ -1,6 +1,11 @@
 //! Manages game controllers such as Keyboard and Gamepad
 
-use bevy::{prelude::*, utils::HashSet};
+use bevy::{ecs::schedule::ShouldRun, prelude::*, utils::HashSet};
+
+use crate::clock::{FixedUpdateStage, STEP};
+
+/// Seconds the interact button must be held for a hold rather than a tap.
+pub const HOLD_THRESHOLD: f32 = 0.25;
 
 /// Label for controller systems
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
@@ -13,17 +18,159 @@ impl Plugin for ControllerPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_event::<DirectionEvent>()
             .init_resource::<GamepadLobby>()
+            .init_resource::<InteractInput>()
+            .init_resource::<InteractAction>()
+            .init_resource::<InputSource>()
+            .init_resource::<InputDevice>()
+            .add_system(connection_system.system())
             .add_system_set(
                 SystemSet::new()
                     .label(ControllerSystems)
-                    .with_system(connection_system.system())
+                    .with_run_criteria(live_input_criteria.system())
                     .with_system(keyboard_system.system())
-                    .with_system(gamepad_system.system()),
+                    .with_system(gamepad_system.system())
+                    .with_system(interact_system.system())
+                    .with_system(input_device_system.system()),
+            )
+            .add_system_to_stage(
+                FixedUpdateStage,
+                interact_action_system.system().label(ControllerSystems),
             );
     }
 }
 
+/// Where the inputs of the game come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputSource {
+    /// Inputs come from the keyboard and gamepads.
+    Live,
+    /// Inputs are fed by another plugin, such as a replay.
+    Scripted,
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        Self::Live
+    }
+}
+
+/// Kind of device the player used last, to show its buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputDevice {
+    /// The keyboard.
+    Keyboard,
+    /// A gamepad.
+    Gamepad,
+}
+
+impl Default for InputDevice {
+    fn default() -> Self {
+        Self::Keyboard
+    }
+}
+
+/// Runs the controller systems only when inputs come from live devices.
+fn live_input_criteria(source: Res<InputSource>) -> ShouldRun {
+    if *source == InputSource::Live {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// State of the interact and cycle buttons, resolved from all controllers
+/// each frame.
+#[derive(Debug, Default)]
+pub struct InteractInput {
+    /// The interact button.
+    pub interact: ButtonLatch,
+    /// The button cycling the inventory slots.
+    pub cycle: ButtonLatch,
+}
+
+/// A button resolved each frame. The steps of the simulation can be several
+/// frames apart, so its presses and releases are kept until a step sees them.
+#[derive(Debug, Default)]
+pub struct ButtonLatch {
+    /// Whether the button is pressed, set with `set_pressed`.
+    pub pressed: bool,
+    /// Whether the button was pressed since the last step.
+    just_pressed: bool,
+    /// Whether the button was released since the last step.
+    just_released: bool,
+}
+
+impl ButtonLatch {
+    /// Sets whether the interact button is pressed, keeping the press or
+    /// release until the next step.
+    pub fn set_pressed(&mut self, pressed: bool) {
+        if pressed && !self.pressed {
+            self.just_pressed = true;
+        } else if !pressed && self.pressed {
+            self.just_released = true;
+        }
+        self.pressed = pressed;
+    }
+
+    /// Returns whether the button is pressed at the step, given whether it
+    /// was at the previous step. A press or release since the previous step
+    /// is seen at this step even if the button is back in its state, and
+    /// is consumed.
+    pub fn pressed_at_step(&mut self, was_pressed: bool) -> bool {
+        if was_pressed && self.just_released {
+            self.just_released = false;
+            false
+        } else if !was_pressed && self.just_pressed {
+            self.just_pressed = false;
+            true
+        } else {
+            self.just_pressed = false;
+            self.just_released = false;
+            self.pressed
+        }
+    }
+}
+
+/// The interact button as seen by the steps of the simulation: when it is
+/// pressed and released, and for how long it is held.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InteractAction {
+    /// Whether the button is pressed.
+    pub pressed: bool,
+    /// Whether the button was pressed at this step.
+    pub just_pressed: bool,
+    /// Whether the button was released at this step.
+    pub just_released: bool,
+    /// Seconds the button is held, or was held when just released.
+    pub held: f32,
+}
+
+impl InteractAction {
+    /// Advances the action by a step, with the state of the button.
+    pub fn update(&mut self, pressed: bool, delta: f32) {
+        self.just_pressed = pressed && !self.pressed;
+        self.just_released = !pressed && self.pressed;
+        if pressed {
+            self.held = if self.just_pressed { 0.0 } else { self.held } + delta;
+        } else if !self.just_released {
+            self.held = 0.0;
+        }
+        self.pressed = pressed;
+    }
+
+    /// Returns true if the button was just released after a short press.
+    pub fn tapped(&self) -> bool {
+        self.just_released && self.held < HOLD_THRESHOLD
+    }
+
+    /// Returns true if the button is pressed for longer than a tap.
+    pub fn is_held(&self) -> bool {
+        self.pressed && self.held >= HOLD_THRESHOLD
+    }
+}
+
 /// An event triggered when a controller choose a direction.
+#[derive(Debug, Clone, Copy)]
 pub struct DirectionEvent {
     /// Direction vector normalized to length 1.
     pub direction: Vec3,
@@ -55,6 +202,54 @@ fn keyboard_system(
     }
 }
 
+/// Updates the interact input from the `Space` key and the gamepads `South`
+/// button, and the cycle input from the `Tab` key and the gamepads `West` button.
+fn interact_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    lobby: Res<GamepadLobby>,
+    buttons: Res<Input<GamepadButton>>,
+    mut interact: ResMut<InteractInput>,
+) {
+    let gamepad_pressed = |button_type| {
+        lobby
+            .gamepads
+            .iter()
+            .any(|gamepad| buttons.pressed(GamepadButton(*gamepad, button_type)))
+    };
+
+    interact.interact.set_pressed(
+        keyboard_input.pressed(KeyCode::Space) || gamepad_pressed(GamepadButtonType::South),
+    );
+    interact.cycle.set_pressed(
+        keyboard_input.pressed(KeyCode::Tab) || gamepad_pressed(GamepadButtonType::West),
+    );
+}
+
+/// Switches the input device to the last one with a pressed button.
+fn input_device_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut device: ResMut<InputDevice>,
+) {
+    let used = if buttons.get_just_pressed().next().is_some() {
+        InputDevice::Gamepad
+    } else if keyboard_input.get_just_pressed().next().is_some() {
+        InputDevice::Keyboard
+    } else {
+        return;
+    };
+    if *device != used {
+        *device = used;
+    }
+}
+
+/// Advances the interact action at each step of the simulation, with the
+/// presses and releases of the frames since the previous step.
+fn interact_action_system(mut interact: ResMut<InteractInput>, mut action: ResMut<InteractAction>) {
+    let pressed = interact.interact.pressed_at_step(action.pressed);
+    action.update(pressed, STEP.as_secs_f32());
+}
+
 /// Lobby containing connected gamepads.
 #[derive(Default)]
 struct GamepadLobby {
@@ -104,3 +299,52 @@ fn gamepad_system(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ButtonLatch, InteractAction, HOLD_THRESHOLD};
+
+    #[test]
+    fn test_interact_action() {
+        let mut action = InteractAction::default();
+        action.update(true, 0.1);
+        assert!(action.just_pressed);
+        assert!(!action.is_held());
+        action.update(false, 0.1);
+        assert!(action.just_released);
+        assert!(action.tapped());
+        action.update(false, 0.1);
+        assert!(!action.just_released);
+        assert!(!action.tapped());
+
+        action.update(true, 0.1);
+        action.update(true, HOLD_THRESHOLD);
+        assert!(!action.just_pressed);
+        assert!(action.is_held());
+        action.update(false, 0.1);
+        assert!(action.just_released);
+        assert!(!action.tapped());
+        assert!((action.held - 0.1 - HOLD_THRESHOLD).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_button_latch_between_steps() {
+        let mut input = ButtonLatch::default();
+
+        // Pressed and released between two steps: pressed, then released.
+        input.set_pressed(true);
+        input.set_pressed(false);
+        assert!(input.pressed_at_step(false));
+        assert!(!input.pressed_at_step(true));
+        assert!(!input.pressed_at_step(false));
+
+        // Released and pressed again between two steps.
+        input.set_pressed(true);
+        assert!(input.pressed_at_step(false));
+        input.set_pressed(false);
+        input.set_pressed(true);
+        assert!(!input.pressed_at_step(true));
+        assert!(input.pressed_at_step(false));
+        assert!(input.pressed_at_step(true));
+    }
+}
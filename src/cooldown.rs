@@ -1,9 +1,44 @@
 //! Manage cooldown timers.
+//!
+//! The `Cooldown` components and the resources added with
+//! `insert_cooldown_resource` are ticked at each step of the simulation
+//! while in game, so they follow the time scale of the `GameClock` and wait
+//! while the round is left.
+
+use std::any::type_name;
 
 use bevy::prelude::*;
 
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    constants::GameState,
+};
+
+/// Label for the systems ticking the cooldowns
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct CooldownSystems;
+
+/// Plugin ticking the cooldown components.
+pub struct CooldownPlugin;
+
+impl Plugin for CooldownPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<CooldownFinishedEvent>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
+                    .label(CooldownSystems)
+                    .with_system(tick_cooldowns_system.system()),
+            );
+    }
+}
+
 /// TODO: Use Bevy cooldown => <https://github.com/bevyengine/bevy/issues/1127>
-#[derive(Clone, Debug, Default, Reflect)]
+///
+/// Only the remaining time, the duration and the availability are saved: the
+/// other fields are set up by the systems spawning the cooldown, or only last
+/// until the next tick.
+#[derive(Clone, Debug, Default, PartialEq, Reflect)]
 #[reflect(Component)]
 pub struct Cooldown {
     /// Remaining time until available.
@@ -12,6 +47,15 @@ pub struct Cooldown {
     duration: f32,
     /// Whether or not the cooldown is available, ie. not in progress.
     available: bool,
+    /// Whether the cooldown finished at the last tick.
+    #[reflect(ignore)]
+    just_finished: bool,
+    /// Whether the cooldown starts again each time it finishes.
+    #[reflect(ignore)]
+    repeating: bool,
+    /// Cooldown started when this one finishes, if any.
+    #[reflect(ignore)]
+    next: Option<Box<Cooldown>>,
 }
 
 impl Cooldown {
@@ -21,13 +65,34 @@ impl Cooldown {
             duration: seconds,
             available: true,
             remaining: 0.0,
+            just_finished: false,
+            repeating: false,
+            next: None,
         }
     }
 
+    /// Makes the cooldown start again each time it finishes, starting it.
+    pub fn repeating(mut self) -> Self {
+        self.repeating = true;
+        self.start();
+        self
+    }
+
+    /// Chains the next cooldown, started when this one and the cooldowns
+    /// already chained finish.
+    pub fn then(mut self, next: Self) -> Self {
+        self.next = Some(Box::new(match self.next.take() {
+            Some(chained) => chained.then(next),
+            None => next,
+        }));
+        self
+    }
+
     /// Starts the cooldown, making it unavailable for the given duration.
     #[inline]
     pub fn start(&mut self) {
         self.available = false;
+        self.just_finished = false;
         self.remaining = self.duration;
     }
 
@@ -37,8 +102,49 @@ impl Cooldown {
         self.available
     }
 
+    /// Returns true if the cooldown finished at the last tick.
+    #[inline]
+    pub const fn just_finished(&self) -> bool {
+        self.just_finished
+    }
+
+    /// Returns the fraction of the cooldown remaining, from 1 when started
+    /// to 0 when available.
+    pub fn fraction_remaining(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.remaining / self.duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Returns the seconds elapsed since the cooldown started.
+    pub fn elapsed(&self) -> f32 {
+        if self.available {
+            0.0
+        } else {
+            (self.duration - self.remaining).max(0.0)
+        }
+    }
+
+    /// Starts the cooldown as if it started `seconds` ago.
+    pub fn set_elapsed(&mut self, seconds: f32) {
+        self.available = false;
+        self.remaining = (self.duration - seconds).max(0.0);
+    }
+
+    /// Changes the duration of the cooldown, keeping the time elapsed.
+    pub fn set_duration(&mut self, seconds: f32) {
+        let elapsed = self.elapsed();
+        self.duration = seconds;
+        if !self.available {
+            self.set_elapsed(elapsed);
+        }
+    }
+
     /// Advances the cooldown by `delta` seconds.
     pub fn tick(&mut self, delta: f32) -> &Self {
+        self.just_finished = false;
         if self.available {
             return self;
         }
@@ -46,13 +152,82 @@ impl Cooldown {
         self.remaining -= delta;
 
         if self.remaining <= 0.0 {
-            self.remaining = 0.0;
-            self.available = true;
+            if let Some(next) = self.next.take() {
+                *self = *next;
+                self.start();
+            } else if self.repeating {
+                self.remaining = (self.remaining + self.duration).max(0.0);
+            } else {
+                self.remaining = 0.0;
+                self.available = true;
+            }
+            self.just_finished = true;
         }
         self
     }
 }
 
+/// An event sent when a cooldown ticked automatically finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CooldownFinishedEvent {
+    /// The cooldown component of the entity finished.
+    Component(Entity),
+    /// The cooldown of the resource, of the given type, finished.
+    Resource(&'static str),
+}
+
+/// A resource holding a cooldown.
+pub trait CooldownResource: Send + Sync + 'static {
+    /// Returns the cooldown of the resource.
+    fn cooldown_mut(&mut self) -> &mut Cooldown;
+}
+
+/// Extension of the app builder to add the cooldown resources.
+pub trait CooldownAppExt {
+    /// Inserts the resource, with a system ticking its cooldown.
+    fn insert_cooldown_resource<R: CooldownResource>(&mut self, resource: R) -> &mut Self;
+}
+
+impl CooldownAppExt for AppBuilder {
+    fn insert_cooldown_resource<R: CooldownResource>(&mut self, resource: R) -> &mut Self {
+        self.insert_resource(resource).add_system_set_to_stage(
+            FixedUpdateStage,
+            SystemSet::on_update(GameState::InGame)
+                .label(CooldownSystems)
+                .with_system(tick_cooldown_resource_system::<R>.system()),
+        )
+    }
+}
+
+/// Ticks the cooldown components, sending an event when they finish.
+fn tick_cooldowns_system(
+    mut cooldowns: Query<(Entity, &mut Cooldown)>,
+    mut finished_events: EventWriter<CooldownFinishedEvent>,
+) {
+    for (entity, mut cooldown) in cooldowns.iter_mut() {
+        if cooldown.available() && !cooldown.just_finished() {
+            continue; // Avoid to trigger the change detection.
+        }
+        if cooldown.tick(STEP.as_secs_f32()).just_finished() {
+            finished_events.send(CooldownFinishedEvent::Component(entity));
+        }
+    }
+}
+
+/// Ticks the cooldown of the resource, sending an event when it finishes.
+fn tick_cooldown_resource_system<R: CooldownResource>(
+    mut resource: ResMut<R>,
+    mut finished_events: EventWriter<CooldownFinishedEvent>,
+) {
+    if resource
+        .cooldown_mut()
+        .tick(STEP.as_secs_f32())
+        .just_finished()
+    {
+        finished_events.send(CooldownFinishedEvent::Resource(type_name::<R>()));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Cooldown;
@@ -76,4 +251,58 @@ mod tests {
         assert_eq!(cd.tick(0.75).available(), false);
         assert_eq!(cd.tick(0.75).available(), true);
     }
+
+    #[test]
+    fn test_cooldown_just_finished() {
+        let mut cd = Cooldown::from_seconds(2.0);
+        cd.start();
+        assert_eq!(cd.tick(0.75).just_finished(), false);
+        assert_eq!(cd.tick(1.5).just_finished(), true);
+        assert_eq!(cd.tick(10.0).just_finished(), false);
+    }
+
+    #[test]
+    fn test_cooldown_fraction_remaining() {
+        let mut cd = Cooldown::from_seconds(2.0);
+        cd.start();
+        assert!((cd.tick(0.75).fraction_remaining() - 0.625).abs() < 1e-6);
+        assert!(cd.tick(1.5).fraction_remaining().abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_repeating_cooldown() {
+        let mut cd = Cooldown::from_seconds(1.0).repeating();
+        assert_eq!(cd.tick(0.5).just_finished(), false);
+        assert_eq!(cd.tick(0.75).just_finished(), true);
+        assert_eq!(cd.available(), false);
+        assert!((cd.fraction_remaining() - 0.75).abs() < 1e-6);
+        assert_eq!(cd.tick(0.75).just_finished(), true);
+    }
+
+    #[test]
+    fn test_cooldown_elapsed() {
+        let mut cd = Cooldown::from_seconds(4.0).repeating();
+        cd.tick(1.0);
+        assert!((cd.elapsed() - 1.0).abs() < 1e-6);
+        cd.set_duration(2.0);
+        assert!((cd.fraction_remaining() - 0.5).abs() < 1e-6);
+        cd.set_elapsed(1.5);
+        assert_eq!(cd.tick(0.5).just_finished(), true);
+        assert!(cd.elapsed().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_chained_cooldowns() {
+        let mut cd = Cooldown::from_seconds(1.0)
+            .then(Cooldown::from_seconds(2.0))
+            .then(Cooldown::from_seconds(0.5).repeating());
+        cd.start();
+        assert_eq!(cd.tick(1.0).just_finished(), true);
+        assert_eq!(cd.available(), false);
+        assert_eq!(cd.tick(1.5).just_finished(), false);
+        assert_eq!(cd.tick(0.5).just_finished(), true);
+        assert_eq!(cd.tick(0.5).just_finished(), true);
+        assert_eq!(cd.tick(0.5).just_finished(), true);
+        assert_eq!(cd.available(), false);
+    }
 }
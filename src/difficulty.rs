@@ -0,0 +1,181 @@
+//! Difficulty presets, configuring how demanding Baobei is.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// Plugin keeping the difficulty configuration in sync with the settings.
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let config = app
+            .world()
+            .get_resource::<Settings>()
+            .map(|settings| DifficultyConfig::new(settings.difficulty, settings.difficulty_ramp))
+            .unwrap_or_default();
+
+        app.insert_resource(config)
+            .add_system(update_difficulty_system.system());
+    }
+}
+
+/// Difficulty of the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// Baobei is patient.
+    Easy,
+    /// The default difficulty.
+    Normal,
+    /// Baobei is demanding.
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns the next difficulty.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Easy,
+        }
+    }
+
+    /// Returns the name of the difficulty.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Normal => "Normal",
+            Self::Hard => "Hard",
+        }
+    }
+}
+
+/// Parameters of the gameplay depending on the difficulty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyConfig {
+    /// Happiness decrease per second at the start of the round.
+    pub happiness_decrease: f32,
+    /// Happiness gained when giving the asked item.
+    pub reward: f32,
+    /// Happiness lost when giving another item.
+    pub penalty: f32,
+    /// Seconds before Baobei changes its mind and asks for another item.
+    pub request_interval: f32,
+    /// Happiness lost when Baobei runs out of patience for its request.
+    pub patience_penalty: f32,
+    /// Additional happiness gained when giving the asked item right after
+    /// the request, decreasing to nothing at half of the patience.
+    pub early_bonus: f32,
+    /// Number of different produced items Baobei can ask for, on top of the
+    /// crafted ones.
+    pub item_count: usize,
+    /// Default number of items Didi can carry, including the one in hand,
+    /// before the upgrades of the inventory.
+    pub inventory_slots: usize,
+    /// Increase of the happiness decrease per minute of the round, as a
+    /// fraction of the initial decrease.
+    pub ramp: f32,
+}
+
+impl DifficultyConfig {
+    /// Returns the preset of the difficulty, with or without the ramp.
+    pub fn new(difficulty: Difficulty, ramp_enabled: bool) -> Self {
+        let preset = match difficulty {
+            Difficulty::Easy => Self {
+                happiness_decrease: 0.02,
+                reward: 0.2,
+                penalty: 0.1,
+                request_interval: 30.0,
+                patience_penalty: 0.1,
+                early_bonus: 0.1,
+                item_count: 2,
+                inventory_slots: 3,
+                ramp: 0.25,
+            },
+            Difficulty::Normal => Self {
+                happiness_decrease: 0.05,
+                reward: 0.15,
+                penalty: 0.15,
+                request_interval: 20.0,
+                patience_penalty: 0.15,
+                early_bonus: 0.1,
+                item_count: 3,
+                inventory_slots: 2,
+                ramp: 0.5,
+            },
+            Difficulty::Hard => Self {
+                happiness_decrease: 0.08,
+                reward: 0.1,
+                penalty: 0.2,
+                request_interval: 12.0,
+                patience_penalty: 0.2,
+                early_bonus: 0.05,
+                item_count: 3,
+                inventory_slots: 2,
+                ramp: 1.0,
+            },
+        };
+
+        if ramp_enabled {
+            preset
+        } else {
+            Self {
+                ramp: 0.0,
+                ..preset
+            }
+        }
+    }
+
+    /// Returns the bonus for giving the asked item after the given fraction
+    /// of the patience of Baobei.
+    pub fn early_bonus(&self, patience_spent: f32) -> f32 {
+        self.early_bonus * (1.0 - 2.0 * patience_spent).max(0.0)
+    }
+
+    /// Returns the happiness decrease per second after the given time spent
+    /// in the round.
+    pub fn happiness_decrease(&self, time_survived: f32) -> f32 {
+        self.happiness_decrease * (1.0 + self.ramp * time_survived / 60.0)
+    }
+}
+
+impl Default for DifficultyConfig {
+    fn default() -> Self {
+        Self::new(Difficulty::Normal, false)
+    }
+}
+
+/// Applies the difficulty chosen in the settings.
+fn update_difficulty_system(settings: Res<Settings>, mut config: ResMut<DifficultyConfig>) {
+    if settings.is_changed() {
+        let new_config = DifficultyConfig::new(settings.difficulty, settings.difficulty_ramp);
+        if *config != new_config {
+            *config = new_config;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Difficulty, DifficultyConfig};
+
+    #[test]
+    fn test_difficulty_ramp() {
+        let config = DifficultyConfig::new(Difficulty::Normal, true);
+        assert!((config.happiness_decrease(0.0) - 0.05).abs() < 1e-6);
+        assert!((config.happiness_decrease(120.0) - 0.1).abs() < 1e-6);
+
+        let without_ramp = DifficultyConfig::new(Difficulty::Normal, false);
+        assert!((without_ramp.happiness_decrease(120.0) - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_early_bonus() {
+        let config = DifficultyConfig::new(Difficulty::Normal, false);
+        assert!((config.early_bonus(0.0) - 0.1).abs() < 1e-6);
+        assert!((config.early_bonus(0.25) - 0.05).abs() < 1e-6);
+        assert!(config.early_bonus(0.75).abs() < 1e-6);
+    }
+}
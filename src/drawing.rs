@@ -1,9 +1,15 @@
 //! Systems and functions managing the display of things in the screen.
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::camera::OrthographicProjection,
+    transform::TransformSystem,
+    window::{WindowCreated, WindowId, WindowResized},
+};
 
 use crate::{
-    collisions::{CollisionSystems, Position},
+    clock::{FixedUpdateStage, GameClock},
+    collisions::{CollisionSystems, Movement, Position},
     constants::{WINDOW_HEIGHT, WINDOW_WIDTH},
 };
 
@@ -12,15 +18,62 @@ pub struct DrawingPlugin;
 
 impl Plugin for DrawingPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system_set(
+        app.add_system_to_stage(
+            FixedUpdateStage,
+            store_previous_position_system
+                .system()
+                .before(CollisionSystems),
+        )
+        .add_system(fit_play_area_system.system())
+        .add_system_set_to_stage(
+            CoreStage::PostUpdate,
             SystemSet::new()
+                .with_system(add_previous_position_system.system())
                 .with_system(update_game_object_position_system.system())
+                .with_system(interpolate_moving_objects_system.system())
                 .with_system(update_ui_objects_position_system.system())
-                .after(CollisionSystems),
+                .before(TransformSystem::TransformPropagate),
         );
     }
 }
 
+/// Component tagging the camera displaying the play area.
+pub struct GameCamera;
+
+/// Scales the game camera so that the whole play area fits in the window.
+///
+/// The play area keeps its aspect ratio, leaving black bars on the sides
+/// when the window has a different one.
+fn fit_play_area_system(
+    mut resized_events: EventReader<WindowResized>,
+    mut created_events: EventReader<WindowCreated>,
+    windows: Res<Windows>,
+    mut cameras: Query<&mut OrthographicProjection, With<GameCamera>>,
+) {
+    let primary_changed = resized_events
+        .iter()
+        .map(|event| event.id)
+        .chain(created_events.iter().map(|event| event.id))
+        .any(|id| id == WindowId::primary());
+
+    if !primary_changed {
+        return;
+    }
+    if let Some(window) = windows.get_primary() {
+        let scale = play_area_scale(window.width(), window.height());
+
+        for mut projection in cameras.iter_mut() {
+            projection.scale = scale;
+        }
+    }
+}
+
+/// Returns the scale of the camera for the play area to fit in a window of
+/// the given size.
+fn play_area_scale(width: f32, height: f32) -> f32 {
+    (WINDOW_WIDTH / width).max(WINDOW_HEIGHT / height)
+}
+
 /// Component meaning that the entity will be drawn in the foreground as a UI object.
 pub struct UiObject;
 
@@ -28,34 +81,74 @@ pub struct UiObject;
 /// z = 0 => background, z = 1000 => foreground
 const Z_LIMIT: f32 = 1000.0;
 
+/// Position of a moving entity at the previous simulation step.
+///
+/// The transform of moving entities is interpolated between their previous
+/// and current positions, so that they move smoothly whatever the frame rate.
+pub struct PreviousPosition(Vec3);
+
+/// Query filter for moving entities without a previous position yet
+type NewMovingObject = (With<Movement>, Without<PreviousPosition>);
+
+/// Adds the previous position to moving entities.
+fn add_previous_position_system(
+    mut commands: Commands,
+    moving_objects: Query<(Entity, &Position), NewMovingObject>,
+) {
+    for (entity, position) in moving_objects.iter() {
+        commands.entity(entity).insert(PreviousPosition(position.0));
+    }
+}
+
+/// Stores the position of moving entities before the step moves them.
+fn store_previous_position_system(mut moving_objects: Query<(&Position, &mut PreviousPosition)>) {
+    for (position, mut previous) in moving_objects.iter_mut() {
+        previous.0 = position.0;
+    }
+}
+
 /// Query filter for game entities that are moved
-type MovedGameObject = (Without<(Parent, UiObject)>, Changed<Position>);
+type MovedGameObject = (
+    Without<(Parent, UiObject)>,
+    Without<PreviousPosition>,
+    Changed<Position>,
+);
 
 /// Updates transform of game objects following their game position.
-///
-/// TODO: Scales the translation and sprite scale with the window size.
-/// This needs the scale as a component and center positions in the middle
-/// of the window
 fn update_game_object_position_system(
-    // windows: Res<Windows>,
     mut game_objects: Query<(&Position, &mut Transform), MovedGameObject>,
 ) {
-    // TODO: scale depending on window size
-    // let window = windows.get_primary().unwrap();
-    // dbg!(window.width(), window.height());
-    // window_size_ratio(window);
-
     for (position, mut transform) in game_objects.iter_mut() {
-        transform.translation = position.0;
+        transform.translation = game_object_translation(position.0);
+    }
+}
 
-        // Scale the z index depending on the y index.
-        transform.translation.z = Z_LIMIT - position.0.y * Z_LIMIT / WINDOW_HEIGHT;
+/// Updates transform of moving objects, interpolated between their previous
+/// and current positions depending on the time elapsed since the last step.
+fn interpolate_moving_objects_system(
+    clock: Res<GameClock>,
+    mut moving_objects: Query<(&Position, &PreviousPosition, &mut Transform)>,
+) {
+    let overstep = clock.overstep();
 
-        // Move up the entities in the air.
-        transform.translation.y += position.0.z;
+    for (position, previous, mut transform) in moving_objects.iter_mut() {
+        let interpolated = previous.0.lerp(position.0, overstep);
+        transform.translation = game_object_translation(interpolated);
     }
 }
 
+/// Returns the translation of a game object at the given position.
+fn game_object_translation(position: Vec3) -> Vec3 {
+    let mut translation = position;
+
+    // Scale the z index depending on the y index.
+    translation.z = Z_LIMIT - position.y * Z_LIMIT / WINDOW_HEIGHT;
+
+    // Move up the entities in the air.
+    translation.y += position.z;
+    translation
+}
+
 /// Query filter for UI entities that are moved
 type MovedUiObject = (With<UiObject>, Changed<Position>);
 
@@ -69,11 +162,17 @@ fn update_ui_objects_position_system(
     }
 }
 
-/// Returns the ratio between the current size and the initial one.
-fn _window_size_ratio(window: Window) -> Vec3 {
-    Vec3::new(
-        window.width() / WINDOW_WIDTH,
-        window.height() / WINDOW_HEIGHT,
-        1.0,
-    )
+#[cfg(test)]
+mod tests {
+    use super::play_area_scale;
+
+    #[test]
+    fn test_play_area_scale() {
+        let scale = |width, height| (play_area_scale(width, height) * 100.0).round() / 100.0;
+
+        assert_eq!(scale(1280.0, 720.0), 1.0);
+        assert_eq!(scale(2560.0, 1440.0), 0.5);
+        assert_eq!(scale(640.0, 720.0), 2.0); // Bars at the top and bottom
+        assert_eq!(scale(1920.0, 360.0), 2.0); // Bars on the sides
+    }
 }
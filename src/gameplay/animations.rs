@@ -0,0 +1,100 @@
+//! Choice of the animation clips of Didi and Baobei.
+
+use bevy::prelude::*;
+
+use crate::{
+    animation::Animation,
+    clock::FixedUpdateStage,
+    collisions::{CollisionSystems, Movement},
+    constants::GameState,
+};
+
+use super::{furniture::Napping, happiness::Happiness, items::Carrying, Baobei, Didi};
+
+/// Plugin playing the clips matching what Didi and Baobei are doing.
+pub struct GameplayAnimationsPlugin;
+
+impl Plugin for GameplayAnimationsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            SystemSet::on_update(GameState::InGame)
+                .with_system(
+                    didi_animation_system
+                        .system()
+                        .after("movement")
+                        .before(CollisionSystems),
+                )
+                .with_system(baobei_animation_system.system()),
+        );
+    }
+}
+
+/// Returns the clip of Didi for the given movement. The sideways clip is
+/// drawn looking to the left and mirrored by the `Facing` of Didi.
+fn didi_clip(movement: Vec3, carrying: bool) -> &'static str {
+    if movement.x.abs() < f32::EPSILON && movement.y.abs() < f32::EPSILON {
+        if carrying {
+            "carry"
+        } else {
+            "idle"
+        }
+    } else if movement.x.abs() >= movement.y.abs() {
+        "walk_side"
+    } else if movement.y < 0.0 {
+        "walk_down"
+    } else {
+        "walk_up"
+    }
+}
+
+/// Returns the clip of Baobei for the given happiness, or sleeping.
+fn baobei_clip(happiness: Happiness, napping: bool) -> &'static str {
+    if napping {
+        "sleep"
+    } else if happiness.value() >= 0.5 {
+        "happy"
+    } else {
+        "sad"
+    }
+}
+
+/// Plays the clip of Didi walking toward its movement, or standing.
+///
+/// It runs before the collisions, which consume the movement.
+fn didi_animation_system(
+    mut didi_query: Query<(&Movement, Option<&Carrying>, &mut Animation), With<Didi>>,
+) {
+    for (movement, carrying, mut animation) in didi_query.iter_mut() {
+        animation.play(didi_clip(movement.0, carrying.is_some()));
+    }
+}
+
+/// Plays the clip of Baobei matching its happiness, or sleeping.
+///
+/// It runs at each step rather than when the happiness changes, so that the
+/// nap starts and ends the sleeping clip at once.
+fn baobei_animation_system(
+    mut baobei_query: Query<(&Happiness, Option<&Napping>, &mut Animation), With<Baobei>>,
+) {
+    for (happiness, napping, mut animation) in baobei_query.iter_mut() {
+        animation.play(baobei_clip(*happiness, napping.is_some()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::didi_clip;
+
+    #[test]
+    fn test_didi_clips() {
+        assert_eq!(didi_clip(Vec3::ZERO, false), "idle");
+        assert_eq!(didi_clip(Vec3::ZERO, true), "carry");
+        assert_eq!(didi_clip(Vec3::new(-2.0, 1.0, 0.0), true), "walk_side");
+        assert_eq!(didi_clip(Vec3::new(1.0, 0.0, 0.0), false), "walk_side");
+        assert_eq!(didi_clip(Vec3::new(0.5, -1.0, 0.0), false), "walk_down");
+        assert_eq!(didi_clip(Vec3::new(0.0, 1.0, 0.0), false), "walk_up");
+    }
+}
@@ -0,0 +1,351 @@
+//! Chores: messes appear in the room and make Baobei sadder until Didi
+//! cleans them, by holding interact or with the sponge.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    collisions::{Position, TriggerArea},
+    constants::GameState,
+    cooldown::{Cooldown, CooldownAppExt, CooldownResource, CooldownSystems},
+    rng::GameRng,
+};
+
+use super::{
+    entities::GameData,
+    furniture::OnTable,
+    happiness::HappinessSystems,
+    items::{ActionEvent, HeldAction, Item, ItemSystems},
+    materials::GameplayMaterials,
+    modifiers::{Modifier, ModifierKind, ModifierSystems, Modifiers},
+    round::NewRoundSystems,
+    Baobei,
+};
+
+/// Plugin spawning the messes and managing their cleaning.
+pub struct ChoresPlugin;
+
+impl Plugin for ChoresPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ChoreConfig>()
+            .register_type::<MessKind>()
+            .insert_cooldown_resource(MessTimer::default())
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(
+                        hold_to_clean_system
+                            .system()
+                            .label("item_actions")
+                            .after("held_action"),
+                    )
+                    .with_system(
+                        handle_chores_actions_system
+                            .system()
+                            .label(ItemSystems)
+                            .after("item_actions"),
+                    )
+                    .with_system(
+                        spawn_messes_system
+                            .system()
+                            .after(ItemSystems)
+                            .after(CooldownSystems),
+                    )
+                    .with_system(
+                        mess_modifier_system
+                            .system()
+                            .after(ModifierSystems)
+                            .before(HappinessSystems),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(show_cleaning_progress_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .label(NewRoundSystems)
+                    .with_system(reset_chores_system.system()),
+            );
+    }
+}
+
+/// Parameters of the chores.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChoreConfig {
+    /// Seconds between two messes.
+    pub interval: f32,
+    /// Maximum number of messes in the room.
+    pub max_messes: usize,
+    /// Increase of the decay of the happiness for each mess.
+    pub decay_per_mess: f32,
+    /// Seconds to hold interact to clean a mess without the sponge.
+    pub cleaning_duration: f32,
+    /// Probability that a mess appears next to a dropped item rather than
+    /// next to Baobei.
+    pub near_dropped_item_chance: f64,
+}
+
+impl Default for ChoreConfig {
+    fn default() -> Self {
+        Self {
+            interval: 25.0,
+            max_messes: 3,
+            decay_per_mess: 0.25,
+            cleaning_duration: 1.5,
+            near_dropped_item_chance: 0.5,
+        }
+    }
+}
+
+/// Time before the next mess appears.
+pub struct MessTimer(pub Cooldown);
+
+impl MessTimer {
+    /// Creates the timer of the messes of a round, already started.
+    pub fn new(config: &ChoreConfig) -> Self {
+        Self(Cooldown::from_seconds(config.interval).repeating())
+    }
+}
+
+impl Default for MessTimer {
+    fn default() -> Self {
+        Self::new(&ChoreConfig::default())
+    }
+}
+
+impl CooldownResource for MessTimer {
+    fn cooldown_mut(&mut self) -> &mut Cooldown {
+        &mut self.0
+    }
+}
+
+/// A kind of mess.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect_value(Component, PartialEq, Serialize, Deserialize)]
+pub enum MessKind {
+    /// Water spilled on the floor
+    SpilledWater,
+    /// Crumbs of food
+    Crumbs,
+}
+
+/// Only needed by the reflection, to create the component before applying
+/// its saved value.
+impl Default for MessKind {
+    fn default() -> Self {
+        Self::SpilledWater
+    }
+}
+
+impl MessKind {
+    /// Returns the mess made by the item.
+    pub const fn made_by(item: Item) -> Self {
+        match item {
+            Item::WaterGlass | Item::Tea | Item::DirtyGlass => Self::SpilledWater,
+            _ => Self::Crumbs,
+        }
+    }
+}
+
+/// Component on a mess to clean.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mess {
+    /// Kind of the mess.
+    pub kind: MessKind,
+    /// Seconds Didi spent cleaning the mess without interruption.
+    pub cleaning: f32,
+}
+
+/// Returns the scale of the sprite of a mess.
+fn mess_scale() -> Vec3 {
+    Vec3::new(0.5, 0.5, 0.0)
+}
+
+/// Spawns the mess at the given position.
+pub fn spawn_mess(
+    commands: &mut Commands,
+    materials: &GameplayMaterials,
+    kind: MessKind,
+    position: Vec3,
+) {
+    commands
+        .spawn()
+        .insert(Mess {
+            kind,
+            cleaning: 0.0,
+        })
+        .insert(Position(position))
+        .insert(TriggerArea::new(80.0, 60.0))
+        .insert_bundle(SpriteBundle {
+            material: materials.mess_sprite_for(kind),
+            transform: Transform::from_scale(mess_scale()),
+            ..SpriteBundle::default()
+        });
+}
+
+/// Spawns messes regularly, next to a dropped item or next to Baobei. A
+/// change of the config starts the timer over.
+#[allow(clippy::too_many_arguments)]
+fn spawn_messes_system(
+    mut commands: Commands,
+    config: Res<ChoreConfig>,
+    mut timer: ResMut<MessTimer>,
+    mut rng: ResMut<GameRng>,
+    materials: Res<GameplayMaterials>,
+    game_data: Res<GameData>,
+    messes: Query<(), With<Mess>>,
+    dropped_items: Query<(&Item, &Position), Without<OnTable>>,
+    positions: Query<&Position>,
+) {
+    if config.is_changed() {
+        *timer = MessTimer::new(&config);
+    }
+    if !timer.0.just_finished() || messes.iter().count() >= config.max_messes {
+        return;
+    }
+
+    let dropped_items: Vec<_> = dropped_items.iter().collect();
+    let (kind, position) =
+        if !dropped_items.is_empty() && rng.gen_bool(config.near_dropped_item_chance) {
+            let (item, position) = dropped_items[rng.gen_range(0..dropped_items.len())];
+            let offset = Vec3::new(rng.gen_range(-40.0..40.0), -30.0, 0.0);
+            (MessKind::made_by(*item), position.0 + offset)
+        } else {
+            let baobei_position = match positions.get(game_data.baobei_entity) {
+                Ok(position) => position.0,
+                Err(_) => return,
+            };
+            let kind = if rng.gen_bool(0.5) {
+                MessKind::SpilledWater
+            } else {
+                MessKind::Crumbs
+            };
+            let offset = Vec3::new(
+                rng.gen_range(-250.0..-100.0),
+                rng.gen_range(80.0..150.0),
+                0.0,
+            );
+            (kind, baobei_position + offset)
+        };
+
+    info!("A mess of {:?} appears", kind);
+    spawn_mess(
+        &mut commands,
+        &materials,
+        kind,
+        position.truncate().extend(0.0),
+    );
+}
+
+/// Cleans the mess targeted by Didi while the interact button is held with
+/// empty hands.
+fn hold_to_clean_system(
+    config: Res<ChoreConfig>,
+    held_action: Res<HeldAction>,
+    mut action_events: EventWriter<ActionEvent>,
+    mut messes: Query<(Entity, &mut Mess)>,
+) {
+    let target = match held_action.0 {
+        Some(ActionEvent::Clean(mess)) => Some(mess),
+        _ => None,
+    };
+
+    for (entity, mut mess) in messes.iter_mut() {
+        if Some(entity) == target {
+            mess.cleaning += STEP.as_secs_f32();
+            if mess.cleaning >= config.cleaning_duration {
+                action_events.send(ActionEvent::Clean(entity));
+            }
+        } else if mess.cleaning > 0.0 {
+            mess.cleaning = 0.0;
+        }
+    }
+}
+
+/// Removes the cleaned messes.
+fn handle_chores_actions_system(
+    mut commands: Commands,
+    mut action_events: EventReader<ActionEvent>,
+    messes: Query<&Mess>,
+) {
+    for action in action_events.iter() {
+        if let ActionEvent::Clean(entity) = action {
+            if let Ok(mess) = messes.get(*entity) {
+                info!("Clean the mess of {:?}", mess.kind);
+                commands.entity(*entity).despawn();
+            }
+        }
+    }
+}
+
+/// Makes the happiness of Baobei decrease faster with each mess.
+fn mess_modifier_system(
+    config: Res<ChoreConfig>,
+    messes: Query<(), With<Mess>>,
+    mut modifiers_query: Query<&mut Modifiers, With<Baobei>>,
+) {
+    let count = messes.iter().count();
+    let decay_factor = 1.0 + config.decay_per_mess * count as f32;
+
+    for mut modifiers in modifiers_query.iter_mut() {
+        let current = modifiers
+            .0
+            .iter()
+            .find(|modifier| modifier.kind == ModifierKind::Messes)
+            .map(|modifier| modifier.decay_factor);
+        let up_to_date = current.map_or(count == 0, |current| {
+            count > 0 && (current - decay_factor).abs() < f32::EPSILON
+        });
+        if up_to_date {
+            continue;
+        }
+
+        if count == 0 {
+            modifiers.remove(ModifierKind::Messes);
+        } else {
+            modifiers.add(Modifier {
+                kind: ModifierKind::Messes,
+                decay_factor,
+                timer: None,
+            });
+        }
+    }
+}
+
+/// Shrinks the messes while Didi cleans them.
+fn show_cleaning_progress_system(
+    config: Res<ChoreConfig>,
+    mut messes: Query<(&Mess, &mut Transform), Changed<Mess>>,
+) {
+    for (mess, mut transform) in messes.iter_mut() {
+        let progress = (mess.cleaning / config.cleaning_duration).min(1.0);
+        transform.scale = mess_scale() * (1.0 - 0.5 * progress);
+    }
+}
+
+/// Removes the messes for the new round.
+fn reset_chores_system(
+    mut commands: Commands,
+    config: Res<ChoreConfig>,
+    mut timer: ResMut<MessTimer>,
+    messes: Query<Entity, With<Mess>>,
+) {
+    *timer = MessTimer::new(&config);
+    for mess in messes.iter() {
+        commands.entity(mess).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Item, MessKind};
+
+    #[test]
+    fn test_mess_made_by_item() {
+        assert_eq!(MessKind::made_by(Item::WaterGlass), MessKind::SpilledWater);
+        assert_eq!(MessKind::made_by(Item::Sandwich), MessKind::Crumbs);
+    }
+}
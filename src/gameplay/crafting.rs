@@ -0,0 +1,412 @@
+//! Crafting of composite items in workstations: Didi deposits the
+//! ingredients of a recipe, prepares them by holding interact, then collects
+//! the product.
+
+use bevy::{prelude::*, sprite::SpriteResizeMode};
+use serde::Deserialize;
+
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    constants::GameState,
+    cooldown::Cooldown,
+};
+
+use super::{
+    entities::GameData,
+    freshness::Freshness,
+    items::{
+        spawn_carried_item, ActionEvent, CarriedItem, Carrying, HeldAction, Item, ItemSystems,
+    },
+    materials::GameplayMaterials,
+};
+
+/// Width of the preparation progress bar when the preparation is done.
+const PROGRESS_BAR_WIDTH: f32 = 80.0;
+
+/// Plugin managing the workstations and their recipes.
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Recipes>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
+                    .label(ItemSystems)
+                    .with_system(
+                        preparation_system
+                            .system()
+                            .label("item_actions")
+                            .after("held_action"),
+                    )
+                    .with_system(
+                        handle_crafting_actions_system
+                            .system()
+                            .after("item_actions"),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(update_preparation_indicators_system.system()),
+            );
+    }
+}
+
+/// A composite item, prepared from its ingredients in a workstation.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Recipe {
+    /// Items to deposit in the workstation, in any order.
+    pub ingredients: Vec<Item>,
+    /// Item produced by the recipe.
+    pub product: Item,
+    /// Seconds to hold interact to prepare the product.
+    pub duration: f32,
+}
+
+impl Recipe {
+    /// Returns the ingredients still missing after the deposited ones, or
+    /// `None` if some deposited items are not part of the recipe.
+    fn missing_ingredients(&self, deposited: &[Item]) -> Option<Vec<Item>> {
+        let mut missing = self.ingredients.clone();
+        for item in deposited {
+            let index = missing.iter().position(|ingredient| ingredient == item)?;
+            missing.swap_remove(index);
+        }
+        Some(missing)
+    }
+}
+
+/// The recipes known by the workstations, defined in `assets/recipes.ron`.
+///
+/// The file is embedded in the game, so that the recipes are available
+/// before any asset is loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipes(pub Vec<Recipe>);
+
+impl Default for Recipes {
+    fn default() -> Self {
+        match ron::from_str(include_str!("../../assets/recipes.ron")) {
+            Ok(recipes) => Self(recipes),
+            Err(error) => {
+                error!(
+                    "Invalid recipes, the workstations accept nothing: {}",
+                    error
+                );
+                Self(Vec::new())
+            }
+        }
+    }
+}
+
+impl Recipes {
+    /// Returns true if the item is an ingredient missing to a recipe, after
+    /// the deposited ones.
+    pub fn accepts(&self, deposited: &[Item], item: Item) -> bool {
+        self.0.iter().any(|recipe| {
+            recipe
+                .missing_ingredients(deposited)
+                .map_or(false, |missing| missing.contains(&item))
+        })
+    }
+
+    /// Returns the recipe whose ingredients are exactly the deposited ones.
+    pub fn completed_by(&self, deposited: &[Item]) -> Option<&Recipe> {
+        self.0.iter().find(|recipe| {
+            recipe
+                .missing_ingredients(deposited)
+                .map_or(false, |missing| missing.is_empty())
+        })
+    }
+}
+
+/// A recipe being prepared in a workstation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preparation {
+    /// Item produced at the end of the preparation.
+    pub product: Item,
+    /// Time before the end of the preparation, only advanced while Didi
+    /// prepares.
+    pub timer: Cooldown,
+}
+
+/// Component on furniture where Didi prepares recipes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Workstation {
+    /// Ingredients deposited for the next recipe, with their freshness.
+    pub ingredients: Vec<(Item, Freshness)>,
+    /// Recipe being prepared, if any.
+    pub preparation: Option<Preparation>,
+    /// Product of the last preparation, waiting to be collected.
+    pub product: Option<Item>,
+}
+
+impl Workstation {
+    /// Returns true if the item can be deposited for a recipe.
+    pub fn accepts(&self, recipes: &Recipes, item: Item) -> bool {
+        self.preparation.is_none()
+            && self.product.is_none()
+            && recipes.accepts(&self.deposited(), item)
+    }
+
+    /// Returns the deposited ingredients, without their freshness.
+    fn deposited(&self) -> Vec<Item> {
+        self.ingredients.iter().map(|(item, _)| *item).collect()
+    }
+
+    /// Returns the item Didi can collect: the product when it is ready,
+    /// otherwise the last deposited ingredient.
+    pub fn collectable(&self) -> Option<Item> {
+        if self.preparation.is_some() {
+            return None;
+        }
+        self.product
+            .or_else(|| self.ingredients.last().map(|(item, _)| *item))
+    }
+
+    /// Removes the item Didi collects from the workstation, with its
+    /// freshness, the product being fresh.
+    fn collect(&mut self) -> Option<(Item, Freshness)> {
+        if self.preparation.is_some() {
+            return None;
+        }
+        self.product
+            .take()
+            .map(|product| (product, Freshness::default()))
+            .or_else(|| self.ingredients.pop())
+    }
+
+    /// Returns the fraction of the preparation done, if preparing.
+    pub fn progress(&self) -> Option<f32> {
+        self.preparation
+            .as_ref()
+            .map(|preparation| 1.0 - preparation.timer.fraction_remaining())
+    }
+}
+
+/// Tag the bar showing the progress of the preparation.
+struct ProgressBar;
+
+/// Tag the icon of the product waiting in the workstation.
+struct ProductIcon;
+
+/// Makes the furniture a workstation, with the indicators of its
+/// preparations.
+///
+/// The indicators are children of the furniture, so they cancel its scale to
+/// keep their size.
+pub fn insert_workstation(
+    commands: &mut Commands,
+    furniture: Entity,
+    scale: f32,
+    materials: &GameplayMaterials,
+) {
+    commands
+        .entity(furniture)
+        .insert(Workstation::default())
+        .with_children(|parent| {
+            parent
+                .spawn()
+                .insert(ProgressBar)
+                .insert_bundle(SpriteBundle {
+                    material: materials.preparation.clone(),
+                    sprite: Sprite {
+                        size: Vec2::new(0.0, 12.0),
+                        resize_mode: SpriteResizeMode::Manual,
+                        ..Sprite::default()
+                    },
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 60.0 / scale, 0.1),
+                        scale: Vec3::new(1.0 / scale, 1.0 / scale, 1.0),
+                        ..Transform::default()
+                    },
+                    visible: Visible {
+                        is_visible: false,
+                        is_transparent: true,
+                    },
+                    ..SpriteBundle::default()
+                });
+            parent
+                .spawn()
+                .insert(ProductIcon)
+                .insert_bundle(SpriteBundle {
+                    material: materials.none.clone(),
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 30.0 / scale, 0.1),
+                        scale: Vec3::new(0.4 / scale, 0.4 / scale, 1.0),
+                        ..Transform::default()
+                    },
+                    visible: Visible {
+                        is_visible: false,
+                        is_transparent: true,
+                    },
+                    ..SpriteBundle::default()
+                });
+        });
+}
+
+/// Handles the actions on workstations:
+/// - Move the item in hand to the workstation when depositing, with its
+///   freshness, starting the preparation once a recipe is complete
+/// - Replace the ingredients by the product when the preparation is done
+/// - Move the item from the workstation to the hand of Didi when collecting,
+///   giving it back its freshness
+fn handle_crafting_actions_system(
+    mut commands: Commands,
+    mut action_events: EventReader<ActionEvent>,
+    game_data: Res<GameData>,
+    recipes: Res<Recipes>,
+    materials: Res<GameplayMaterials>,
+    carried_items: Query<(Entity, &Freshness), With<CarriedItem>>,
+    mut workstations: Query<&mut Workstation>,
+) {
+    let didi = game_data.didi_entity;
+
+    for action in action_events.iter() {
+        match action {
+            ActionEvent::Deposit(entity, item) => {
+                let mut workstation = match workstations.get_mut(*entity) {
+                    Ok(workstation) => workstation,
+                    Err(_) => continue,
+                };
+                info!("Deposit {:?} in the workstation", item);
+                commands.entity(didi).remove::<Carrying>();
+                let mut freshness = Freshness::default();
+                for (item_in_hand, item_freshness) in carried_items.iter() {
+                    freshness = *item_freshness;
+                    commands.entity(item_in_hand).despawn();
+                }
+
+                workstation.ingredients.push((*item, freshness));
+                if let Some(recipe) = recipes.completed_by(&workstation.deposited()) {
+                    info!("Start preparing {:?}", recipe.product);
+                    let mut timer = Cooldown::from_seconds(recipe.duration);
+                    timer.start();
+                    workstation.preparation = Some(Preparation {
+                        product: recipe.product,
+                        timer,
+                    });
+                }
+            }
+            ActionEvent::Prepare(entity) => {
+                let mut workstation = match workstations.get_mut(*entity) {
+                    Ok(workstation) => workstation,
+                    Err(_) => continue,
+                };
+                if let Some(preparation) = workstation.preparation.take() {
+                    info!("{:?} is ready", preparation.product);
+                    workstation.ingredients.clear();
+                    workstation.product = Some(preparation.product);
+                }
+            }
+            ActionEvent::Collect(entity, item) => {
+                let mut workstation = match workstations.get_mut(*entity) {
+                    Ok(workstation) => workstation,
+                    Err(_) => continue,
+                };
+                let collected = workstation
+                    .collect()
+                    .filter(|(collected_item, _)| collected_item == item);
+                if let Some((_, freshness)) = collected {
+                    info!("Collect {:?} from the workstation", item);
+                    let item_in_hand = spawn_carried_item(&mut commands, &materials, didi, *item);
+                    commands.entity(item_in_hand).insert(freshness);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Advances the preparation in the workstation targeted by Didi while the
+/// interact button is held, sending the action once done.
+fn preparation_system(
+    held_action: Res<HeldAction>,
+    mut action_events: EventWriter<ActionEvent>,
+    mut workstations: Query<&mut Workstation>,
+) {
+    let entity = match held_action.0 {
+        Some(ActionEvent::Prepare(entity)) => entity,
+        _ => return,
+    };
+    if let Ok(mut workstation) = workstations.get_mut(entity) {
+        let done = workstation
+            .preparation
+            .as_mut()
+            .map_or(false, |preparation| {
+                preparation.timer.tick(STEP.as_secs_f32()).just_finished()
+            });
+        if done {
+            action_events.send(ActionEvent::Prepare(entity));
+        }
+    }
+}
+
+/// Query filter for the product icons, apart from the progress bars
+type ProductIconOnly = (With<ProductIcon>, Without<ProgressBar>);
+
+/// Shows the progress of the preparations, and the products waiting to be
+/// collected.
+fn update_preparation_indicators_system(
+    materials: Res<GameplayMaterials>,
+    workstations: Query<(&Workstation, &Children), Changed<Workstation>>,
+    mut progress_bars: Query<(&mut Sprite, &mut Transform, &mut Visible), With<ProgressBar>>,
+    mut product_icons: Query<(&mut Handle<ColorMaterial>, &mut Visible), ProductIconOnly>,
+) {
+    for (workstation, children) in workstations.iter() {
+        for child in children.iter() {
+            if let Ok((mut sprite, mut transform, mut visible)) = progress_bars.get_mut(*child) {
+                let progress = workstation.progress();
+                visible.is_visible = progress.is_some();
+                sprite.size.x = PROGRESS_BAR_WIDTH * progress.unwrap_or(0.0);
+                // Keep the left end of the bar in place, in the space of the furniture.
+                transform.translation.x =
+                    (sprite.size.x - PROGRESS_BAR_WIDTH) / 2.0 * transform.scale.x;
+            } else if let Ok((mut material, mut visible)) = product_icons.get_mut(*child) {
+                visible.is_visible = workstation.product.is_some();
+                if let Some(product) = workstation.product {
+                    *material = materials.item_sprite_for(product);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Freshness, Item, Recipes, Workstation};
+
+    #[test]
+    fn test_recipes() {
+        let recipes = Recipes::default();
+        assert!(recipes.accepts(&[], Item::Bread));
+        assert!(recipes.accepts(&[Item::Chips], Item::Bread));
+        assert!(!recipes.accepts(&[Item::Chips], Item::Chips));
+        assert!(!recipes.accepts(&[], Item::IceCream));
+
+        assert_eq!(recipes.completed_by(&[Item::Bread]), None);
+        let sandwich = recipes.completed_by(&[Item::Chips, Item::Bread]).unwrap();
+        assert_eq!(sandwich.product, Item::Sandwich);
+        assert_eq!(
+            recipes.completed_by(&[Item::WaterGlass]).unwrap().product,
+            Item::Tea
+        );
+    }
+
+    #[test]
+    fn test_collect_from_workstation() {
+        let mut workstation = Workstation {
+            ingredients: vec![(Item::Bread, Freshness(0.5))],
+            ..Workstation::default()
+        };
+        assert_eq!(workstation.collectable(), Some(Item::Bread));
+        assert_eq!(workstation.collect(), Some((Item::Bread, Freshness(0.5))));
+        assert_eq!(workstation.collectable(), None);
+
+        workstation.product = Some(Item::Tea);
+        assert!(!workstation.accepts(&Recipes::default(), Item::WaterGlass));
+        assert_eq!(
+            workstation.collect(),
+            Some((Item::Tea, Freshness::default()))
+        );
+    }
+}
@@ -1,17 +1,27 @@
 //! Systems spawning entities of the game.
 
 use bevy::prelude::*;
-use rand::random;
 
 use crate::{
+    animation::Animation,
     collisions::{BoxCollider, Movement, Position, TriggerArea},
     constants::{WINDOW_HEIGHT, WINDOW_WIDTH},
+    difficulty::DifficultyConfig,
+    drawing::GameCamera,
+    rng::GameRng,
 };
 
 use super::{
+    crafting::insert_workstation,
+    freshness::TrashBin,
+    furniture::{Interactable, Rest},
     happiness::Happiness,
-    items::{AskedItem, AskingItem, Item, ItemProducer},
+    inventory::{Inventory, InventoryUpgrades},
+    items::{request_cooldown, AskedItem, AskingItem, Item, ItemProducer},
     materials::GameplayMaterials,
+    modifiers::Modifiers,
+    movement::Facing,
+    stock::{insert_stock, StockConfig},
     Baobei, Didi,
 };
 
@@ -37,12 +47,12 @@ pub struct GameData {
     pub baobei_entity: Entity,
 }
 
-/// Spawn the camera.
+/// Spawn the camera, centered on the play area.
 fn setup_camera(mut commands: Commands) {
     let mut camera_2d = OrthographicCameraBundle::new_2d();
     camera_2d.transform.translation += Vec3::new(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0, 0.0);
 
-    commands.spawn().insert_bundle(camera_2d);
+    commands.spawn().insert_bundle(camera_2d).insert(GameCamera);
 }
 
 /// Spawn the background of the screen.
@@ -54,27 +64,40 @@ fn spawn_background(mut commands: Commands, materials: Res<GameplayMaterials>) {
     });
 }
 
+/// Returns the position of Didi at the start of a round.
+pub fn didi_start_position() -> Position {
+    Position(Vec3::new(640.0, 260.0, 0.0))
+}
+
 /// Spawn the entity for Didi, the player and Baobei.
-fn spawn_didi_and_baobei(mut commands: Commands, materials: Res<GameplayMaterials>) {
+fn spawn_didi_and_baobei(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    config: Res<DifficultyConfig>,
+    upgrades: Res<InventoryUpgrades>,
+    materials: Res<GameplayMaterials>,
+) {
     let transform = Transform::from_scale(Vec3::new(0.3, 0.3, 0.0));
 
     let didi_entity = commands
         .spawn()
         .insert(Didi)
-        .insert(Position(Vec3::new(640.0, 260.0, 0.0)))
+        .insert(didi_start_position())
         .insert(BoxCollider {
             size: Vec2::new(75.0, 50.0),
             offset: Vec3::new(0.0, -10.0, 0.0),
         })
         .insert(Movement::default())
-        .insert_bundle(SpriteBundle {
-            material: materials.didi_sprite.clone(),
+        .insert(Facing::default())
+        .insert(Inventory::new(upgrades.slots(&config)))
+        .insert(Animation::new(materials.didi_animations.clone(), "idle"))
+        .insert_bundle(SpriteSheetBundle {
             transform,
-            ..SpriteBundle::default()
+            ..SpriteSheetBundle::default()
         })
         .id();
 
-    let asked_item = random::<Item>();
+    let asked_item = Item::random(&mut *rng, config.item_count);
 
     let baobei_entity = commands
         .spawn()
@@ -82,11 +105,14 @@ fn spawn_didi_and_baobei(mut commands: Commands, materials: Res<GameplayMaterial
         .insert(Position(Vec3::new(1050.0, 150.0, 85.0)))
         .insert(TriggerArea::new(150.0, 150.0))
         .insert(AskingItem(asked_item))
+        .insert(request_cooldown(&config))
         .insert(Happiness::happy())
-        .insert_bundle(SpriteBundle {
-            material: materials.baobei_sprite.clone(),
+        .insert(Rest::default())
+        .insert(Modifiers::default())
+        .insert(Animation::new(materials.baobei_animations.clone(), "happy"))
+        .insert_bundle(SpriteSheetBundle {
             transform,
-            ..SpriteBundle::default()
+            ..SpriteSheetBundle::default()
         })
         .with_children(|parent| {
             parent
@@ -125,16 +151,26 @@ fn spawn_furniture(mut commands: Commands, materials: Res<GameplayMaterials>) {
             transform: Transform::from_scale(Vec3::new(0.3, 0.3, 0.0)),
             ..SpriteBundle::default()
         });
-    // Kitchen
+    // Basin of the sink where Didi washes the dirty glasses, right of the
+    // glasses
     commands
+        .spawn()
+        .insert(Interactable::Sink)
+        .insert(Position(Vec3::new(1110.0, 500.0, 0.0)))
+        .insert(TriggerArea::new(110.0, 50.0));
+    // Kitchen, with a workstation in the middle of its counter
+    let kitchen = commands
         .spawn()
         .insert(Position(Vec3::new(300.0, 540.0, 0.0)))
         .insert(BoxCollider::new(400.0, 100.0))
+        .insert(TriggerArea::new(100.0, 195.0))
         .insert_bundle(SpriteBundle {
             material: materials.kitchen_sprite.clone(),
             transform: Transform::from_scale(Vec3::new(0.5, 0.5, 0.0)),
             ..SpriteBundle::default()
-        });
+        })
+        .id();
+    insert_workstation(&mut commands, kitchen, 0.5, &materials);
     // Fridge
     commands
         .spawn()
@@ -158,10 +194,18 @@ fn spawn_furniture(mut commands: Commands, materials: Res<GameplayMaterials>) {
             transform: Transform::from_scale(Vec3::new(0.4, 0.4, 0.0)),
             ..SpriteBundle::default()
         });
+    // Spot of the couch where Didi lets Baobei nap, away from Baobei
+    commands
+        .spawn()
+        .insert(Interactable::Couch)
+        .insert(Position(Vec3::new(1180.0, 190.0, 0.0)))
+        .insert(TriggerArea::new(70.0, 70.0));
     // Table
     commands
         .spawn()
+        .insert(Interactable::table(3))
         .insert(Position(Vec3::new(300.0, 200.0, 0.0)))
+        .insert(TriggerArea::new(320.0, 80.0))
         .insert(BoxCollider {
             size: Vec2::new(300.0, 40.0),
             offset: Vec3::new(0.0, 25.0, 0.0),
@@ -171,25 +215,66 @@ fn spawn_furniture(mut commands: Commands, materials: Res<GameplayMaterials>) {
             transform: Transform::from_scale(Vec3::new(0.4, 0.4, 0.0)),
             ..SpriteBundle::default()
         });
-}
-
-/// Spawn item producers.
-fn spawn_item_producers(mut commands: Commands) {
+    // Trash bin
     commands
         .spawn()
-        .insert(ItemProducer(Item::WaterGlass))
-        .insert(Position(Vec3::new(1050.0, 500.0, 0.0)))
-        .insert(TriggerArea::new(230.0, 50.0));
-    commands
-        .spawn()
-        .insert(ItemProducer(Item::Chips))
-        .insert(Position(Vec3::new(210.0, 480.0, 0.0)))
-        .insert(TriggerArea::new(75.0, 75.0));
-    commands
-        .spawn()
-        .insert(ItemProducer(Item::IceCream))
-        .insert(Position(Vec3::new(720.0, 540.0, 0.0)))
-        .insert(TriggerArea::new(175.0, 175.0));
+        .insert(TrashBin)
+        .insert(Position(Vec3::new(100.0, 110.0, 0.0)))
+        .insert(BoxCollider::new(60.0, 40.0))
+        .insert(TriggerArea::new(130.0, 110.0))
+        .insert_bundle(SpriteBundle {
+            material: materials.trash_bin_sprite.clone(),
+            transform: Transform::from_scale(Vec3::new(0.4, 0.4, 0.0)),
+            ..SpriteBundle::default()
+        });
+}
+
+/// Spawn item producers, with a stock for the limited ones.
+fn spawn_item_producers(
+    mut commands: Commands,
+    config: Res<StockConfig>,
+    asset_server: Res<AssetServer>,
+) {
+    let producers = vec![
+        (
+            Item::WaterGlass,
+            Vec3::new(990.0, 500.0, 0.0),
+            TriggerArea::new(110.0, 50.0),
+        ),
+        (
+            Item::Chips,
+            Vec3::new(210.0, 480.0, 0.0),
+            TriggerArea::new(75.0, 75.0),
+        ),
+        (
+            Item::Bread,
+            Vec3::new(120.0, 480.0, 0.0),
+            TriggerArea::new(75.0, 75.0),
+        ),
+        (
+            Item::IceCream,
+            Vec3::new(720.0, 540.0, 0.0),
+            TriggerArea::new(175.0, 175.0),
+        ),
+        (
+            Item::Sponge,
+            Vec3::new(880.0, 500.0, 0.0),
+            TriggerArea::new(60.0, 60.0),
+        ),
+    ];
+    let font = asset_server.load("FiraSans-Bold.ttf");
+
+    for (item, position, trigger_area) in producers {
+        let producer = commands
+            .spawn()
+            .insert(ItemProducer(item))
+            .insert(Position(position))
+            .insert(trigger_area)
+            .id();
+        if let Some(capacity) = config.capacity(item) {
+            insert_stock(&mut commands, producer, capacity, font.clone());
+        }
+    }
 }
 
 /// Spawn boarders of the room, avoiding the user to go out of the screen.
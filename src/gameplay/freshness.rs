@@ -0,0 +1,215 @@
+//! Freshness of the items: it decreases over time, faster on the floor,
+//! tints the stale items and turns the spoiled ones into trash.
+
+use bevy::prelude::*;
+
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    constants::GameState,
+};
+
+use super::{
+    entities::GameData,
+    furniture::OnTable,
+    inventory::Inventory,
+    items::{CarriedItem, Carrying, Item, ItemSystems},
+    materials::GameplayMaterials,
+};
+
+/// Freshness under which an item is stale.
+const STALE: f32 = 0.5;
+
+/// Plugin managing the freshness of the items.
+pub struct FreshnessPlugin;
+
+impl Plugin for FreshnessPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<FreshnessConfig>()
+            .register_type::<Freshness>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(spoil_items_system.system().after(ItemSystems)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(tint_items_system.system()),
+            );
+    }
+}
+
+/// Parameters of the freshness of the items.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FreshnessConfig {
+    /// Seconds before each item spoils when carried.
+    pub lifetimes: Vec<(Item, f32)>,
+    /// Seconds before the items missing in `lifetimes` spoil when carried.
+    pub default_lifetime: f32,
+    /// Factor of the decay of the items lying on the floor.
+    pub floor_factor: f32,
+    /// Factor of the reward for giving an item about to spoil.
+    pub min_reward_factor: f32,
+}
+
+impl Default for FreshnessConfig {
+    fn default() -> Self {
+        Self {
+            lifetimes: vec![
+                (Item::IceCream, 45.0),
+                (Item::Tea, 60.0),
+                (Item::Sandwich, 90.0),
+                (Item::Chips, 180.0),
+            ],
+            default_lifetime: 120.0,
+            floor_factor: 2.0,
+            min_reward_factor: 0.5,
+        }
+    }
+}
+
+impl FreshnessConfig {
+    /// Returns the seconds before the item spoils when carried.
+    pub fn lifetime(&self, item: Item) -> f32 {
+        self.lifetimes
+            .iter()
+            .find(|(lasting_item, _)| *lasting_item == item)
+            .map_or(self.default_lifetime, |(_, lifetime)| *lifetime)
+    }
+
+    /// Returns the freshness of the item after a step, decaying `factor`
+    /// times faster than when carried.
+    fn spoil(&self, item: Item, freshness: Freshness, factor: f32) -> Freshness {
+        Freshness((freshness.0 - factor * STEP.as_secs_f32() / self.lifetime(item)).max(0.0))
+    }
+
+    /// Returns the factor of the reward for giving an item with the freshness.
+    pub fn reward_factor(&self, freshness: Freshness) -> f32 {
+        self.min_reward_factor + (1.0 - self.min_reward_factor) * freshness.0
+    }
+}
+
+/// Component on furniture where items can be thrown away.
+pub struct TrashBin;
+
+/// Component with the freshness of an item, from 1 when fresh to 0 when spoiled.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Freshness(pub f32);
+
+impl Default for Freshness {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl Freshness {
+    /// Returns true if the item starts to spoil.
+    pub fn is_stale(self) -> bool {
+        self.0 < STALE
+    }
+}
+
+/// Decreases the freshness of the items, turning them into trash once
+/// spoiled.
+fn spoil_items_system(
+    config: Res<FreshnessConfig>,
+    game_data: Res<GameData>,
+    mut items: Query<(
+        &mut Item,
+        &mut Freshness,
+        Option<&CarriedItem>,
+        Option<&OnTable>,
+    )>,
+    mut carriers: Query<&mut Carrying>,
+    mut inventories: Query<&mut Inventory>,
+) {
+    for (mut item, mut freshness, carried, on_table) in items.iter_mut() {
+        if !item.is_perishable() {
+            continue;
+        }
+        let factor = if carried.is_some() || on_table.is_some() {
+            1.0
+        } else {
+            config.floor_factor
+        };
+        freshness.0 = config.spoil(*item, *freshness, factor).0;
+
+        if freshness.0 <= 0.0 {
+            info!("The item {:?} is spoiled", *item);
+            *item = Item::Trash;
+            if carried.is_some() {
+                if let Ok(mut carrying) = carriers.get_mut(game_data.didi_entity) {
+                    carrying.0 = Item::Trash;
+                }
+            }
+        }
+    }
+
+    // The items stored in the inventory spoil as if carried.
+    for mut inventory in inventories.iter_mut() {
+        if inventory.stored.is_empty() {
+            continue; // Avoid to trigger the change detection.
+        }
+        for (item, freshness) in &mut inventory.stored {
+            if !item.is_perishable() {
+                continue;
+            }
+            *freshness = config.spoil(*item, *freshness, 1.0);
+            if freshness.0 <= 0.0 {
+                info!("The stored item {:?} is spoiled", *item);
+                *item = Item::Trash;
+            }
+        }
+    }
+}
+
+/// Shows the freshness of the items with their sprite.
+fn tint_items_system(
+    materials: Res<GameplayMaterials>,
+    mut items: Query<(&Item, &Freshness, &mut Handle<ColorMaterial>), Changed<Freshness>>,
+) {
+    for (item, freshness, mut material) in items.iter_mut() {
+        let sprite = if freshness.is_stale() {
+            materials.stale_item_sprite_for(*item)
+        } else {
+            materials.item_sprite_for(*item)
+        };
+        if *material != sprite {
+            *material = sprite;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Freshness, FreshnessConfig, Item};
+
+    #[test]
+    fn test_freshness_config() {
+        let config = FreshnessConfig::default();
+        assert!((config.lifetime(Item::IceCream) - 45.0).abs() < 1e-6);
+        assert!((config.lifetime(Item::WaterGlass) - 120.0).abs() < 1e-6);
+
+        assert!((config.reward_factor(Freshness::default()) - 1.0).abs() < 1e-6);
+        assert!((config.reward_factor(Freshness(0.5)) - 0.75).abs() < 1e-6);
+        assert!(Freshness(0.4).is_stale());
+    }
+
+    #[test]
+    fn test_perishable_items() {
+        let perishable: Vec<_> = Item::perishable().collect();
+        assert_eq!(
+            perishable,
+            [
+                Item::IceCream,
+                Item::WaterGlass,
+                Item::Chips,
+                Item::Tea,
+                Item::Sandwich,
+                Item::Bread,
+            ]
+        );
+        for item in &Item::ALL {
+            assert_eq!(perishable.contains(item), item.is_perishable());
+        }
+    }
+}
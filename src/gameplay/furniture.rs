@@ -0,0 +1,447 @@
+//! Interactions with the furniture besides producing items: Baobei naps on
+//! the couch, items are left on the table and dirty glasses are washed in
+//! the sink, going back to the stock of clean ones.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    collisions::Position,
+    constants::GameState,
+};
+
+use super::{
+    entities::GameData,
+    freshness::Freshness,
+    happiness::HappinessSystems,
+    items::{
+        didi_scale, dropped_item_area, picked_item_translation, spawn_dropped_item, ActionEvent,
+        AskingItem, CarriedItem, Carrying, Item, ItemSystems,
+    },
+    materials::GameplayMaterials,
+    modifiers::{Modifier, ModifierKind, ModifierSystems, Modifiers},
+    round::NewRoundSystems,
+    Baobei,
+};
+
+/// Maximum number of dirty glasses left on the ground, Baobei keeps the next
+/// ones.
+const MAX_DIRTY_GLASSES: usize = 4;
+
+/// Plugin managing the interactions with the furniture.
+pub struct FurniturePlugin;
+
+impl Plugin for FurniturePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<FurnitureConfig>()
+            .register_type::<Rest>()
+            .register_type::<Napping>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(
+                        leave_dirty_glass_system
+                            .system()
+                            .after("item_actions")
+                            .before("handle_actions"),
+                    )
+                    .with_system(
+                        handle_furniture_actions_system
+                            .system()
+                            .label(ItemSystems)
+                            .after("item_actions"),
+                    )
+                    .with_system(
+                        rest_system
+                            .system()
+                            .after(ModifierSystems)
+                            .before(HappinessSystems),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .label(NewRoundSystems)
+                    .with_system(reset_furniture_system.system()),
+            );
+    }
+}
+
+/// Parameters of the interactions with the furniture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FurnitureConfig {
+    /// Seconds for Baobei to get tired once fully rested.
+    pub awake_duration: f32,
+    /// Seconds of nap for Baobei to get fully rested once exhausted.
+    pub nap_duration: f32,
+    /// Rest at or under which Baobei is tired, and may nap.
+    pub tired_threshold: f32,
+    /// Factor of the decay of the happiness while Baobei is tired.
+    pub tired_decay_factor: f32,
+}
+
+impl Default for FurnitureConfig {
+    fn default() -> Self {
+        Self {
+            awake_duration: 90.0,
+            nap_duration: 6.0,
+            tired_threshold: 0.3,
+            tired_decay_factor: 1.5,
+        }
+    }
+}
+
+impl FurnitureConfig {
+    /// Returns true if Baobei is tired with the given rest.
+    pub fn is_tired(self, rest: Rest) -> bool {
+        rest.0 <= self.tired_threshold
+    }
+}
+
+/// Tiredness of Baobei, deciding if it can nap.
+#[derive(SystemParam)]
+pub struct Tiredness<'a> {
+    /// Parameters of the tiredness.
+    config: Res<'a, FurnitureConfig>,
+    /// Rest of Baobei.
+    rests: Query<'a, &'static Rest, With<Baobei>>,
+}
+
+impl<'a> Tiredness<'a> {
+    /// Returns true if Baobei is tired.
+    pub fn baobei_tired(&self) -> bool {
+        self.rests.iter().any(|rest| self.config.is_tired(*rest))
+    }
+}
+
+/// Component on furniture Didi can interact with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Interactable {
+    /// Baobei naps on the couch.
+    Couch,
+    /// Items left on the table don't lie on the floor.
+    Table {
+        /// Items on the table, the last one on top.
+        items: Vec<Entity>,
+        /// Number of items the table holds.
+        capacity: usize,
+    },
+    /// Dirty glasses are washed in the sink.
+    Sink,
+}
+
+impl Interactable {
+    /// Returns an empty table holding the given number of items.
+    pub const fn table(capacity: usize) -> Self {
+        Self::Table {
+            items: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the action of Didi on the furniture, carrying the item, Baobei
+    /// napping only when it is tired.
+    pub fn action(
+        &self,
+        entity: Entity,
+        carried_item: Option<Item>,
+        baobei_tired: bool,
+    ) -> Option<ActionEvent> {
+        match (self, carried_item) {
+            (Self::Couch, None) if baobei_tired => Some(ActionEvent::Nap(entity)),
+            (Self::Table { items, capacity }, Some(item)) if items.len() < *capacity => {
+                Some(ActionEvent::Store(entity, item))
+            }
+            (Self::Table { items, .. }, None) if !items.is_empty() => {
+                Some(ActionEvent::Retrieve(entity))
+            }
+            (Self::Sink, Some(item)) if item.washed().is_some() => {
+                Some(ActionEvent::Wash(entity, item))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Component on the items lying on a table.
+pub struct OnTable;
+
+/// Component with the rest of Baobei, from 1 when rested to 0 when exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Rest(pub f32);
+
+impl Default for Rest {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Component on Baobei while napping.
+#[derive(Reflect, Default)]
+#[reflect(Component)]
+pub struct Napping;
+
+/// Returns the offset of the item in the given slot of a table.
+fn table_slot_offset(slot: usize) -> Vec3 {
+    Vec3::new(80.0 * slot as f32 - 80.0, -5.0, 65.0)
+}
+
+/// Spawns the item on the table, on top of the items already there.
+pub fn spawn_table_item(
+    commands: &mut Commands,
+    materials: &GameplayMaterials,
+    table_items: &mut Vec<Entity>,
+    table_position: Vec3,
+    item: Item,
+    freshness: Freshness,
+) {
+    let entity = commands
+        .spawn()
+        .insert(item)
+        .insert(freshness)
+        .insert(OnTable)
+        .insert(Position(
+            table_position + table_slot_offset(table_items.len()),
+        ))
+        .insert_bundle(SpriteBundle {
+            material: materials.item_sprite_for(item),
+            transform: Transform::from_scale(didi_scale()),
+            ..SpriteBundle::default()
+        })
+        .id();
+    table_items.push(entity);
+}
+
+/// Leaves a dirty glass next to Baobei when it receives a drink it asked for,
+/// on the left of the dirty glasses already there.
+///
+/// It runs before the actions are handled, to know whether the given item is
+/// the one Baobei asks for.
+fn leave_dirty_glass_system(
+    mut commands: Commands,
+    materials: Res<GameplayMaterials>,
+    mut action_events: EventReader<ActionEvent>,
+    item_askers: Query<(&AskingItem, &Position), With<Baobei>>,
+    dropped_items: Query<&Item, (With<Position>, Without<OnTable>)>,
+) {
+    let mut dirty_glasses = dropped_items
+        .iter()
+        .filter(|item| **item == Item::DirtyGlass)
+        .count();
+
+    for action in action_events.iter() {
+        if let ActionEvent::Give(item) = action {
+            if !matches!(item, Item::WaterGlass | Item::Tea) || dirty_glasses >= MAX_DIRTY_GLASSES {
+                continue;
+            }
+            for (asking_item, position) in item_askers.iter() {
+                if asking_item.0 == *item {
+                    let offset = Vec3::new(-150.0 - 60.0 * dirty_glasses as f32, 80.0, 0.0);
+                    dirty_glasses += 1;
+                    spawn_dropped_item(
+                        &mut commands,
+                        &materials,
+                        Item::DirtyGlass,
+                        Freshness::default(),
+                        Position((position.0 + offset).truncate().extend(0.0)),
+                        dropped_item_area(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Query filter for Baobei while awake
+type AwakeBaobei = (With<Baobei>, Without<Napping>);
+
+/// Handles the actions on the furniture:
+/// - Move the item in hand on the table when storing, and back when retrieving
+/// - Make Baobei nap when Didi asks it on the couch, if it is tired
+/// - Remove the dirty glass in hand when washing, the stock taking it back
+#[allow(clippy::too_many_arguments)]
+fn handle_furniture_actions_system(
+    mut commands: Commands,
+    mut action_events: EventReader<ActionEvent>,
+    game_data: Res<GameData>,
+    config: Res<FurnitureConfig>,
+    carried_items: Query<Entity, With<CarriedItem>>,
+    items: Query<&Item>,
+    sleepers: Query<(Entity, &Rest), AwakeBaobei>,
+    mut interactables: Query<(&mut Interactable, &Position)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let didi = game_data.didi_entity;
+
+    for action in action_events.iter() {
+        match action {
+            ActionEvent::Store(entity, item) => {
+                if let Ok((mut interactable, position)) = interactables.get_mut(*entity) {
+                    if let Interactable::Table {
+                        items: table_items, ..
+                    } = &mut *interactable
+                    {
+                        info!("Leave {:?} on the table", item);
+                        commands.entity(didi).remove::<Carrying>();
+
+                        for item_in_hand in carried_items.iter() {
+                            commands
+                                .entity(item_in_hand)
+                                .remove::<Parent>()
+                                .remove::<CarriedItem>()
+                                .insert(OnTable)
+                                .insert(Position(
+                                    position.0 + table_slot_offset(table_items.len()),
+                                ));
+                            if let Ok(mut transform) = transforms.get_mut(item_in_hand) {
+                                transform.scale = didi_scale();
+                            }
+                            table_items.push(item_in_hand);
+                        }
+                    }
+                }
+            }
+            ActionEvent::Retrieve(entity) => {
+                let item_entity = match interactables.get_mut(*entity) {
+                    Ok((mut interactable, _)) => match &mut *interactable {
+                        Interactable::Table {
+                            items: table_items, ..
+                        } => table_items.pop(),
+                        _ => None,
+                    },
+                    Err(_) => None,
+                };
+                let (item_entity, item) = match item_entity
+                    .and_then(|entity| items.get(entity).ok().map(|item| (entity, *item)))
+                {
+                    Some(item) => item,
+                    None => continue,
+                };
+                info!("Take {:?} from the table", item);
+
+                commands
+                    .entity(didi)
+                    .insert(Carrying(item))
+                    .push_children(&[item_entity]);
+                commands
+                    .entity(item_entity)
+                    .insert(CarriedItem)
+                    .remove::<Position>()
+                    .remove::<OnTable>();
+                if let Ok(mut transform) = transforms.get_mut(item_entity) {
+                    transform.translation = picked_item_translation();
+                    transform.scale = Vec3::ONE;
+                }
+            }
+            ActionEvent::Nap(_) => {
+                for (baobei, rest) in sleepers.iter() {
+                    if config.is_tired(*rest) {
+                        info!("Baobei naps on the couch");
+                        commands.entity(baobei).insert(Napping);
+                    }
+                }
+            }
+            ActionEvent::Wash(_, item) => {
+                info!("Wash {:?} in the sink", item);
+                commands.entity(didi).remove::<Carrying>();
+                for item_in_hand in carried_items.iter() {
+                    commands.entity(item_in_hand).despawn();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Tires Baobei over time and rests it while napping, making its happiness
+/// decrease faster when tired.
+fn rest_system(
+    mut commands: Commands,
+    config: Res<FurnitureConfig>,
+    mut baobei_query: Query<(Entity, &mut Rest, Option<&Napping>, &mut Modifiers), With<Baobei>>,
+) {
+    for (baobei, mut rest, napping, mut modifiers) in baobei_query.iter_mut() {
+        if napping.is_some() {
+            rest.0 = (rest.0 + STEP.as_secs_f32() / config.nap_duration).min(1.0);
+            if rest.0 >= 1.0 {
+                info!("Baobei wakes up");
+                commands.entity(baobei).remove::<Napping>();
+            }
+        } else {
+            rest.0 = (rest.0 - STEP.as_secs_f32() / config.awake_duration).max(0.0);
+        }
+
+        let tired = config.is_tired(*rest);
+        if tired != modifiers.contains(ModifierKind::Tired) {
+            if tired {
+                modifiers.add(Modifier {
+                    kind: ModifierKind::Tired,
+                    decay_factor: config.tired_decay_factor,
+                    timer: None,
+                });
+            } else {
+                modifiers.remove(ModifierKind::Tired);
+            }
+        }
+    }
+}
+
+/// Clears the tables and wakes Baobei up for the new round.
+fn reset_furniture_system(
+    mut commands: Commands,
+    mut interactables: Query<&mut Interactable>,
+    mut baobei_query: Query<(Entity, &mut Rest), With<Baobei>>,
+) {
+    for mut interactable in interactables.iter_mut() {
+        if let Interactable::Table { items, .. } = &mut *interactable {
+            items.clear();
+        }
+    }
+    for (baobei, mut rest) in baobei_query.iter_mut() {
+        *rest = Rest::default();
+        commands.entity(baobei).remove::<Napping>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Entity;
+
+    use super::{ActionEvent, Interactable, Item};
+
+    #[test]
+    fn test_furniture_actions() {
+        let entity = Entity::new(1);
+        let mut table = Interactable::table(1);
+        assert_eq!(table.action(entity, None, false), None);
+        assert_eq!(
+            table.action(entity, Some(Item::Chips), false),
+            Some(ActionEvent::Store(entity, Item::Chips))
+        );
+
+        table = Interactable::Table {
+            items: vec![Entity::new(2)],
+            capacity: 1,
+        };
+        assert_eq!(table.action(entity, Some(Item::Chips), false), None);
+        assert_eq!(
+            table.action(entity, None, false),
+            Some(ActionEvent::Retrieve(entity))
+        );
+
+        let sink = Interactable::Sink;
+        assert_eq!(sink.action(entity, Some(Item::Chips), false), None);
+        assert_eq!(
+            sink.action(entity, Some(Item::DirtyGlass), false),
+            Some(ActionEvent::Wash(entity, Item::DirtyGlass))
+        );
+
+        let couch = Interactable::Couch;
+        assert_eq!(
+            couch.action(entity, None, true),
+            Some(ActionEvent::Nap(entity))
+        );
+        assert_eq!(couch.action(entity, None, false), None);
+        assert_eq!(couch.action(entity, Some(Item::Chips), true), None);
+    }
+}
@@ -2,12 +2,21 @@
 use bevy::prelude::*;
 
 use crate::{
-    collisions::Position,
-    constants::{GameState, HAPPINESS_DECREASE},
-    drawing::UiObject,
+    clock::{FixedUpdateStage, STEP},
+    constants::GameState,
+    difficulty::DifficultyConfig,
 };
 
-use super::{items::ItemSystems, materials::GameplayMaterials};
+use super::{
+    items::ItemSystems,
+    materials::EMOTION_COUNT,
+    modifiers::{ModifierConfig, Modifiers},
+    score::Score,
+};
+
+/// Label for systems changing the happiness over time
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct HappinessSystems;
 
 /// Plugin managing the happiness value.
 pub struct HappinessPlugin;
@@ -15,19 +24,28 @@ pub struct HappinessPlugin;
 impl Plugin for HappinessPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.insert_resource(HappinessTimer::default())
-            .add_startup_system(spawn_happiness_smiley.system())
+            .register_type::<Happiness>()
+            .add_event::<HappinessChangeEvent>()
             .add_startup_system(spawn_debug_text.system())
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame).with_system(
+                    decrease_happiness_system
+                        .system()
+                        .label(HappinessSystems)
+                        .after(ItemSystems),
+                ),
+            )
             .add_system_set(
-                SystemSet::on_update(GameState::InGame)
-                    .with_system(decrease_happiness_system.system())
-                    .with_system(text_update_system.system())
-                    .with_system(update_happiness_sprite_system.system().after(ItemSystems)),
+                SystemSet::on_update(GameState::InGame).with_system(text_update_system.system()),
             );
     }
 }
 
 /// Component representing the  for the happiness of the entity (Baobei).
 /// Between 0 and 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component)]
 pub struct Happiness(f32);
 
 impl Happiness {
@@ -36,6 +54,17 @@ impl Happiness {
         Self(1.0)
     }
 
+    /// Returns the happiness value, between 0 and 1.
+    pub const fn value(self) -> f32 {
+        self.0
+    }
+
+    /// Returns the emotion shown for this happiness, from 0 (the saddest) to
+    /// `EMOTION_COUNT - 1` (the happiest).
+    pub fn emotion(self) -> usize {
+        emotion_of(self.0)
+    }
+
     /// Adds the given value and clamps the result between 0 and 1
     pub fn add(&mut self, value: f32) {
         self.0 += value;
@@ -55,25 +84,22 @@ impl Happiness {
     }
 }
 
-/// Spawn boarders of the room, avoiding the user to go out of the screen.
-fn spawn_happiness_smiley(mut commands: Commands, materials: Res<GameplayMaterials>) {
-    commands
-        .spawn()
-        .insert(UiObject)
-        .insert(Position(Vec3::new(1125.0, 300.0, 0.0)))
-        .insert_bundle(SpriteSheetBundle {
-            texture_atlas: materials.emotion_atlas.clone(),
-            transform: Transform::from_scale(Vec3::splat(0.3)),
-            sprite: TextureAtlasSprite {
-                index: 4,
-                ..TextureAtlasSprite::default()
-            },
-            ..SpriteSheetBundle::default()
-        });
+/// Returns the emotion shown for the happiness value, from 0 (the saddest)
+/// to `EMOTION_COUNT - 1` (the happiest).
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+pub fn emotion_of(value: f32) -> usize {
+    ((value * EMOTION_COUNT as f32) as usize).min(EMOTION_COUNT - 1)
+}
+
+/// Event sent when an action of the player changes the happiness of Baobei.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HappinessChangeEvent {
+    /// Happiness gained, or lost when negative.
+    pub change: f32,
 }
 
 /// Timer of the decrease of the happiness over time.
-struct HappinessTimer(Timer);
+pub struct HappinessTimer(pub Timer);
 
 impl Default for HappinessTimer {
     fn default() -> Self {
@@ -81,37 +107,23 @@ impl Default for HappinessTimer {
     }
 }
 
-/// Update the Happiness smiley image depending on the new happiness value.
-fn update_happiness_sprite_system(
-    texture_atlases: Res<Assets<TextureAtlas>>,
-    mut sprites: Query<(&mut TextureAtlasSprite, &Handle<TextureAtlas>)>,
-    happiness_values: Query<&Happiness, Changed<Happiness>>,
-) {
-    for happiness_value in happiness_values.iter() {
-        for (mut sprite, texture_atlas_handle) in sprites.iter_mut() {
-            let texture_atlas = texture_atlases.get(texture_atlas_handle).unwrap();
-            let nb_sprites = texture_atlas.textures.len() as f32;
-
-            // Happiness is between 0 and 1 and the result index is a small number
-            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-            let sprite_index = (happiness_value.0 * nb_sprites) as u32;
-
-            sprite.index = sprite_index;
-        }
-    }
-}
-
-/// Update the value of the happiness text.
+/// Decreases the happiness every second, along the decay curve and the
+/// modifiers.
 fn decrease_happiness_system(
-    time: Res<Time>,
     mut timer: ResMut<HappinessTimer>,
-    mut happiness_values: Query<&mut Happiness>,
+    config: Res<DifficultyConfig>,
+    modifier_config: Res<ModifierConfig>,
+    score: Res<Score>,
+    mut happiness_values: Query<(&mut Happiness, Option<&Modifiers>)>,
 ) {
-    if !timer.0.tick(time.delta()).just_finished() {
+    if !timer.0.tick(STEP).just_finished() {
         return;
     }
-    for mut happiness in happiness_values.iter_mut() {
-        happiness.sub(HAPPINESS_DECREASE);
+    for (mut happiness, modifiers) in happiness_values.iter_mut() {
+        let decrease = config.happiness_decrease(score.time_survived)
+            * modifier_config.decay_curve.factor(happiness.0)
+            * modifiers.map_or(1.0, Modifiers::decay_factor);
+        happiness.sub(decrease);
     }
 }
 
@@ -144,11 +156,21 @@ pub fn spawn_debug_text(mut commands: Commands, asset_server: Res<AssetServer>)
 /// Update the value of the happiness text.
 fn text_update_system(
     mut happiness_text: Query<&mut Text, With<HappinessText>>,
-    happiness_value: Query<&Happiness, Changed<Happiness>>,
+    happiness_values: Query<(&Happiness, Option<&Modifiers>)>,
 ) {
+    let (happiness, modifiers) = match happiness_values.iter().next() {
+        Some(values) => values,
+        None => return,
+    };
+    let mut value = format!("Happiness: {:.2}", happiness.0);
+    for modifier in modifiers.iter().flat_map(|modifiers| &modifiers.0) {
+        value.push('\n');
+        value.push_str(&modifier.describe());
+    }
+
     for mut text in happiness_text.iter_mut() {
-        if let Some(value) = happiness_value.iter().next() {
-            text.sections[0].value = format!("Happiness: {:.2}", value.0);
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
         }
     }
 }
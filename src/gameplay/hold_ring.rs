@@ -0,0 +1,161 @@
+//! Ring around the target of a timed action, filling up while Didi holds
+//! interact to clean, restock or prepare.
+
+use bevy::{prelude::*, sprite::SpriteResizeMode};
+
+use crate::{
+    collisions::{Contact, Position},
+    constants::GameState,
+    controllers::InteractAction,
+    drawing::UiObject,
+};
+
+use super::{
+    chores::{ChoreConfig, Mess},
+    crafting::Workstation,
+    entities::GameData,
+    materials::GameplayMaterials,
+    patience::{spawn_ring_dots, visible_dots},
+    stock::{Stock, StockConfig},
+};
+
+/// Radius of the ring around the target.
+const RING_RADIUS: f32 = 60.0;
+
+/// Plugin displaying the progress of the timed actions.
+pub struct HoldRingPlugin;
+
+impl Plugin for HoldRingPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(spawn_hold_ring.system())
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(update_hold_ring_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame).with_system(hide_hold_ring_system.system()),
+            );
+    }
+}
+
+/// Tag the ring showing the progress of the timed action.
+pub struct HoldRing;
+
+/// A dot of the hold ring, with its position in the ring starting at the top
+/// and going clockwise.
+pub struct HoldDot(usize);
+
+/// Spawns the dots of the ring, hidden until Didi holds interact.
+fn spawn_hold_ring(mut commands: Commands, materials: Res<GameplayMaterials>) {
+    let sprite = SpriteBundle {
+        material: materials.preparation.clone(),
+        sprite: Sprite {
+            size: Vec2::splat(10.0),
+            resize_mode: SpriteResizeMode::Manual,
+            ..Sprite::default()
+        },
+        visible: Visible {
+            is_visible: false,
+            is_transparent: true,
+        },
+        ..SpriteBundle::default()
+    };
+
+    commands
+        .spawn()
+        .insert(HoldRing)
+        .insert(UiObject)
+        .insert(Position::default())
+        .insert(Transform::default())
+        .insert(GlobalTransform::default())
+        .with_children(|parent| {
+            spawn_ring_dots(parent, RING_RADIUS, &sprite, HoldDot);
+        });
+}
+
+/// Fills the ring around the mess, the producer or the workstation with the
+/// progress of the action Didi does by holding interact, once the press is
+/// longer than a tap.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn update_hold_ring_system(
+    game_data: Res<GameData>,
+    interact: Res<InteractAction>,
+    chore_config: Res<ChoreConfig>,
+    stock_config: Res<StockConfig>,
+    contacts: Query<&Contact>,
+    messes: Query<(&Mess, &Position)>,
+    stocks: Query<(&Stock, &Position)>,
+    workstations: Query<(&Workstation, &Position)>,
+    mut rings: Query<
+        &mut Position,
+        (
+            With<HoldRing>,
+            Without<Mess>,
+            Without<Stock>,
+            Without<Workstation>,
+        ),
+    >,
+    mut dots: Query<(&HoldDot, &mut Visible)>,
+) {
+    let didi = game_data.didi_entity;
+    let progress = if interact.is_held() {
+        contacts
+            .iter()
+            .filter(|contact| contact.0 == didi)
+            .find_map(|contact| {
+                let cleaning = messes
+                    .get(contact.1)
+                    .ok()
+                    .filter(|(mess, _)| mess.cleaning > 0.0)
+                    .map(|(mess, position)| {
+                        (mess.cleaning / chore_config.cleaning_duration, position)
+                    });
+                let restocking = || {
+                    stocks
+                        .get(contact.1)
+                        .ok()
+                        .filter(|(stock, _)| stock.restocking > 0.0)
+                        .map(|(stock, position)| {
+                            (stock.restocking / stock_config.restock_duration, position)
+                        })
+                };
+                let preparing = || {
+                    workstations
+                        .get(contact.1)
+                        .ok()
+                        .and_then(|(workstation, position)| {
+                            workstation.progress().map(|progress| (progress, position))
+                        })
+                };
+                cleaning.or_else(restocking).or_else(preparing)
+            })
+    } else {
+        None
+    };
+
+    if let Some((_, target_position)) = progress {
+        let ring_position = target_position.0.truncate().extend(0.0);
+        for mut position in rings.iter_mut() {
+            if position.0 != ring_position {
+                position.0 = ring_position;
+            }
+        }
+    }
+
+    let shown = progress.map_or(0, |(fraction, _)| visible_dots(fraction));
+    for (dot, mut visible) in dots.iter_mut() {
+        let is_visible = dot.0 < shown;
+        if visible.is_visible != is_visible {
+            visible.is_visible = is_visible;
+        }
+    }
+}
+
+/// Hides the ring when leaving the game.
+fn hide_hold_ring_system(mut dots: Query<&mut Visible, With<HoldDot>>) {
+    for mut visible in dots.iter_mut() {
+        if visible.is_visible {
+            visible.is_visible = false;
+        }
+    }
+}
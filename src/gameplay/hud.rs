@@ -0,0 +1,363 @@
+//! Heads-up display of the happiness of Baobei: the smiley, the happiness
+//! bar and the popups of the happiness changes.
+
+use bevy::{prelude::*, sprite::SpriteResizeMode};
+
+use crate::{clock::GameClock, collisions::Position, constants::GameState, drawing::UiObject};
+
+use super::{
+    entities::GameData,
+    happiness::{emotion_of, Happiness, HappinessChangeEvent},
+    materials::GameplayMaterials,
+    round::NewRoundSystems,
+    Baobei,
+};
+
+/// Speed at which the displayed happiness catches up with the real one,
+/// as a fraction of the difference per second.
+const SMOOTHING_SPEED: f32 = 4.0;
+
+/// Happiness under which the HUD pulses.
+const CRITICAL_HAPPINESS: f32 = 0.25;
+
+/// Number of pulses per second when the happiness is critical.
+const PULSE_FREQUENCY: f32 = 2.0;
+
+/// Increase of the scale at the peak of a pulse.
+const PULSE_AMPLITUDE: f32 = 0.15;
+
+/// Duration of a popup, in seconds.
+const POPUP_DURATION: f32 = 1.0;
+
+/// Speed at which popups rise, in pixels per second.
+const POPUP_SPEED: f32 = 80.0;
+
+/// Vertical space between the popups shown at the same time, in pixels.
+const POPUP_SPACING: f32 = 45.0;
+
+/// Plugin displaying the happiness of Baobei.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<DisplayedHappiness>()
+            .add_startup_system(spawn_happiness_smiley.system())
+            .add_startup_system(spawn_happiness_bar.system())
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(smooth_happiness_system.system().label("smooth_happiness"))
+                    .with_system(update_smiley_system.system().after("smooth_happiness"))
+                    .with_system(
+                        update_happiness_bar_system
+                            .system()
+                            .after("smooth_happiness"),
+                    )
+                    .with_system(pulse_system.system().after("smooth_happiness"))
+                    .with_system(spawn_popups_system.system())
+                    .with_system(animate_popups_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .label(NewRoundSystems)
+                    .with_system(reset_displayed_happiness_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame).with_system(despawn_popups_system.system()),
+            );
+    }
+}
+
+/// Happiness shown by the HUD, following the happiness of Baobei smoothly.
+pub struct DisplayedHappiness(pub f32);
+
+impl Default for DisplayedHappiness {
+    fn default() -> Self {
+        Self(Happiness::happy().value())
+    }
+}
+
+/// Tag the smiley showing the emotion of Baobei.
+struct Smiley;
+
+/// Tag the filled part of the happiness bar.
+struct HappinessBarFill;
+
+/// Component of HUD elements pulsing when the happiness is critical.
+struct Pulsing {
+    /// Scale of the element when not pulsing.
+    scale: Vec3,
+}
+
+/// A text showing a change of happiness, rising and fading above Baobei.
+pub struct Popup {
+    /// Time before the popup disappears.
+    timer: Timer,
+}
+
+/// Spawns the smiley showing the emotion of Baobei.
+fn spawn_happiness_smiley(mut commands: Commands, materials: Res<GameplayMaterials>) {
+    let scale = Vec3::splat(0.3);
+
+    commands
+        .spawn()
+        .insert(Smiley)
+        .insert(UiObject)
+        .insert(Pulsing { scale })
+        .insert(Position(Vec3::new(1125.0, 300.0, 0.0)))
+        .insert_bundle(SpriteSheetBundle {
+            texture_atlas: materials.emotion_atlas.clone(),
+            transform: Transform::from_scale(scale),
+            sprite: TextureAtlasSprite {
+                index: smiley_index(DisplayedHappiness::default().0),
+                ..TextureAtlasSprite::default()
+            },
+            ..SpriteSheetBundle::default()
+        });
+}
+
+/// Spawns the happiness bar, under the smiley.
+fn spawn_happiness_bar(
+    mut commands: Commands,
+    gameplay_materials: Res<GameplayMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let fill_material = materials.add(happiness_color(DisplayedHappiness::default().0).into());
+
+    commands
+        .spawn()
+        .insert(UiObject)
+        .insert(Pulsing { scale: Vec3::ONE })
+        .insert(Position(Vec3::new(1125.0, 200.0, 0.0)))
+        .insert_bundle(SpriteBundle {
+            material: gameplay_materials.hud_background.clone(),
+            sprite: Sprite {
+                size: bar_size() + Vec2::splat(8.0),
+                resize_mode: SpriteResizeMode::Manual,
+                ..Sprite::default()
+            },
+            ..SpriteBundle::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn()
+                .insert(HappinessBarFill)
+                .insert_bundle(SpriteBundle {
+                    material: fill_material,
+                    sprite: Sprite {
+                        size: bar_size(),
+                        resize_mode: SpriteResizeMode::Manual,
+                        ..Sprite::default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..SpriteBundle::default()
+                });
+        });
+}
+
+/// Returns the size of the happiness bar when Baobei is fully happy.
+fn bar_size() -> Vec2 {
+    Vec2::new(200.0, 24.0)
+}
+
+/// Returns the index of the smiley sprite for the happiness value.
+#[allow(clippy::cast_possible_truncation)]
+fn smiley_index(value: f32) -> u32 {
+    emotion_of(value) as u32
+}
+
+/// Returns the color of the bar for the happiness value, from red when
+/// Baobei is sad to green when it is happy, through yellow.
+fn happiness_color(value: f32) -> Color {
+    let value = value.clamp(0.0, 1.0);
+    Color::rgb((2.0 * (1.0 - value)).min(1.0), (2.0 * value).min(1.0), 0.0)
+}
+
+/// Returns the text of a popup for the happiness change, as a percentage.
+fn popup_text(change: f32) -> String {
+    format!("{:+.0}%", change * 100.0)
+}
+
+/// Moves the displayed happiness toward the happiness of Baobei.
+fn smooth_happiness_system(
+    clock: Res<GameClock>,
+    mut displayed: ResMut<DisplayedHappiness>,
+    happiness_values: Query<&Happiness, With<Baobei>>,
+) {
+    if let Some(happiness) = happiness_values.iter().next() {
+        let target = happiness.value();
+        let difference = target - displayed.0;
+        if difference.abs() <= f32::EPSILON {
+            return;
+        }
+        displayed.0 = if difference.abs() < 0.001 {
+            target
+        } else {
+            displayed.0 + difference * (SMOOTHING_SPEED * clock.delta().as_secs_f32()).min(1.0)
+        };
+    }
+}
+
+/// Shows the emotion of the displayed happiness.
+fn update_smiley_system(
+    displayed: Res<DisplayedHappiness>,
+    mut smileys: Query<&mut TextureAtlasSprite, With<Smiley>>,
+) {
+    if !displayed.is_changed() {
+        return;
+    }
+    for mut sprite in smileys.iter_mut() {
+        let index = smiley_index(displayed.0);
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
+
+/// Fills the happiness bar up to the displayed happiness, keeping it
+/// aligned on the left.
+fn update_happiness_bar_system(
+    displayed: Res<DisplayedHappiness>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut fills: Query<(&mut Sprite, &mut Transform, &Handle<ColorMaterial>), With<HappinessBarFill>>,
+) {
+    if !displayed.is_changed() {
+        return;
+    }
+    for (mut sprite, mut transform, material) in fills.iter_mut() {
+        sprite.size.x = bar_size().x * displayed.0;
+        transform.translation.x = (sprite.size.x - bar_size().x) / 2.0;
+
+        if let Some(material) = materials.get_mut(material) {
+            material.color = happiness_color(displayed.0);
+        }
+    }
+}
+
+/// Pulses the HUD while the happiness is critical.
+fn pulse_system(
+    clock: Res<GameClock>,
+    displayed: Res<DisplayedHappiness>,
+    mut elapsed: Local<f32>,
+    mut pulsing: Query<(&Pulsing, &mut Transform)>,
+) {
+    let factor = if displayed.0 < CRITICAL_HAPPINESS {
+        *elapsed += clock.delta().as_secs_f32();
+        let phase = *elapsed * PULSE_FREQUENCY * std::f32::consts::PI;
+        1.0 + PULSE_AMPLITUDE * phase.sin().abs()
+    } else {
+        1.0
+    };
+
+    for (pulsing, mut transform) in pulsing.iter_mut() {
+        let scale = pulsing.scale * factor;
+        if transform.scale != scale {
+            transform.scale = scale;
+        }
+    }
+}
+
+/// Shows the happiness changes above Baobei, stacking the ones of the same
+/// frame.
+#[allow(clippy::cast_precision_loss)]
+fn spawn_popups_system(
+    mut commands: Commands,
+    mut happiness_events: EventReader<HappinessChangeEvent>,
+    asset_server: Res<AssetServer>,
+    game_data: Res<GameData>,
+    positions: Query<&Position>,
+) {
+    for (index, event) in happiness_events.iter().enumerate() {
+        let baobei_position = match positions.get(game_data.baobei_entity) {
+            Ok(position) => position.0,
+            Err(_) => continue,
+        };
+        let color = if event.change >= 0.0 {
+            Color::GREEN
+        } else {
+            Color::RED
+        };
+
+        commands
+            .spawn()
+            .insert(UiObject)
+            .insert(Popup {
+                timer: Timer::from_seconds(POPUP_DURATION, false),
+            })
+            .insert(Position(
+                baobei_position + Vec3::new(0.0, 150.0 + POPUP_SPACING * index as f32, 0.0),
+            ))
+            .insert_bundle(Text2dBundle {
+                text: Text::with_section(
+                    popup_text(event.change),
+                    TextStyle {
+                        font: asset_server.load("FiraSans-Bold.ttf"),
+                        font_size: 40.0,
+                        color,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                ..Text2dBundle::default()
+            });
+    }
+}
+
+/// Raises and fades out the popups, then removes them.
+fn animate_popups_system(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut popups: Query<(Entity, &mut Popup, &mut Position, &mut Text)>,
+) {
+    for (entity, mut popup, mut position, mut text) in popups.iter_mut() {
+        if popup.timer.tick(clock.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        position.0.y += POPUP_SPEED * clock.delta().as_secs_f32();
+        for section in &mut text.sections {
+            section.style.color.set_a(1.0 - popup.timer.percent());
+        }
+    }
+}
+
+/// Shows the happiness of the new round without catching up with the last
+/// one.
+fn reset_displayed_happiness_system(mut displayed: ResMut<DisplayedHappiness>) {
+    *displayed = DisplayedHappiness::default();
+}
+
+/// Removes the popups when leaving the game.
+fn despawn_popups_system(mut commands: Commands, popups: Query<Entity, With<Popup>>) {
+    for entity in popups.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Color;
+
+    use super::{happiness_color, popup_text, smiley_index};
+
+    #[test]
+    fn test_happiness_color() {
+        assert_eq!(happiness_color(0.0), Color::rgb(1.0, 0.0, 0.0));
+        assert_eq!(happiness_color(0.5), Color::rgb(1.0, 1.0, 0.0));
+        assert_eq!(happiness_color(1.0), Color::rgb(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_smiley_index() {
+        assert_eq!(smiley_index(0.0), 0);
+        assert_eq!(smiley_index(0.79), 3);
+        assert_eq!(smiley_index(1.0), 4);
+    }
+
+    #[test]
+    fn test_popup_text() {
+        assert_eq!(popup_text(0.15), "+15%");
+        assert_eq!(popup_text(-0.15), "-15%");
+    }
+}
@@ -0,0 +1,256 @@
+//! Inventory of Didi: besides the item in hand, Didi stores items in the
+//! other slots of its inventory, cycles through them and refills its hand.
+//!
+//! The number of slots is set when a round starts, from the default of the
+//! difficulty and the upgrades, so the stored items are never lost.
+
+use bevy::{prelude::*, sprite::SpriteResizeMode};
+
+use crate::{
+    clock::FixedUpdateStage, collisions::Position, constants::GameState,
+    controllers::InteractInput, difficulty::DifficultyConfig, drawing::UiObject,
+};
+
+use super::{
+    entities::GameData,
+    freshness::Freshness,
+    items::{spawn_carried_item, CarriedItem, Carrying, Item, ItemSystems, PickAndDropCooldown},
+    materials::GameplayMaterials,
+    Didi,
+};
+
+/// Maximum number of slots shown by the HUD.
+const MAX_SLOTS: usize = 4;
+
+/// Size of a slot of the HUD.
+const SLOT_SIZE: f32 = 70.0;
+
+/// Plugin managing the inventory of Didi.
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<InventoryUpgrades>()
+            .add_startup_system(spawn_inventory_hud.system())
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(hand_system.system().after(ItemSystems)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(update_inventory_hud_system.system()),
+            );
+    }
+}
+
+/// Upgrades of the inventory, on top of the slots given by the difficulty.
+/// They are read when a round starts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InventoryUpgrades {
+    /// Slots added to the default number of the difficulty.
+    pub extra_slots: usize,
+}
+
+impl InventoryUpgrades {
+    /// Returns the number of slots of the inventory, including the hand, at
+    /// most the number shown by the HUD.
+    pub fn slots(self, config: &DifficultyConfig) -> usize {
+        (config.inventory_slots + self.extra_slots).min(MAX_SLOTS)
+    }
+}
+
+/// Component with the items carried by Didi besides the one in hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inventory {
+    /// Number of slots, including the hand.
+    pub capacity: usize,
+    /// Items stored in the other slots, with their freshness.
+    pub stored: Vec<(Item, Freshness)>,
+}
+
+impl Inventory {
+    /// Returns an empty inventory with the given number of slots.
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            stored: Vec::new(),
+        }
+    }
+
+    /// Returns true if no item can be stored besides the one in hand.
+    pub fn is_full(&self) -> bool {
+        self.stored.len() + 1 >= self.capacity
+    }
+
+    /// Returns true if the item is stored.
+    pub fn contains(&self, item: Item) -> bool {
+        self.stored
+            .iter()
+            .any(|(stored_item, _)| *stored_item == item)
+    }
+
+    /// Removes the item from the stored ones, returning its freshness.
+    pub fn take(&mut self, item: Item) -> Option<Freshness> {
+        let index = self
+            .stored
+            .iter()
+            .position(|(stored_item, _)| *stored_item == item)?;
+        Some(self.stored.remove(index).1)
+    }
+}
+
+/// A slot of the inventory HUD, the first one is the hand.
+struct InventorySlot(usize);
+
+/// Tag the icon of the item in a slot of the HUD.
+struct SlotIcon;
+
+/// Refills the empty hand of Didi with a stored item, and cycles the items
+/// through the hand when the cycle button is pressed since the last step.
+#[allow(clippy::too_many_arguments)]
+fn hand_system(
+    mut commands: Commands,
+    game_data: Res<GameData>,
+    materials: Res<GameplayMaterials>,
+    mut interact: ResMut<InteractInput>,
+    mut cooldown: ResMut<PickAndDropCooldown>,
+    mut was_cycling: Local<bool>,
+    mut inventories: Query<(&mut Inventory, Option<&Carrying>), With<Didi>>,
+    items_in_hand: Query<(Entity, &Item, &Freshness), With<CarriedItem>>,
+) {
+    let cycling = interact.cycle.pressed_at_step(*was_cycling);
+    let cycle = cycling && !*was_cycling && cooldown.0.available();
+    *was_cycling = cycling;
+
+    let didi = game_data.didi_entity;
+    let (mut inventory, carrying) = match inventories.get_mut(didi) {
+        Ok(didi) => didi,
+        Err(_) => return,
+    };
+    if inventory.stored.is_empty() || (carrying.is_some() && !cycle) {
+        return;
+    }
+
+    if carrying.is_some() {
+        cooldown.0.start(); // Avoid to do another action at once.
+        for (item_in_hand, item, freshness) in items_in_hand.iter() {
+            inventory.stored.push((*item, *freshness));
+            commands.entity(item_in_hand).despawn();
+        }
+    }
+    let (item, freshness) = inventory.stored.remove(0);
+    info!("Take {:?} in hand", item);
+    let item_in_hand = spawn_carried_item(&mut commands, &materials, didi, item);
+    commands.entity(item_in_hand).insert(freshness);
+}
+
+/// Spawns the slots of the inventory HUD, hidden until Didi has enough slots.
+fn spawn_inventory_hud(mut commands: Commands, materials: Res<GameplayMaterials>) {
+    for index in 0..MAX_SLOTS {
+        let material = if index == 0 {
+            materials.selected_slot.clone()
+        } else {
+            materials.hud_background.clone()
+        };
+
+        commands
+            .spawn()
+            .insert(InventorySlot(index))
+            .insert(UiObject)
+            .insert(Position(Vec3::new(
+                60.0 + index as f32 * (SLOT_SIZE + 10.0),
+                660.0,
+                0.0,
+            )))
+            .insert_bundle(SpriteBundle {
+                material,
+                sprite: Sprite {
+                    size: Vec2::splat(SLOT_SIZE),
+                    resize_mode: SpriteResizeMode::Manual,
+                    ..Sprite::default()
+                },
+                ..SpriteBundle::default()
+            })
+            .with_children(|parent| {
+                parent.spawn().insert(SlotIcon).insert_bundle(SpriteBundle {
+                    material: materials.none.clone(),
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 0.0, 0.1),
+                        scale: Vec3::new(0.4, 0.4, 1.0),
+                        ..Transform::default()
+                    },
+                    ..SpriteBundle::default()
+                });
+            });
+    }
+}
+
+/// Query filter for the icons of the slots, apart from the slots
+type SlotIconOnly = (With<SlotIcon>, Without<InventorySlot>);
+
+/// Shows the slots of the inventory and the items in them.
+fn update_inventory_hud_system(
+    materials: Res<GameplayMaterials>,
+    didi_query: Query<(&Inventory, Option<&Carrying>), With<Didi>>,
+    mut slots: Query<(&InventorySlot, &mut Visible, &Children)>,
+    mut icons: Query<(&mut Handle<ColorMaterial>, &mut Visible), SlotIconOnly>,
+) {
+    let (inventory, carrying) = match didi_query.iter().next() {
+        Some(didi) => didi,
+        None => return,
+    };
+
+    for (slot, mut visible, children) in slots.iter_mut() {
+        let is_visible = slot.0 < inventory.capacity;
+        if visible.is_visible != is_visible {
+            visible.is_visible = is_visible;
+        }
+
+        let sprite = if slot.0 == 0 {
+            carrying.map(|carrying| materials.item_sprite_for(carrying.0))
+        } else {
+            inventory.stored.get(slot.0 - 1).map(|(item, freshness)| {
+                if freshness.is_stale() {
+                    materials.stale_item_sprite_for(*item)
+                } else {
+                    materials.item_sprite_for(*item)
+                }
+            })
+        };
+
+        for child in children.iter() {
+            if let Ok((mut material, mut icon_visible)) = icons.get_mut(*child) {
+                let shown = is_visible && sprite.is_some();
+                if icon_visible.is_visible != shown {
+                    icon_visible.is_visible = shown;
+                }
+                if let Some(sprite) = &sprite {
+                    if *material != *sprite {
+                        *material = sprite.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Freshness, Inventory, Item};
+
+    #[test]
+    fn test_inventory() {
+        let mut inventory = Inventory::new(3);
+        assert!(!inventory.is_full());
+
+        inventory.stored.push((Item::Chips, Freshness::default()));
+        inventory.stored.push((Item::Tea, Freshness(0.5)));
+        assert!(inventory.is_full());
+        assert!(inventory.contains(Item::Tea));
+
+        assert_eq!(inventory.take(Item::Tea), Some(Freshness(0.5)));
+        assert_eq!(inventory.take(Item::Tea), None);
+        assert!(!inventory.is_full());
+    }
+}
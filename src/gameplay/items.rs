@@ -1,13 +1,37 @@
 //! Systems and components managing items in the game.
 
-use bevy::prelude::*;
-use rand::{distributions::Standard, prelude::Distribution, random, Rng};
-
-use super::{entities::GameData, happiness::Happiness, materials::GameplayMaterials, Baobei, Didi};
+use std::cmp::Ordering;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    chores::Mess,
+    crafting::{Recipes, Workstation},
+    entities::GameData,
+    freshness::{Freshness, FreshnessConfig, TrashBin},
+    furniture::{Interactable, Tiredness},
+    happiness::{Happiness, HappinessChangeEvent},
+    inventory::Inventory,
+    materials::GameplayMaterials,
+    modifiers::{Modifier, ModifierConfig, Modifiers},
+    movement::Facing,
+    score::Score,
+    stock::Stock,
+    Baobei, Didi,
+};
 use crate::{
+    clock::FixedUpdateStage,
     collisions::{Contact, Position, TriggerArea},
     constants::GameState,
-    cooldown::Cooldown,
+    controllers::{ControllerSystems, InteractAction},
+    cooldown::{
+        Cooldown, CooldownAppExt, CooldownFinishedEvent, CooldownResource, CooldownSystems,
+    },
+    difficulty::DifficultyConfig,
+    drawing::UiObject,
+    rng::GameRng,
 };
 
 /// Label for systems managing items
@@ -20,18 +44,47 @@ pub struct ItemsPlugin;
 impl Plugin for ItemsPlugin {
     fn build(&self, app: &mut AppBuilder) {
         app.add_event::<ActionEvent>()
-            .insert_resource(PickAndDropCooldown(Cooldown::from_seconds(0.2)))
-            .add_system_set(
+            .register_type::<Item>()
+            .register_type::<Carrying>()
+            .register_type::<AskingItem>()
+            .insert_cooldown_resource(PickAndDropCooldown(Cooldown::from_seconds(0.2)))
+            .init_resource::<HeldAction>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
                 SystemSet::on_update(GameState::InGame)
                     .label(ItemSystems)
-                    .with_system(pick_or_drop_system.system().label("item_actions"))
-                    .with_system(handle_actions_system.system().after("item_actions")),
+                    .with_system(
+                        held_action_system
+                            .system()
+                            .label("held_action")
+                            .after(ControllerSystems),
+                    )
+                    .with_system(
+                        pick_or_drop_system
+                            .system()
+                            .label("item_actions")
+                            .after(ControllerSystems)
+                            .after(CooldownSystems),
+                    )
+                    .with_system(
+                        handle_actions_system
+                            .system()
+                            .label("handle_actions")
+                            .after("item_actions"),
+                    )
+                    .with_system(
+                        change_request_system
+                            .system()
+                            .after("handle_actions")
+                            .after(CooldownSystems),
+                    ),
             );
     }
 }
 
 /// An items that can be produced, carried and received.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect_value(Component, PartialEq, Serialize, Deserialize)]
 pub enum Item {
     /// A delicious ice cream
     IceCream,
@@ -39,19 +92,107 @@ pub enum Item {
     WaterGlass,
     /// A bag of chips
     Chips,
+    /// A cup of tea, prepared in the kitchen
+    Tea,
+    /// A sandwich, prepared in the kitchen
+    Sandwich,
+    /// A slice of bread, ingredient of the sandwich
+    Bread,
+    /// A spoiled item, never asked, to throw away
+    Trash,
+    /// A sponge, never asked, to clean the messes
+    Sponge,
+    /// A glass left by Baobei, never asked, to wash in the sink
+    DirtyGlass,
 }
 
-impl Distribution<Item> for Standard {
-    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Item {
-        match rng.gen_range(0..=2) {
-            0 => Item::IceCream,
-            1 => Item::WaterGlass,
-            _ => Item::Chips,
+/// Only needed by the reflection, to create the component before applying
+/// its saved value.
+impl Default for Item {
+    fn default() -> Self {
+        Self::IceCream
+    }
+}
+
+impl Item {
+    /// Items given by the producers that Baobei asks for, in the order they
+    /// are asked with more difficulty.
+    pub const PRODUCED: [Self; 3] = [Self::IceCream, Self::WaterGlass, Self::Chips];
+
+    /// Items prepared in the kitchen, asked in every difficulty.
+    pub const CRAFTED: [Self; 2] = [Self::Tea, Self::Sandwich];
+
+    /// All the items.
+    pub const ALL: [Self; 9] = [
+        Self::IceCream,
+        Self::WaterGlass,
+        Self::Chips,
+        Self::Tea,
+        Self::Sandwich,
+        Self::Bread,
+        Self::Trash,
+        Self::Sponge,
+        Self::DirtyGlass,
+    ];
+
+    /// Returns the name of the item shown to the player.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::IceCream => "Ice Cream",
+            Self::WaterGlass => "Water Glass",
+            Self::Chips => "Chips",
+            Self::Tea => "Tea",
+            Self::Sandwich => "Sandwich",
+            Self::Bread => "Bread",
+            Self::Trash => "Trash",
+            Self::Sponge => "Sponge",
+            Self::DirtyGlass => "Dirty Glass",
+        }
+    }
+
+    /// Returns the clean item put back in the stock of its producer by
+    /// washing the item, if dirty.
+    pub const fn washed(self) -> Option<Self> {
+        match self {
+            Self::DirtyGlass => Some(Self::WaterGlass),
+            _ => None,
         }
     }
+
+    /// Returns true if the item spoils over time.
+    pub const fn is_perishable(self) -> bool {
+        !matches!(self, Self::Trash | Self::Sponge | Self::DirtyGlass)
+    }
+
+    /// Returns the items that spoil over time, and so have a stale sprite.
+    pub fn perishable() -> impl Iterator<Item = Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .filter(|item| item.is_perishable())
+    }
+
+    /// Returns the items Baobei can ask for: the first `count` produced
+    /// items, at least two, and the crafted items.
+    pub fn pool(count: usize) -> Vec<Self> {
+        let count = count.clamp(2, Self::PRODUCED.len());
+        Self::PRODUCED[..count]
+            .iter()
+            .chain(&Self::CRAFTED)
+            .copied()
+            .collect()
+    }
+
+    /// Returns a random item among the ones Baobei can ask for, see `pool`.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, count: usize) -> Self {
+        let pool = Self::pool(count);
+        pool[rng.gen_range(0..pool.len())]
+    }
 }
 
 /// Component on entities carrying an item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component)]
 pub struct Carrying(pub Item);
 
 /// Component on entities that is a carried item.
@@ -63,12 +204,15 @@ pub struct AskedItem;
 pub struct ItemProducer(pub Item);
 
 /// Component on entities that can ask for the item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component)]
 pub struct AskingItem(pub Item);
 
 /// An event about an action the player made.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ActionEvent {
     /// The player takes an item in the item producer.
-    Take(Item),
+    Take(Entity, Item),
     /// The player puts away the item back in the item producer.
     PutAway(Item),
     /// The player drops the item on the ground.
@@ -79,82 +223,341 @@ pub enum ActionEvent {
     Keep(Item),
     /// The player gives the item to Baobei.
     Give(Item),
+    /// The player deposits the item in the workstation, as an ingredient.
+    Deposit(Entity, Item),
+    /// The player collects the item from the workstation.
+    Collect(Entity, Item),
+    /// The player prepares the recipe in the workstation.
+    Prepare(Entity),
+    /// The player throws the item away in the trash bin.
+    ThrowAway(Item),
+    /// The player cleans the mess.
+    Clean(Entity),
+    /// The player restocks the empty item producer.
+    Restock(Entity),
+    /// The player leaves the item on the furniture.
+    Store(Entity, Item),
+    /// The player takes back the last item left on the furniture.
+    Retrieve(Entity),
+    /// The player lets Baobei nap on the furniture.
+    Nap(Entity),
+    /// The player washes the dirty item in the furniture, putting the clean
+    /// item back in its producer.
+    Wash(Entity, Item),
+}
+
+/// Returns the patience of Baobei for the current request, as its cooldown:
+/// when it finishes, Baobei changes its mind and asks for another item.
+pub fn request_cooldown(config: &DifficultyConfig) -> Cooldown {
+    Cooldown::from_seconds(config.request_interval).repeating()
 }
 
 /// Cooldown of the action of picking or dropping items.
 pub struct PickAndDropCooldown(pub Cooldown);
 
-/// Pick or drop an item in an item producer.
-#[allow(clippy::too_many_arguments)]
-pub fn pick_or_drop_system(
-    time: Res<Time>,
-    game_data: Res<GameData>,
-    mut cooldown: ResMut<PickAndDropCooldown>,
-    keyboard: Res<Input<KeyCode>>,
-    mut action_events: EventWriter<ActionEvent>,
-    contacts: Query<&Contact>,
-    item_producers: Query<&ItemProducer>,
-    item_askers: Query<&AskingItem>,
-    items: Query<(Entity, &Item)>,
-    carriers: Query<&Carrying, With<Didi>>,
-) {
-    if !cooldown.0.tick(time.delta_seconds()).available() || !keyboard.pressed(KeyCode::Space) {
-        return;
+impl CooldownResource for PickAndDropCooldown {
+    fn cooldown_mut(&mut self) -> &mut Cooldown {
+        &mut self.0
     }
-    let didi = game_data.didi_entity;
+}
 
-    let carried_item = carriers.get(didi);
-
-    // Pick or put away an item in a producer
-    contacts
-        .iter()
-        .filter(|contact| contact.0 == didi)
-        .filter_map(|contact| item_producers.get(contact.1).ok())
-        .for_each(|ItemProducer(produced_item)| {
-            match carried_item {
-                Ok(Carrying(item)) if (item == produced_item) => {
-                    action_events.send(ActionEvent::PutAway(*item))
-                }
-                Ok(Carrying(item)) => action_events.send(ActionEvent::Keep(*item)),
-                _ => action_events.send(ActionEvent::Take(*produced_item)),
-            }
-            cooldown.0.start();
-        });
+/// An action Didi does when interacting, with the entity it targets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interaction {
+    /// Action sent when interacting.
+    pub action: ActionEvent,
+    /// Entity the action is about, none when dropping the item in hand.
+    pub target: Option<Entity>,
+    /// Whether the action is done by holding interact rather than tapping.
+    pub held: bool,
+}
 
-    if !cooldown.0.available() {
-        return; // Avoid to do more than one action at once.
+impl Interaction {
+    /// Returns the interaction doing the action on the target, held when
+    /// restocking, cleaning or preparing with empty hands.
+    const fn new(action: ActionEvent, target: Entity, carried_item: Option<Item>) -> Self {
+        Self {
+            action,
+            target: Some(target),
+            held: carried_item.is_none()
+                && matches!(
+                    action,
+                    ActionEvent::Restock(_) | ActionEvent::Clean(_) | ActionEvent::Prepare(_)
+                ),
+        }
     }
+}
+
+/// Priority of the interactions when Didi can do several at once, any
+/// interaction winning over the next ones whatever the distance. Giving comes
+/// first, for Didi never to miss a delivery next to other furniture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InteractionPriority {
+    /// Giving an item to Baobei.
+    Asker,
+    /// Napping, storing, retrieving or washing on the furniture.
+    Furniture,
+    /// Taking, keeping or putting away an item in a producer.
+    Producer,
+    /// Throwing away the item in a trash bin.
+    TrashBin,
+    /// Cleaning a mess, with the sponge or by hand.
+    Mess,
+    /// Depositing, preparing or collecting an item in a workstation.
+    Workstation,
+    /// Picking up an item on the ground.
+    Ground,
+}
 
-    // Give an item to baobei
-    if let Ok(Carrying(item)) = carried_item {
-        contacts
+/// Entities Didi can interact with, to decide what interacting does.
+#[derive(SystemParam)]
+pub struct InteractionTargets<'a> {
+    /// Contacts of Didi with the trigger areas.
+    contacts: Query<'a, &'static Contact>,
+    /// Recipes accepted by the workstations.
+    recipes: Res<'a, Recipes>,
+    /// Positions of Didi and of the targets, the UI objects being apart.
+    positions: Query<'a, &'static Position, Without<UiObject>>,
+    /// Side Didi looks toward.
+    facings: Query<'a, &'static Facing, With<Didi>>,
+    /// Producers of items.
+    item_producers: Query<'a, &'static ItemProducer>,
+    /// Workstations preparing items.
+    workstations: Query<'a, &'static Workstation>,
+    /// Bins to throw away items.
+    trash_bins: Query<'a, (), With<TrashBin>>,
+    /// Messes to clean.
+    messes: Query<'a, (), With<Mess>>,
+    /// Entities asking for items.
+    item_askers: Query<'a, &'static AskingItem>,
+    /// Items on the ground.
+    items: Query<'a, &'static Item>,
+    /// Item carried by Didi.
+    carriers: Query<'a, &'static Carrying, With<Didi>>,
+    /// Inventory of Didi.
+    inventories: Query<'a, &'static Inventory>,
+    /// Stocks of the producers.
+    stocks: Query<'a, &'static Stock>,
+    /// Furniture to interact with.
+    interactables: Query<'a, &'static Interactable>,
+    /// Tiredness of Baobei, to nap.
+    tiredness: Tiredness<'a>,
+}
+
+impl<'a> InteractionTargets<'a> {
+    /// Returns what interacting does for Didi: the action of highest priority
+    /// among the entities in contact, on the nearest one from its hand when
+    /// several have the same priority, or dropping the item in hand. A tap
+    /// action wins over a held one, only shown when nothing can be tapped.
+    pub fn interaction(&self, didi: Entity) -> Option<Interaction> {
+        let carried_item = self.carriers.get(didi).ok().map(|carrying| carrying.0);
+        let reach = self.reach(didi);
+
+        let best = self
+            .contacts
             .iter()
             .filter(|contact| contact.0 == didi)
-            .filter_map(|contact| item_askers.get(contact.1).ok())
-            .for_each(|_| {
-                action_events.send(ActionEvent::Give(*item));
-                cooldown.0.start();
+            .filter_map(|contact| {
+                let target = contact.1;
+                let (priority, action) = self.action_on(didi, target, carried_item)?;
+                let distance = match (reach, self.positions.get(target)) {
+                    (Some(reach), Ok(position)) => reach.distance(position.0.truncate()),
+                    _ => f32::INFINITY,
+                };
+                Some((
+                    Interaction::new(action, target, carried_item),
+                    priority,
+                    distance,
+                ))
+            })
+            .min_by(|(a, a_priority, a_distance), (b, b_priority, b_distance)| {
+                a.held
+                    .cmp(&b.held)
+                    .then(a_priority.cmp(b_priority))
+                    .then(
+                        a_distance
+                            .partial_cmp(b_distance)
+                            .unwrap_or(Ordering::Equal),
+                    )
+                    .then(
+                        a.target
+                            .map(Entity::to_bits)
+                            .cmp(&b.target.map(Entity::to_bits)),
+                    )
             });
+
+        match (best, carried_item) {
+            (Some((interaction, _, _)), _) => Some(interaction),
+            (None, Some(item)) => Some(Interaction {
+                action: ActionEvent::Drop(item),
+                target: None,
+                held: false,
+            }),
+            (None, None) => None,
+        }
     }
 
-    if !cooldown.0.available() {
-        return; // Avoid to do more than one action at once.
+    /// Returns the point on the ground in front of Didi, where its hand is.
+    fn reach(&self, didi: Entity) -> Option<Vec2> {
+        let position = self.positions.get(didi).ok()?;
+        let facing = self.facings.get(didi).ok()?;
+        let offset = facing.mirror(picked_item_translation()) * didi_scale();
+        Some((position.0 + offset).truncate())
     }
 
-    // Drop or pick up the item to the ground
-    if let Ok(Carrying(item)) = carried_item {
-        action_events.send(ActionEvent::Drop(*item));
-        cooldown.0.start();
+    /// Returns the action Didi can do on the target with its priority, if any.
+    fn action_on(
+        &self,
+        didi: Entity,
+        target: Entity,
+        carried_item: Option<Item>,
+    ) -> Option<(InteractionPriority, ActionEvent)> {
+        // Interact with the furniture
+        if let Ok(interactable) = self.interactables.get(target) {
+            let tired = self.tiredness.baobei_tired();
+            if let Some(action) = interactable.action(target, carried_item, tired) {
+                return Some((InteractionPriority::Furniture, action));
+            }
+        }
+
+        // Pick or put away an item in a producer
+        if let Ok(ItemProducer(produced_item)) = self.item_producers.get(target) {
+            let in_stock = self
+                .stocks
+                .get(target)
+                .map_or(true, |stock| !stock.is_empty());
+            let inventory_full = self.inventories.get(didi).map_or(true, Inventory::is_full);
+            let action = match carried_item {
+                Some(item) if item == *produced_item => ActionEvent::PutAway(item),
+                Some(item) if inventory_full || !in_stock => ActionEvent::Keep(item),
+                _ if in_stock => ActionEvent::Take(target, *produced_item),
+                _ => ActionEvent::Restock(target),
+            };
+            return Some((InteractionPriority::Producer, action));
+        }
+
+        match carried_item {
+            Some(item) => {
+                // Throw away an item in a trash bin
+                if self.trash_bins.get(target).is_ok() {
+                    return Some((InteractionPriority::TrashBin, ActionEvent::ThrowAway(item)));
+                }
+
+                // Clean a mess with the sponge
+                if item == Item::Sponge && self.messes.get(target).is_ok() {
+                    return Some((InteractionPriority::Mess, ActionEvent::Clean(target)));
+                }
+
+                // Deposit an ingredient in a workstation
+                if let Ok(workstation) = self.workstations.get(target) {
+                    if workstation.accepts(&self.recipes, item) {
+                        let action = ActionEvent::Deposit(target, item);
+                        return Some((InteractionPriority::Workstation, action));
+                    }
+                }
+
+                // Give an item to baobei, the asked one if it is in any slot
+                if let Ok(AskingItem(asked_item)) = self.item_askers.get(target) {
+                    let stored = self
+                        .inventories
+                        .get(didi)
+                        .map_or(false, |inventory| inventory.contains(*asked_item));
+                    let item = if stored { *asked_item } else { item };
+                    return Some((InteractionPriority::Asker, ActionEvent::Give(item)));
+                }
+
+                // Pick up the item on the ground, storing the one in hand
+                let inventory_full = self.inventories.get(didi).map_or(true, Inventory::is_full);
+                if inventory_full {
+                    return None;
+                }
+                self.items.get(target).ok().map(|item| {
+                    (
+                        InteractionPriority::Ground,
+                        ActionEvent::PickUp(target, *item),
+                    )
+                })
+            }
+            None => {
+                // Clean a mess by hand
+                if self.messes.get(target).is_ok() {
+                    return Some((InteractionPriority::Mess, ActionEvent::Clean(target)));
+                }
+
+                // Prepare the recipe or collect an item in a workstation
+                if let Ok(workstation) = self.workstations.get(target) {
+                    if workstation.preparation.is_some() {
+                        let action = ActionEvent::Prepare(target);
+                        return Some((InteractionPriority::Workstation, action));
+                    }
+                    if let Some(item) = workstation.collectable() {
+                        let action = ActionEvent::Collect(target, item);
+                        return Some((InteractionPriority::Workstation, action));
+                    }
+                }
+
+                // Pick up the item on the ground
+                self.items.get(target).ok().map(|item| {
+                    (
+                        InteractionPriority::Ground,
+                        ActionEvent::PickUp(target, *item),
+                    )
+                })
+            }
+        }
+    }
+}
+
+/// Action of Didi while the player holds interact, if it is a held one: the
+/// timed actions only progress on its target.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HeldAction(pub Option<ActionEvent>);
+
+/// Decides the held action of Didi, for the timed actions to follow the same
+/// target as the prompt.
+fn held_action_system(
+    game_data: Res<GameData>,
+    interact: Res<InteractAction>,
+    mut held_action: ResMut<HeldAction>,
+    targets: InteractionTargets,
+) {
+    let action = if interact.is_held() {
+        targets
+            .interaction(game_data.didi_entity)
+            .filter(|interaction| interaction.held)
+            .map(|interaction| interaction.action)
     } else {
-        let item_on_the_ground = contacts
-            .iter()
-            .filter(|contact| contact.0 == didi)
-            .find_map(|contact| items.get(contact.1).ok());
+        None
+    };
+    if held_action.0 != action {
+        held_action.0 = action;
+    }
+}
+
+/// Sends the action of Didi when the player taps interact, holding it being
+/// left to the timed actions. A tap during the cooldown waits for its end.
+pub fn pick_or_drop_system(
+    game_data: Res<GameData>,
+    mut cooldown: ResMut<PickAndDropCooldown>,
+    interact: Res<InteractAction>,
+    mut pending_tap: Local<bool>,
+    mut action_events: EventWriter<ActionEvent>,
+    targets: InteractionTargets,
+) {
+    if interact.tapped() {
+        *pending_tap = true;
+    }
+    if !*pending_tap || !cooldown.0.available() {
+        return;
+    }
+    *pending_tap = false;
 
-        if let Some((item_entity, item)) = item_on_the_ground {
-            action_events.send(ActionEvent::PickUp(item_entity, *item));
+    match targets.interaction(game_data.didi_entity) {
+        Some(interaction) if !interaction.held => {
+            action_events.send(interaction.action);
             cooldown.0.start();
         }
+        _ => {}
     }
 }
 
@@ -165,105 +568,128 @@ pub fn pick_or_drop_system(
 pub fn handle_actions_system(
     mut commands: Commands,
     mut action_events: EventReader<ActionEvent>,
+    mut happiness_events: EventWriter<HappinessChangeEvent>,
     game_data: Res<GameData>,
+    mut rng: ResMut<GameRng>,
+    mut score: ResMut<Score>,
+    config: Res<DifficultyConfig>,
+    modifier_config: Res<ModifierConfig>,
+    freshness_config: Res<FreshnessConfig>,
     materials: Res<GameplayMaterials>,
-    carried_items: Query<Entity, With<CarriedItem>>,
-    mut baobei_query: Query<(&mut AskingItem, &mut Happiness), With<Baobei>>,
+    carried_items: Query<(Entity, &Item, &Freshness), With<CarriedItem>>,
+    mut baobei_query: Query<
+        (
+            &mut AskingItem,
+            &mut Happiness,
+            &mut Modifiers,
+            &mut Cooldown,
+        ),
+        With<Baobei>,
+    >,
     mut asked_item_materials: Query<&mut Handle<ColorMaterial>, With<AskedItem>>,
-    positions: Query<&Position>,
+    mut didi_query: Query<(&Position, &Facing, &mut Inventory)>,
     mut transforms: Query<&mut Transform>,
 ) {
     let didi = game_data.didi_entity;
-    let picked_item_translation = Vec3::new(-170.0, -10.0, 0.0);
-    let didi_scale = Vec3::new(0.3, 0.3, 0.0);
 
     for action in action_events.iter() {
         match action {
             ActionEvent::PutAway(item) => {
                 info!("Put way item {:?}", item);
-                commands.entity(didi).remove::<Carrying>();
-
-                for item_in_hand in carried_items.iter() {
-                    commands.entity(item_in_hand).despawn();
-                }
+                despawn_carried_items(&mut commands, didi, &carried_items);
+            }
+            ActionEvent::ThrowAway(item) => {
+                info!("Throw away the item {:?}", item);
+                despawn_carried_items(&mut commands, didi, &carried_items);
             }
             ActionEvent::Drop(item) => {
                 info!("Drop the item {:?}", item);
-                commands.entity(didi).remove::<Carrying>();
-
-                for item_to_drop in carried_items.iter() {
-                    let didi_position = positions.get(didi).unwrap();
-
-                    commands
-                        .entity(item_to_drop)
-                        .remove::<Parent>()
-                        .remove::<CarriedItem>()
-                        .insert_bundle((
-                            Position(didi_position.0 + picked_item_translation * didi_scale),
-                            TriggerArea::new(75.0, 100.0),
-                        ));
-
-                    if let Ok(mut transform) = transforms.get_mut(item_to_drop) {
-                        transform.scale = didi_scale;
-                    }
+                if let Ok((didi_position, facing, _)) = didi_query.get_mut(didi) {
+                    drop_carried_items(
+                        &mut commands,
+                        didi,
+                        &carried_items,
+                        &mut transforms,
+                        *didi_position,
+                        *facing,
+                    );
                 }
             }
             ActionEvent::PickUp(item_entity, item) => {
                 info!("Pick up the item {:?}", item);
-
-                commands
-                    .entity(didi)
-                    .insert(Carrying(*item))
-                    .push_children(&[*item_entity]);
-                commands
-                    .entity(*item_entity)
-                    .insert(CarriedItem)
-                    .remove::<Position>()
-                    .remove::<TriggerArea>();
-
-                if let Ok(mut transform) = transforms.get_mut(*item_entity) {
-                    transform.translation = picked_item_translation;
-                    transform.scale = Vec3::ONE;
+                if let Ok((_, _, mut inventory)) = didi_query.get_mut(didi) {
+                    store_item_in_hand(&mut commands, &carried_items, &mut inventory);
                 }
+                pick_up_item(&mut commands, didi, *item_entity, *item, &mut transforms);
             }
-            ActionEvent::Take(item) => {
+            ActionEvent::Take(_, item) => {
                 info!("Take item {:?}", item);
-
-                let item_in_hand = commands
-                    .spawn()
-                    .insert(*item)
-                    .insert(CarriedItem)
-                    .insert_bundle(SpriteBundle {
-                        material: materials.item_sprite_for(*item),
-                        transform: Transform::from_translation(picked_item_translation),
-                        ..SpriteBundle::default()
-                    })
-                    .id();
-
-                commands
-                    .entity(didi)
-                    .insert(Carrying(*item))
-                    .push_children(&[item_in_hand]);
+                if let Ok((_, _, mut inventory)) = didi_query.get_mut(didi) {
+                    store_item_in_hand(&mut commands, &carried_items, &mut inventory);
+                }
+                spawn_carried_item(&mut commands, &materials, didi, *item);
             }
             ActionEvent::Keep(item) => info!("Keep item {:?}", item),
+            // Handled by the crafting, chores, stock and furniture systems.
+            ActionEvent::Deposit(_, _)
+            | ActionEvent::Collect(_, _)
+            | ActionEvent::Prepare(_)
+            | ActionEvent::Clean(_)
+            | ActionEvent::Restock(_)
+            | ActionEvent::Store(_, _)
+            | ActionEvent::Retrieve(_)
+            | ActionEvent::Nap(_)
+            | ActionEvent::Wash(_, _) => {}
             ActionEvent::Give(item) => {
                 info!("Give item {:?}", item);
-                for (mut asking_item, mut happiness) in baobei_query.iter_mut() {
+                for (mut asking_item, mut happiness, mut modifiers, mut request_cooldown) in
+                    baobei_query.iter_mut()
+                {
                     if asking_item.0 != *item {
-                        happiness.sub(0.15);
+                        happiness.sub(config.penalty);
+                        happiness_events.send(HappinessChangeEvent {
+                            change: -config.penalty,
+                        });
                         return;
                     }
 
-                    happiness.add(0.15);
+                    // Give the item in hand, or the one stored in another slot
+                    let in_hand = carried_items
+                        .iter()
+                        .find(|(_, item_in_hand, _)| **item_in_hand == *item);
+                    let freshness = match in_hand {
+                        Some((_, _, freshness)) => Some(*freshness),
+                        None => didi_query
+                            .get_mut(didi)
+                            .ok()
+                            .and_then(|(_, _, mut inventory)| inventory.take(*item)),
+                    };
+                    let freshness_factor = freshness
+                        .map_or(1.0, |freshness| freshness_config.reward_factor(freshness));
+                    let reward =
+                        config.reward * modifier_config.satisfaction(*item) * freshness_factor;
+                    happiness.add(reward);
+                    happiness_events.send(HappinessChangeEvent { change: reward });
+                    let bonus = config.early_bonus(1.0 - request_cooldown.fraction_remaining())
+                        * freshness_factor;
+                    if bonus > 0.0 {
+                        happiness.add(bonus);
+                        happiness_events.send(HappinessChangeEvent { change: bonus });
+                    }
+                    if let Some(buff) = modifier_config.buff(*item) {
+                        modifiers.add(Modifier::from_buff(buff));
+                    }
+                    score.deliveries += 1;
 
                     // Remove item
-                    commands.entity(didi).remove::<Carrying>();
-                    for item_in_hand in carried_items.iter() {
+                    if let Some((item_in_hand, _, _)) = in_hand {
+                        commands.entity(didi).remove::<Carrying>();
                         commands.entity(item_in_hand).despawn();
                     }
 
                     // Add another item
-                    let next_item = random_different_item(*item);
+                    let next_item = random_different_item(&mut *rng, *item, config.item_count);
+                    request_cooldown.start();
                     for mut item_material in asked_item_materials.iter_mut() {
                         *item_material = materials.item_sprite_for(next_item);
                     }
@@ -274,10 +700,195 @@ pub fn handle_actions_system(
     }
 }
 
-/// Returns a random item different than the given one.
-fn random_different_item(item: Item) -> Item {
+/// Removes the item in the hand of Didi.
+fn despawn_carried_items(
+    commands: &mut Commands,
+    didi: Entity,
+    carried_items: &Query<(Entity, &Item, &Freshness), With<CarriedItem>>,
+) {
+    commands.entity(didi).remove::<Carrying>();
+
+    for (item_in_hand, _, _) in carried_items.iter() {
+        commands.entity(item_in_hand).despawn();
+    }
+}
+
+/// Drops the item in the hand of Didi on the ground, in front of Didi.
+fn drop_carried_items(
+    commands: &mut Commands,
+    didi: Entity,
+    carried_items: &Query<(Entity, &Item, &Freshness), With<CarriedItem>>,
+    transforms: &mut Query<&mut Transform>,
+    didi_position: Position,
+    facing: Facing,
+) {
+    commands.entity(didi).remove::<Carrying>();
+
+    let offset = facing.mirror(picked_item_translation());
+    for (item_to_drop, _, _) in carried_items.iter() {
+        commands
+            .entity(item_to_drop)
+            .remove::<Parent>()
+            .remove::<CarriedItem>()
+            .insert_bundle((
+                Position(didi_position.0 + offset * didi_scale()),
+                dropped_item_area(),
+            ));
+
+        if let Ok(mut transform) = transforms.get_mut(item_to_drop) {
+            transform.scale = didi_scale();
+        }
+    }
+}
+
+/// Stores the item in the hand of Didi in another slot of the inventory.
+fn store_item_in_hand(
+    commands: &mut Commands,
+    carried_items: &Query<(Entity, &Item, &Freshness), With<CarriedItem>>,
+    inventory: &mut Inventory,
+) {
+    for (item_in_hand, stored_item, freshness) in carried_items.iter() {
+        inventory.stored.push((*stored_item, *freshness));
+        commands.entity(item_in_hand).despawn();
+    }
+}
+
+/// Moves the item from the ground to the hand of Didi.
+fn pick_up_item(
+    commands: &mut Commands,
+    didi: Entity,
+    item_entity: Entity,
+    item: Item,
+    transforms: &mut Query<&mut Transform>,
+) {
+    commands
+        .entity(didi)
+        .insert(Carrying(item))
+        .push_children(&[item_entity]);
+    commands
+        .entity(item_entity)
+        .insert(CarriedItem)
+        .remove::<Position>()
+        .remove::<TriggerArea>();
+
+    if let Ok(mut transform) = transforms.get_mut(item_entity) {
+        transform.translation = picked_item_translation();
+        transform.scale = Vec3::ONE;
+    }
+}
+
+/// Spawns the item in the hand of Didi, returning the entity of the item.
+pub fn spawn_carried_item(
+    commands: &mut Commands,
+    materials: &GameplayMaterials,
+    didi: Entity,
+    item: Item,
+) -> Entity {
+    let item_in_hand = commands
+        .spawn()
+        .insert(item)
+        .insert(Freshness::default())
+        .insert(CarriedItem)
+        .insert_bundle(SpriteBundle {
+            material: materials.item_sprite_for(item),
+            transform: Transform::from_translation(picked_item_translation()),
+            ..SpriteBundle::default()
+        })
+        .id();
+
+    commands
+        .entity(didi)
+        .insert(Carrying(item))
+        .push_children(&[item_in_hand]);
+    item_in_hand
+}
+
+/// Spawns the item on the ground at the given position, with its freshness.
+pub fn spawn_dropped_item(
+    commands: &mut Commands,
+    materials: &GameplayMaterials,
+    item: Item,
+    freshness: Freshness,
+    position: Position,
+    trigger_area: TriggerArea,
+) {
+    commands
+        .spawn()
+        .insert(item)
+        .insert(freshness)
+        .insert(position)
+        .insert(trigger_area)
+        .insert_bundle(SpriteBundle {
+            material: materials.item_sprite_for(item),
+            transform: Transform::from_scale(didi_scale()),
+            ..SpriteBundle::default()
+        });
+}
+
+/// Returns the translation of an item in the hand of Didi, relative to Didi
+/// looking to the left.
+pub fn picked_item_translation() -> Vec3 {
+    Vec3::new(-170.0, -10.0, 0.0)
+}
+
+/// Returns the scale of the sprite of Didi, also applied to dropped items.
+pub fn didi_scale() -> Vec3 {
+    Vec3::new(0.3, 0.3, 0.0)
+}
+
+/// Returns the area where an item on the ground can be picked up.
+pub fn dropped_item_area() -> TriggerArea {
+    TriggerArea::new(75.0, 100.0)
+}
+
+/// Makes Baobei lose patience and ask for another item when its request
+/// cooldown finishes.
+fn change_request_system(
+    config: Res<DifficultyConfig>,
+    mut rng: ResMut<GameRng>,
+    materials: Res<GameplayMaterials>,
+    mut finished_events: EventReader<CooldownFinishedEvent>,
+    mut happiness_events: EventWriter<HappinessChangeEvent>,
+    mut item_askers: Query<(&mut AskingItem, &mut Happiness, &mut Cooldown)>,
+    mut asked_item_materials: Query<&mut Handle<ColorMaterial>, With<AskedItem>>,
+) {
+    if config.is_changed() {
+        for (_, _, mut request_cooldown) in item_askers.iter_mut() {
+            request_cooldown.set_duration(config.request_interval);
+        }
+    }
+
+    for event in finished_events.iter() {
+        let entity = match event {
+            CooldownFinishedEvent::Component(entity) => *entity,
+            CooldownFinishedEvent::Resource(_) => continue,
+        };
+        let (mut asking_item, mut happiness, request_cooldown) = match item_askers.get_mut(entity) {
+            Ok(item_asker) => item_asker,
+            Err(_) => continue,
+        };
+        if !request_cooldown.just_finished() {
+            continue; // Started again by a delivery at the same step.
+        }
+        let next_item = random_different_item(&mut *rng, asking_item.0, config.item_count);
+        info!("Baobei loses patience and asks for {:?}", next_item);
+
+        happiness.sub(config.patience_penalty);
+        happiness_events.send(HappinessChangeEvent {
+            change: -config.patience_penalty,
+        });
+
+        for mut item_material in asked_item_materials.iter_mut() {
+            *item_material = materials.item_sprite_for(next_item);
+        }
+        asking_item.0 = next_item;
+    }
+}
+
+/// Returns a random item, among the ones Baobei can ask for, different than the given one.
+fn random_different_item<R: Rng + ?Sized>(rng: &mut R, item: Item, count: usize) -> Item {
     loop {
-        let next_item = random::<Item>();
+        let next_item = Item::random(rng, count);
         if next_item != item {
             return next_item;
         }
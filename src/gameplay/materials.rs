@@ -2,7 +2,13 @@
 
 use bevy::prelude::*;
 
-use super::items::Item;
+use crate::animation::AnimationSheet;
+
+use super::{chores::MessKind, items::Item};
+
+/// Number of emotions in the emotions sprite sheet, from the saddest to the
+/// happiest.
+pub const EMOTION_COUNT: usize = 5;
 
 /// Sprites and colors in the gameplay phase.
 pub struct GameplayMaterials {
@@ -10,16 +16,30 @@ pub struct GameplayMaterials {
     pub none: Handle<ColorMaterial>,
     /// Sprite of the background image
     pub background_sprite: Handle<ColorMaterial>,
-    /// Sprite of didi
-    pub didi_sprite: Handle<ColorMaterial>,
-    /// Sprite of baobei
-    pub baobei_sprite: Handle<ColorMaterial>,
+    /// Animations of didi
+    pub didi_animations: Handle<AnimationSheet>,
+    /// Animations of baobei
+    pub baobei_animations: Handle<AnimationSheet>,
     /// Sprite for the ice cream item
     pub ice_cream_sprite: Handle<ColorMaterial>,
     /// Sprite for the water glass item
     pub water_glass_sprite: Handle<ColorMaterial>,
     /// Sprite for the chips item
     pub chips_sprite: Handle<ColorMaterial>,
+    /// Sprite for the tea item
+    pub tea_sprite: Handle<ColorMaterial>,
+    /// Sprite for the sandwich item
+    pub sandwich_sprite: Handle<ColorMaterial>,
+    /// Sprite for the bread item
+    pub bread_sprite: Handle<ColorMaterial>,
+    /// Sprite for the trash item
+    pub trash_sprite: Handle<ColorMaterial>,
+    /// Sprite for the sponge item
+    pub sponge_sprite: Handle<ColorMaterial>,
+    /// Sprite for the dirty glass item
+    pub dirty_glass_sprite: Handle<ColorMaterial>,
+    /// Sprites for the stale items, tinted
+    pub stale_item_sprites: Vec<(Item, Handle<ColorMaterial>)>,
     /// Sprite for the fridge
     pub fridge_sprite: Handle<ColorMaterial>,
     /// Sprite for the couch
@@ -30,21 +50,47 @@ pub struct GameplayMaterials {
     pub sink_sprite: Handle<ColorMaterial>,
     /// Sprite for the table
     pub table_sprite: Handle<ColorMaterial>,
+    /// Sprite for the trash bin
+    pub trash_bin_sprite: Handle<ColorMaterial>,
+    /// Sprite for the spilled water mess
+    pub spilled_water_sprite: Handle<ColorMaterial>,
+    /// Sprite for the crumbs mess
+    pub crumbs_sprite: Handle<ColorMaterial>,
+    /// Background of the HUD elements
+    pub hud_background: Handle<ColorMaterial>,
+    /// Background of the inventory slot of the hand
+    pub selected_slot: Handle<ColorMaterial>,
+    /// Color of the patience ring
+    pub patience: Handle<ColorMaterial>,
+    /// Color of the patience ring when Baobei is about to lose patience
+    pub low_patience: Handle<ColorMaterial>,
+    /// Color of the preparation progress of the workstations
+    pub preparation: Handle<ColorMaterial>,
+    /// Color of the highlight over the target of the interaction
+    pub highlight: Handle<ColorMaterial>,
     /// Texture atlas for emotions sprites
     pub emotion_atlas: Handle<TextureAtlas>,
 }
 
 impl FromWorld for GameplayMaterials {
     fn from_world(world: &mut World) -> Self {
-        let none = {
+        let (none, hud_background, selected_slot, patience, low_patience, preparation, highlight) = {
             let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
-            materials.add(Color::NONE.into())
+            (
+                materials.add(Color::NONE.into()),
+                materials.add(Color::rgba(0.0, 0.0, 0.0, 0.6).into()),
+                materials.add(Color::rgba(0.3, 0.3, 0.3, 0.8).into()),
+                materials.add(Color::WHITE.into()),
+                materials.add(Color::RED.into()),
+                materials.add(Color::ORANGE.into()),
+                materials.add(Color::rgba(1.0, 1.0, 0.6, 0.25).into()),
+            )
         };
 
         let emotion_atlas = {
             let asset_server = world.get_resource::<AssetServer>().unwrap();
             let sprite = asset_server.load("emotions.png");
-            let atlas = TextureAtlas::from_grid(sprite, Vec2::new(152.0, 152.0), 5, 1);
+            let atlas = TextureAtlas::from_grid(sprite, Vec2::new(152.0, 152.0), EMOTION_COUNT, 1);
 
             let mut texture_atlases = world.get_resource_mut::<Assets<TextureAtlas>>().unwrap();
             texture_atlases.add(atlas)
@@ -52,22 +98,49 @@ impl FromWorld for GameplayMaterials {
 
         Self {
             none,
-            didi_sprite: load_sprite(world, "didi.png"),
+            didi_animations: load_animations(world, "animations/didi.anim"),
             background_sprite: load_sprite(world, "background.png"),
-            baobei_sprite: load_sprite(world, "baobei.png"),
-            ice_cream_sprite: load_sprite(world, "items/ice_cream.png"),
-            water_glass_sprite: load_sprite(world, "items/water_glass.png"),
-            chips_sprite: load_sprite(world, "items/chips.png"),
+            baobei_animations: load_animations(world, "animations/baobei.anim"),
+            ice_cream_sprite: load_sprite(world, item_file(Item::IceCream)),
+            water_glass_sprite: load_sprite(world, item_file(Item::WaterGlass)),
+            chips_sprite: load_sprite(world, item_file(Item::Chips)),
+            tea_sprite: load_sprite(world, item_file(Item::Tea)),
+            sandwich_sprite: load_sprite(world, item_file(Item::Sandwich)),
+            bread_sprite: load_sprite(world, item_file(Item::Bread)),
+            trash_sprite: load_sprite(world, item_file(Item::Trash)),
+            sponge_sprite: load_sprite(world, item_file(Item::Sponge)),
+            dirty_glass_sprite: load_sprite(world, item_file(Item::DirtyGlass)),
+            stale_item_sprites: Item::perishable()
+                .map(|item| {
+                    let sprite = load_tinted_sprite(world, item_file(item), stale_tint());
+                    (item, sprite)
+                })
+                .collect(),
             fridge_sprite: load_sprite(world, "furniture/fridge.png"),
             couch_sprite: load_sprite(world, "furniture/couch.png"),
             kitchen_sprite: load_sprite(world, "furniture/kitchen.png"),
             sink_sprite: load_sprite(world, "furniture/sink.png"),
             table_sprite: load_sprite(world, "furniture/table.png"),
+            trash_bin_sprite: load_sprite(world, "furniture/trash_bin.png"),
+            spilled_water_sprite: load_sprite(world, "chores/spilled_water.png"),
+            crumbs_sprite: load_sprite(world, "chores/crumbs.png"),
+            hud_background,
+            selected_slot,
+            patience,
+            low_patience,
+            preparation,
+            highlight,
             emotion_atlas,
         }
     }
 }
 
+/// Load the animation sheet in the given file.
+fn load_animations(world: &World, file_name: &str) -> Handle<AnimationSheet> {
+    let asset_server = world.get_resource::<AssetServer>().unwrap();
+    asset_server.load(file_name)
+}
+
 /// Load the sprite in the given file.
 fn load_sprite(world: &mut World, file_name: &str) -> Handle<ColorMaterial> {
     let asset_server = world.get_resource::<AssetServer>().unwrap();
@@ -77,6 +150,35 @@ fn load_sprite(world: &mut World, file_name: &str) -> Handle<ColorMaterial> {
     materials.add(sprite)
 }
 
+/// Load the sprite in the given file, tinted with the color.
+fn load_tinted_sprite(world: &mut World, file_name: &str, color: Color) -> Handle<ColorMaterial> {
+    let asset_server = world.get_resource::<AssetServer>().unwrap();
+    let sprite = ColorMaterial::modulated_texture(asset_server.load(file_name), color);
+
+    let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
+    materials.add(sprite)
+}
+
+/// Returns the tint of the stale items.
+fn stale_tint() -> Color {
+    Color::rgb(0.7, 0.8, 0.45)
+}
+
+/// Returns the file of the sprite of the item.
+const fn item_file(item: Item) -> &'static str {
+    match item {
+        Item::IceCream => "items/ice_cream.png",
+        Item::WaterGlass => "items/water_glass.png",
+        Item::Chips => "items/chips.png",
+        Item::Tea => "items/tea.png",
+        Item::Sandwich => "items/sandwich.png",
+        Item::Bread => "items/bread.png",
+        Item::Trash => "items/trash.png",
+        Item::Sponge => "items/sponge.png",
+        Item::DirtyGlass => "items/dirty_glass.png",
+    }
+}
+
 impl GameplayMaterials {
     /// Returns the sprite handle for the given item
     pub fn item_sprite_for(&self, item: Item) -> Handle<ColorMaterial> {
@@ -84,6 +186,28 @@ impl GameplayMaterials {
             Item::IceCream => self.ice_cream_sprite.clone(),
             Item::WaterGlass => self.water_glass_sprite.clone(),
             Item::Chips => self.chips_sprite.clone(),
+            Item::Tea => self.tea_sprite.clone(),
+            Item::Sandwich => self.sandwich_sprite.clone(),
+            Item::Bread => self.bread_sprite.clone(),
+            Item::Trash => self.trash_sprite.clone(),
+            Item::Sponge => self.sponge_sprite.clone(),
+            Item::DirtyGlass => self.dirty_glass_sprite.clone(),
         }
     }
+
+    /// Returns the sprite handle for the given mess
+    pub fn mess_sprite_for(&self, kind: MessKind) -> Handle<ColorMaterial> {
+        match kind {
+            MessKind::SpilledWater => self.spilled_water_sprite.clone(),
+            MessKind::Crumbs => self.crumbs_sprite.clone(),
+        }
+    }
+
+    /// Returns the sprite handle for the given item, tinted when it is stale
+    pub fn stale_item_sprite_for(&self, item: Item) -> Handle<ColorMaterial> {
+        self.stale_item_sprites
+            .iter()
+            .find(|(stale_item, _)| *stale_item == item)
+            .map_or_else(|| self.item_sprite_for(item), |(_, sprite)| sprite.clone())
+    }
 }
@@ -2,18 +2,69 @@
 
 use bevy::prelude::*;
 
-use crate::{collisions::CollisionSystems, constants::GameState, controllers::ControllerSystems};
+use crate::{
+    clock::FixedUpdateStage, collisions::CollisionSystems, constants::GameState,
+    controllers::ControllerSystems, difficulty::DifficultyPlugin,
+};
 
 use self::{
-    entities::SpawnEntitiesPlugin, happiness::HappinessPlugin, items::ItemsPlugin,
-    materials::GameplayMaterials, movement::movement_system,
+    animations::GameplayAnimationsPlugin,
+    chores::ChoresPlugin,
+    crafting::CraftingPlugin,
+    entities::SpawnEntitiesPlugin,
+    freshness::FreshnessPlugin,
+    furniture::FurniturePlugin,
+    happiness::HappinessPlugin,
+    hold_ring::HoldRingPlugin,
+    hud::HudPlugin,
+    inventory::InventoryPlugin,
+    items::ItemsPlugin,
+    materials::GameplayMaterials,
+    modifiers::ModifiersPlugin,
+    movement::{
+        facing_system, flip_sprite_system, movement_system, player_direction_system,
+        PlayerDirection,
+    },
+    patience::PatiencePlugin,
+    prompts::PromptsPlugin,
+    round::RoundPlugin,
+    save::SavePlugin,
+    score::ScorePlugin,
+    sounds::GameplaySoundsPlugin,
+    stock::StockPlugin,
+};
+
+pub use self::{
+    happiness::Happiness,
+    items::ActionEvent,
+    round::NewRoundSystems,
+    save::{ContinueRound, RoundSaveFile},
+    score::Score,
 };
 
+mod animations;
+mod chores;
+mod crafting;
 mod entities;
+mod freshness;
+mod furniture;
 mod happiness;
+mod hold_ring;
+mod hud;
+mod inventory;
 mod items;
 mod materials;
+mod modifiers;
 mod movement;
+mod patience;
+mod prompts;
+mod round;
+mod save;
+mod score;
+mod sounds;
+mod stock;
+#[cfg(test)]
+mod tests;
 
 /// Plugin the gameplay of the game
 pub struct GameplayPlugin;
@@ -24,19 +75,49 @@ impl Plugin for GameplayPlugin {
             .register_type::<Didi>()
             .register_type::<Furniture>()
             .register_type::<Baobei>()
+            .init_resource::<PlayerDirection>()
+            .add_plugin(DifficultyPlugin)
             .add_plugin(SpawnEntitiesPlugin)
             .add_system_set(
                 SystemSet::on_update(GameState::InGame)
                     .with_system(back_to_menu_system.system())
+                    .with_system(player_direction_system.system().after(ControllerSystems))
+                    .with_system(flip_sprite_system.system()),
+            )
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
                     .with_system(
                         movement_system
                             .system()
-                            .after(ControllerSystems)
+                            .label("movement")
+                            .before(CollisionSystems),
+                    )
+                    .with_system(
+                        facing_system
+                            .system()
+                            .after("movement")
                             .before(CollisionSystems),
                     ),
             )
             .add_plugin(ItemsPlugin)
-            .add_plugin(HappinessPlugin);
+            .add_plugin(CraftingPlugin)
+            .add_plugin(FreshnessPlugin)
+            .add_plugin(ChoresPlugin)
+            .add_plugin(InventoryPlugin)
+            .add_plugin(StockPlugin)
+            .add_plugin(FurniturePlugin)
+            .add_plugin(PromptsPlugin)
+            .add_plugin(HoldRingPlugin)
+            .add_plugin(ModifiersPlugin)
+            .add_plugin(HappinessPlugin)
+            .add_plugin(HudPlugin)
+            .add_plugin(PatiencePlugin)
+            .add_plugin(ScorePlugin)
+            .add_plugin(RoundPlugin)
+            .add_plugin(SavePlugin)
+            .add_plugin(GameplaySoundsPlugin)
+            .add_plugin(GameplayAnimationsPlugin);
     }
 }
 
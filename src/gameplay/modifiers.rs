@@ -0,0 +1,277 @@
+//! Modifiers of the happiness of Baobei: the decay curve, the satisfaction
+//! given by each item, and the buffs and debuffs changing the decay.
+
+use bevy::prelude::*;
+
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    constants::GameState,
+    cooldown::Cooldown,
+    difficulty::DifficultyConfig,
+};
+
+use super::{
+    happiness::HappinessSystems,
+    items::{Item, ItemSystems},
+    Baobei,
+};
+
+/// Label for systems updating the modifiers
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct ModifierSystems;
+
+/// Plugin managing the modifiers of the happiness.
+pub struct ModifiersPlugin;
+
+impl Plugin for ModifiersPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<ModifierConfig>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame).with_system(
+                    update_modifiers_system
+                        .system()
+                        .label(ModifierSystems)
+                        .after(ItemSystems)
+                        .before(HappinessSystems),
+                ),
+            );
+    }
+}
+
+/// How the decay of the happiness changes with the happiness itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DecayCurve {
+    /// The happiness decreases faster when low, up to `1 + factor` times
+    /// faster when empty.
+    FasterWhenLow {
+        /// Additional decay when the happiness is empty.
+        factor: f32,
+    },
+}
+
+impl DecayCurve {
+    /// Returns the factor of the decay for the happiness value.
+    pub fn factor(self, happiness: f32) -> f32 {
+        match self {
+            Self::FasterWhenLow { factor } => 1.0 + factor * (1.0 - happiness),
+        }
+    }
+}
+
+/// A buff given by an item when Baobei receives it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ItemBuff {
+    /// Item giving the buff.
+    pub item: Item,
+    /// Factor of the decay while the buff lasts.
+    pub decay_factor: f32,
+    /// Duration of the buff, in seconds.
+    pub duration: f32,
+}
+
+/// Parameters of the modifiers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModifierConfig {
+    /// Curve of the decay of the happiness.
+    pub decay_curve: DecayCurve,
+    /// Factor of the reward given by each item, 1 if missing.
+    pub satisfactions: Vec<(Item, f32)>,
+    /// Buffs given by the items.
+    pub buffs: Vec<ItemBuff>,
+    /// Fraction of the request interval after which Baobei is tired of waiting.
+    pub patience: f32,
+    /// Factor of the decay while Baobei is tired of waiting.
+    pub waiting_decay_factor: f32,
+}
+
+impl Default for ModifierConfig {
+    fn default() -> Self {
+        Self {
+            decay_curve: DecayCurve::FasterWhenLow { factor: 0.25 },
+            satisfactions: vec![
+                (Item::IceCream, 1.2),
+                (Item::WaterGlass, 1.0),
+                (Item::Chips, 0.8),
+            ],
+            buffs: vec![ItemBuff {
+                item: Item::IceCream,
+                decay_factor: 0.5,
+                duration: 10.0,
+            }],
+            patience: 0.75,
+            waiting_decay_factor: 1.25,
+        }
+    }
+}
+
+impl ModifierConfig {
+    /// Returns the factor of the reward given by the item.
+    pub fn satisfaction(&self, item: Item) -> f32 {
+        self.satisfactions
+            .iter()
+            .find(|(satisfying_item, _)| *satisfying_item == item)
+            .map_or(1.0, |(_, satisfaction)| *satisfaction)
+    }
+
+    /// Returns the buff given by the item, if any.
+    pub fn buff(&self, item: Item) -> Option<ItemBuff> {
+        self.buffs.iter().copied().find(|buff| buff.item == item)
+    }
+}
+
+/// The cause of a modifier.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModifierKind {
+    /// Baobei received an item giving a buff.
+    Buff(Item),
+    /// Baobei waits for its item for too long.
+    WaitingTooLong,
+    /// Messes are waiting to be cleaned.
+    Messes,
+    /// Baobei needs a nap.
+    Tired,
+}
+
+/// A change of the decay of the happiness.
+#[derive(Debug, Clone)]
+pub struct Modifier {
+    /// The cause of the modifier.
+    pub kind: ModifierKind,
+    /// Factor of the decay while the modifier is active.
+    pub decay_factor: f32,
+    /// Time before the modifier ends, if it is temporary.
+    pub timer: Option<Timer>,
+}
+
+impl Modifier {
+    /// Returns the modifier given by the item buff.
+    pub fn from_buff(buff: ItemBuff) -> Self {
+        Self {
+            kind: ModifierKind::Buff(buff.item),
+            decay_factor: buff.decay_factor,
+            timer: Some(Timer::from_seconds(buff.duration, false)),
+        }
+    }
+
+    /// Returns a description of the modifier for the debug output.
+    pub fn describe(&self) -> String {
+        let name = match self.kind {
+            ModifierKind::Buff(item) => format!("{:?} buff", item),
+            ModifierKind::WaitingTooLong => "Waiting too long".to_string(),
+            ModifierKind::Messes => "Messes".to_string(),
+            ModifierKind::Tired => "Tired".to_string(),
+        };
+        match &self.timer {
+            Some(timer) => format!(
+                "{} x{:.2} ({:.0}s)",
+                name,
+                self.decay_factor,
+                timer.duration().as_secs_f32() - timer.elapsed_secs()
+            ),
+            None => format!("{} x{:.2}", name, self.decay_factor),
+        }
+    }
+}
+
+/// Component with the modifiers active on the happiness of the entity.
+#[derive(Debug, Clone, Default)]
+pub struct Modifiers(pub Vec<Modifier>);
+
+impl Modifiers {
+    /// Returns the factor of the decay, with all the modifiers applied.
+    pub fn decay_factor(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|modifier| modifier.decay_factor)
+            .product()
+    }
+
+    /// Adds the modifier, replacing the one of the same kind.
+    pub fn add(&mut self, modifier: Modifier) {
+        self.remove(modifier.kind);
+        info!("Add modifier {}", modifier.describe());
+        self.0.push(modifier);
+    }
+
+    /// Removes the modifier of the given kind.
+    pub fn remove(&mut self, kind: ModifierKind) {
+        self.0.retain(|modifier| modifier.kind != kind);
+    }
+
+    /// Returns true if a modifier of the given kind is active.
+    pub fn contains(&self, kind: ModifierKind) -> bool {
+        self.0.iter().any(|modifier| modifier.kind == kind)
+    }
+}
+
+/// Ends the finished buffs, and applies the debuff when Baobei waits too long.
+fn update_modifiers_system(
+    config: Res<ModifierConfig>,
+    difficulty: Res<DifficultyConfig>,
+    request_cooldowns: Query<&Cooldown, With<Baobei>>,
+    mut modifiers_query: Query<&mut Modifiers>,
+) {
+    let waiting_too_long = request_cooldowns
+        .iter()
+        .any(|cooldown| cooldown.elapsed() > config.patience * difficulty.request_interval);
+
+    for mut modifiers in modifiers_query.iter_mut() {
+        for timer in modifiers
+            .0
+            .iter_mut()
+            .filter_map(|modifier| modifier.timer.as_mut())
+        {
+            timer.tick(STEP);
+        }
+        modifiers.0.retain(|modifier| {
+            let ended = modifier.timer.as_ref().map_or(false, Timer::finished);
+            if ended {
+                info!("End of modifier {}", modifier.describe());
+            }
+            !ended
+        });
+
+        if waiting_too_long != modifiers.contains(ModifierKind::WaitingTooLong) {
+            if waiting_too_long {
+                modifiers.add(Modifier {
+                    kind: ModifierKind::WaitingTooLong,
+                    decay_factor: config.waiting_decay_factor,
+                    timer: None,
+                });
+            } else {
+                modifiers.remove(ModifierKind::WaitingTooLong);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecayCurve, Modifier, ModifierConfig, ModifierKind, Modifiers};
+    use crate::gameplay::items::Item;
+
+    #[test]
+    fn test_decay_factor() {
+        let curve = DecayCurve::FasterWhenLow { factor: 0.5 };
+        assert!((curve.factor(1.0) - 1.0).abs() < 1e-6);
+        assert!((curve.factor(0.0) - 1.5).abs() < 1e-6);
+
+        let config = ModifierConfig::default();
+        let mut modifiers = Modifiers::default();
+        modifiers.add(Modifier::from_buff(config.buff(Item::IceCream).unwrap()));
+        modifiers.add(Modifier {
+            kind: ModifierKind::WaitingTooLong,
+            decay_factor: 1.25,
+            timer: None,
+        });
+        modifiers.add(Modifier::from_buff(config.buff(Item::IceCream).unwrap()));
+        assert_eq!(modifiers.0.len(), 2);
+        assert!((modifiers.decay_factor() - 0.625).abs() < 1e-6);
+        assert_eq!(modifiers.0[1].describe(), "IceCream buff x0.50 (10s)");
+
+        modifiers.remove(ModifierKind::Buff(Item::IceCream));
+        assert!((modifiers.decay_factor() - 1.25).abs() < 1e-6);
+        assert!(config.buff(Item::Chips).is_none());
+    }
+}
@@ -2,19 +2,98 @@
 
 use bevy::prelude::*;
 
-use crate::{collisions::Movement, constants::SPEED, controllers::DirectionEvent};
+use crate::{clock::STEP, collisions::Movement, constants::SPEED, controllers::DirectionEvent};
 
 use super::Didi;
 
-/// Moves Didi toward the direction sent by controllers.
-pub fn movement_system(
-    time: Res<Time>,
+/// Direction chosen by the player during the current frame.
+#[derive(Debug, Default)]
+pub struct PlayerDirection(pub Vec3);
+
+/// Stores the direction sent by controllers, used by all the steps of the frame.
+pub fn player_direction_system(
     mut direction_events: EventReader<DirectionEvent>,
+    mut player_direction: ResMut<PlayerDirection>,
+) {
+    player_direction.0 = direction_events
+        .iter()
+        .last()
+        .map_or(Vec3::ZERO, |event| event.direction);
+}
+
+/// Moves Didi toward the direction chosen by the player.
+pub fn movement_system(
+    player_direction: Res<PlayerDirection>,
     mut query: Query<&mut Movement, With<Didi>>,
 ) {
-    for event in direction_events.iter() {
-        for mut movement in query.iter_mut() {
-            movement.0 = event.direction * time.delta_seconds() * SPEED;
+    for mut movement in query.iter_mut() {
+        movement.0 = player_direction.0 * STEP.as_secs_f32() * SPEED;
+    }
+}
+
+/// Side toward which an entity looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facing {
+    /// Looks to the left, as drawn in the sprites.
+    Left,
+    /// Looks to the right, with the sprite flipped.
+    Right,
+}
+
+impl Default for Facing {
+    fn default() -> Self {
+        Self::Left
+    }
+}
+
+impl Facing {
+    /// Returns the side toward the horizontal movement, or the current side
+    /// when not moving horizontally.
+    pub fn toward(self, movement: Vec3) -> Self {
+        if movement.x < 0.0 {
+            Self::Left
+        } else if movement.x > 0.0 {
+            Self::Right
+        } else {
+            self
         }
     }
+
+    /// Mirrors the offset, given for an entity looking to the left, to the
+    /// side of the entity.
+    pub fn mirror(self, offset: Vec3) -> Vec3 {
+        match self {
+            Self::Left => offset,
+            Self::Right => Vec3::new(-offset.x, offset.y, offset.z),
+        }
+    }
+
+    /// Returns the sign of the horizontal scale of the sprite.
+    const fn scale_sign(self) -> f32 {
+        match self {
+            Self::Left => 1.0,
+            Self::Right => -1.0,
+        }
+    }
+}
+
+/// Turns moving entities toward their movement.
+///
+/// It runs before the collisions, which consume the movement.
+pub fn facing_system(mut query: Query<(&Movement, &mut Facing)>) {
+    for (movement, mut facing) in query.iter_mut() {
+        let next_facing = facing.toward(movement.0);
+        if *facing != next_facing {
+            *facing = next_facing;
+        }
+    }
+}
+
+/// Flips the sprite of entities to the side they are facing.
+///
+/// The children, like the item carried by Didi, are mirrored along.
+pub fn flip_sprite_system(mut query: Query<(&Facing, &mut Transform), Changed<Facing>>) {
+    for (facing, mut transform) in query.iter_mut() {
+        transform.scale.x = facing.scale_sign() * transform.scale.x.abs();
+    }
 }
@@ -0,0 +1,126 @@
+//! Ring around the asked item, showing the patience left to Baobei.
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::{ecs::component::Component, prelude::*, sprite::SpriteResizeMode};
+
+use crate::{constants::GameState, cooldown::Cooldown};
+
+use super::{items::AskedItem, materials::GameplayMaterials, Baobei};
+
+/// Number of dots forming the ring.
+pub const DOT_COUNT: usize = 24;
+
+/// Radius of the ring, relative to the asked item.
+const RING_RADIUS: f32 = 90.0;
+
+/// Fraction of the patience left under which the ring turns red.
+const LOW_PATIENCE: f32 = 0.25;
+
+/// Plugin displaying the patience of Baobei.
+pub struct PatiencePlugin;
+
+impl Plugin for PatiencePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system_to_stage(StartupStage::PostStartup, spawn_patience_ring.system())
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(update_patience_ring.system()),
+            );
+    }
+}
+
+/// A dot of the patience ring, with its position in the ring starting at
+/// the top and going clockwise.
+struct PatienceDot(usize);
+
+/// Returns the number of dots of a ring shown for the fraction, such as the
+/// patience left.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn visible_dots(fraction: f32) -> usize {
+    (fraction.clamp(0.0, 1.0) * DOT_COUNT as f32).ceil() as usize
+}
+
+/// Spawns the dots of a ring of the given radius as children, from the top
+/// and clockwise, each one with the sprite and the component made from its
+/// index.
+pub fn spawn_ring_dots<T: Component>(
+    parent: &mut ChildBuilder,
+    radius: f32,
+    sprite: &SpriteBundle,
+    dot: impl Fn(usize) -> T,
+) {
+    for index in 0..DOT_COUNT {
+        let angle = FRAC_PI_2 - TAU * index as f32 / DOT_COUNT as f32;
+
+        parent
+            .spawn()
+            .insert(dot(index))
+            .insert_bundle(SpriteBundle {
+                transform: Transform::from_xyz(radius * angle.cos(), radius * angle.sin(), 0.1),
+                ..sprite.clone()
+            });
+    }
+}
+
+/// Spawns the dots of the ring around the asked item.
+fn spawn_patience_ring(
+    mut commands: Commands,
+    materials: Res<GameplayMaterials>,
+    asked_items: Query<Entity, With<AskedItem>>,
+) {
+    for asked_item in asked_items.iter() {
+        let sprite = SpriteBundle {
+            material: materials.patience.clone(),
+            sprite: Sprite {
+                size: Vec2::splat(12.0),
+                resize_mode: SpriteResizeMode::Manual,
+                ..Sprite::default()
+            },
+            ..SpriteBundle::default()
+        };
+        commands.entity(asked_item).with_children(|parent| {
+            spawn_ring_dots(parent, RING_RADIUS, &sprite, PatienceDot);
+        });
+    }
+}
+
+/// Shrinks the ring with the time left before Baobei loses patience.
+fn update_patience_ring(
+    materials: Res<GameplayMaterials>,
+    request_cooldowns: Query<&Cooldown, With<Baobei>>,
+    mut dots: Query<(&PatienceDot, &mut Visible, &mut Handle<ColorMaterial>)>,
+) {
+    let patience_left = request_cooldowns
+        .iter()
+        .next()
+        .map_or(1.0, Cooldown::fraction_remaining);
+    let shown = visible_dots(patience_left);
+    let material = if patience_left < LOW_PATIENCE {
+        &materials.low_patience
+    } else {
+        &materials.patience
+    };
+
+    for (dot, mut visible, mut dot_material) in dots.iter_mut() {
+        let is_visible = dot.0 < shown;
+        if visible.is_visible != is_visible {
+            visible.is_visible = is_visible;
+        }
+        if *dot_material != *material {
+            *dot_material = material.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{visible_dots, DOT_COUNT};
+
+    #[test]
+    fn test_visible_dots() {
+        assert_eq!(visible_dots(1.0), DOT_COUNT);
+        assert_eq!(visible_dots(0.5), DOT_COUNT / 2);
+        assert_eq!(visible_dots(0.01), 1);
+        assert_eq!(visible_dots(0.0), 0);
+    }
+}
@@ -0,0 +1,267 @@
+//! Prompts telling the player what interacting does: a floating label above
+//! the targeted entity, highlighted, from the same decision as the action.
+
+use bevy::prelude::*;
+
+use crate::{
+    collisions::{Position, TriggerArea},
+    constants::GameState,
+    controllers::InputDevice,
+    drawing::UiObject,
+    settings::{Language, Settings},
+};
+
+use super::{
+    entities::GameData,
+    items::{ActionEvent, InteractionTargets, Item},
+    materials::GameplayMaterials,
+};
+
+/// Height of the target when it has no trigger area.
+const DEFAULT_TARGET_HEIGHT: f32 = 100.0;
+
+/// Space between the top of the target and the prompt.
+const PROMPT_MARGIN: f32 = 30.0;
+
+/// Plugin showing the prompts of the interactions.
+pub struct PromptsPlugin;
+
+impl Plugin for PromptsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_startup_system(spawn_prompt.system())
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(interaction_prompt_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame).with_system(hide_prompt_system.system()),
+            );
+    }
+}
+
+/// Tag the text showing what interacting does.
+pub struct InteractionPrompt;
+
+/// Tag the highlight over the target of the interaction.
+pub struct TargetHighlight;
+
+/// Returns the prompt of the action in the language, starting with the
+/// interact button of the device, to hold for the held actions.
+pub fn prompt(action: ActionEvent, held: bool, language: Language, device: InputDevice) -> String {
+    let translate = |text| language.translate(text);
+    let with_item = |verb, item: Item| format!("{} {}", translate(verb), translate(item.name()));
+
+    let text = match action {
+        ActionEvent::Take(_, item) => with_item("Take", item),
+        ActionEvent::PutAway(item) => with_item("Put away", item),
+        ActionEvent::Drop(item) => with_item("Drop", item),
+        ActionEvent::PickUp(_, item) => with_item("Pick up", item),
+        ActionEvent::Keep(item) => with_item("Keep", item),
+        ActionEvent::Give(item) => with_item("Give", item),
+        ActionEvent::Deposit(_, item) => with_item("Add", item),
+        ActionEvent::Collect(_, item) => with_item("Collect", item),
+        ActionEvent::Prepare(_) => translate("Prepare").to_string(),
+        ActionEvent::ThrowAway(item) => with_item("Throw away", item),
+        ActionEvent::Clean(_) => translate("Clean").to_string(),
+        ActionEvent::Restock(_) => translate("Restock").to_string(),
+        ActionEvent::Store(_, item) => {
+            format!("{} {}", with_item("Leave", item), translate("on the table"))
+        }
+        ActionEvent::Retrieve(_) => translate("Take from the table").to_string(),
+        ActionEvent::Nap(_) => translate("Nap").to_string(),
+        ActionEvent::Wash(_, item) => with_item("Wash", item),
+    };
+    let button = match device {
+        InputDevice::Keyboard => translate("Space"),
+        InputDevice::Gamepad => "A",
+    };
+    if held {
+        format!("{} {}: {}", translate("Hold"), button, text)
+    } else {
+        format!("{}: {}", button, text)
+    }
+}
+
+/// Spawns the prompt and the highlight, hidden until Didi can interact.
+fn spawn_prompt(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<GameplayMaterials>,
+) {
+    let hidden = Visible {
+        is_visible: false,
+        is_transparent: true,
+    };
+
+    commands
+        .spawn()
+        .insert(InteractionPrompt)
+        .insert(UiObject)
+        .insert(Position::default())
+        .insert_bundle(Text2dBundle {
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("FiraSans-Bold.ttf"),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            visible: hidden.clone(),
+            ..Text2dBundle::default()
+        });
+
+    commands
+        .spawn()
+        .insert(TargetHighlight)
+        .insert(UiObject)
+        .insert(Position::default())
+        .insert_bundle(SpriteBundle {
+            material: materials.highlight.clone(),
+            visible: hidden,
+            ..SpriteBundle::default()
+        });
+}
+
+/// Shows what interacting does above its target, or above Didi when
+/// dropping, and highlights the target.
+#[allow(clippy::type_complexity)]
+fn interaction_prompt_system(
+    game_data: Res<GameData>,
+    settings: Res<Settings>,
+    device: Res<InputDevice>,
+    targets: InteractionTargets,
+    positions: Query<
+        (&Position, Option<&TriggerArea>),
+        (Without<InteractionPrompt>, Without<TargetHighlight>),
+    >,
+    mut prompts: Query<
+        (&mut Text, &mut Position, &mut Visible),
+        (With<InteractionPrompt>, With<UiObject>),
+    >,
+    mut highlights: Query<
+        (&mut Sprite, &mut Position, &mut Visible),
+        (
+            With<TargetHighlight>,
+            With<UiObject>,
+            Without<InteractionPrompt>,
+        ),
+    >,
+) {
+    let didi = game_data.didi_entity;
+    let interaction = targets.interaction(didi);
+    let target = interaction.and_then(|interaction| {
+        let entity = interaction.target.unwrap_or(didi);
+        let (position, area) = positions.get(entity).ok()?;
+        let size = area.map_or(Vec2::new(0.0, DEFAULT_TARGET_HEIGHT), |area| area.size);
+        Some((position.0.truncate().extend(0.0), size))
+    });
+
+    for (mut text, mut position, mut visible) in prompts.iter_mut() {
+        match (interaction, target) {
+            (Some(interaction), Some((target_position, size))) => {
+                let prompt = prompt(
+                    interaction.action,
+                    interaction.held,
+                    settings.language,
+                    *device,
+                );
+                if text.sections[0].value != prompt {
+                    text.sections[0].value = prompt;
+                }
+                let prompt_position =
+                    target_position + Vec3::new(0.0, size.y / 2.0 + PROMPT_MARGIN, 0.0);
+                if position.0 != prompt_position {
+                    position.0 = prompt_position;
+                }
+                set_visible(&mut visible, true);
+            }
+            _ => set_visible(&mut visible, false),
+        }
+    }
+
+    let highlighted = interaction
+        .and_then(|interaction| interaction.target)
+        .and(target);
+    for (mut sprite, mut position, mut visible) in highlights.iter_mut() {
+        match highlighted {
+            Some((target_position, size)) => {
+                if sprite.size != size {
+                    sprite.size = size;
+                }
+                if position.0 != target_position {
+                    position.0 = target_position;
+                }
+                set_visible(&mut visible, true);
+            }
+            None => set_visible(&mut visible, false),
+        }
+    }
+}
+
+/// Query filter for the prompt and the highlight
+type PromptOrHighlight = Or<(With<InteractionPrompt>, With<TargetHighlight>)>;
+
+/// Hides the prompt and the highlight when leaving the game.
+fn hide_prompt_system(mut visibles: Query<&mut Visible, PromptOrHighlight>) {
+    for mut visible in visibles.iter_mut() {
+        set_visible(&mut visible, false);
+    }
+}
+
+/// Changes the visibility, without triggering the change detection if the
+/// same.
+fn set_visible(visible: &mut Mut<Visible>, is_visible: bool) {
+    if visible.is_visible != is_visible {
+        visible.is_visible = is_visible;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Entity;
+
+    use super::{prompt, ActionEvent, InputDevice, Language};
+    use crate::gameplay::items::Item;
+
+    #[test]
+    fn test_prompt() {
+        let english = |action| prompt(action, false, Language::English, InputDevice::Keyboard);
+        assert_eq!(
+            english(ActionEvent::Take(Entity::new(1), Item::IceCream)),
+            "Space: Take Ice Cream"
+        );
+        assert_eq!(
+            english(ActionEvent::Give(Item::WaterGlass)),
+            "Space: Give Water Glass"
+        );
+        assert_eq!(
+            english(ActionEvent::Store(Entity::new(1), Item::Chips)),
+            "Space: Leave Chips on the table"
+        );
+        assert_eq!(english(ActionEvent::Nap(Entity::new(1))), "Space: Nap");
+
+        assert_eq!(
+            prompt(
+                ActionEvent::Restock(Entity::new(1)),
+                true,
+                Language::English,
+                InputDevice::Gamepad
+            ),
+            "Hold A: Restock"
+        );
+        assert_eq!(
+            prompt(
+                ActionEvent::Take(Entity::new(1), Item::IceCream),
+                false,
+                Language::French,
+                InputDevice::Keyboard
+            ),
+            "Espace: Prendre Glace"
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! End of the rounds and start of the next ones.
+
+use bevy::prelude::*;
+
+use crate::{
+    clock::FixedUpdateStage, constants::GameState, cooldown::Cooldown,
+    difficulty::DifficultyConfig, rng::GameRng,
+};
+
+use super::{
+    crafting::Workstation,
+    entities::{didi_start_position, GameData},
+    happiness::{Happiness, HappinessSystems, HappinessTimer},
+    inventory::{Inventory, InventoryUpgrades},
+    items::{request_cooldown, AskedItem, AskingItem, Carrying, Item},
+    materials::GameplayMaterials,
+    modifiers::Modifiers,
+    movement::Facing,
+    score::Score,
+};
+
+/// Label for the systems putting everything back in place when entering the
+/// game, the saved round being restored after them when continued.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct NewRoundSystems;
+
+/// Plugin ending the round when Baobei is too sad, and starting a new one
+/// each time the game is entered.
+pub struct RoundPlugin;
+
+impl Plugin for RoundPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            SystemSet::on_update(GameState::InGame)
+                .with_system(end_round_system.system().after(HappinessSystems)),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::InGame)
+                .label(NewRoundSystems)
+                .with_system(reset_round_system.system()),
+        );
+    }
+}
+
+/// Ends the round when the happiness of Baobei is down to zero.
+fn end_round_system(
+    mut state: ResMut<State<GameState>>,
+    happiness_values: Query<&Happiness, Changed<Happiness>>,
+) {
+    if happiness_values
+        .iter()
+        .any(|happiness| happiness.value() <= 0.0)
+    {
+        info!("Baobei is too sad, the round is over");
+        state.set(GameState::RoundOver).unwrap();
+    }
+}
+
+/// Puts everything back in place for the new round, restarting the random
+/// generator so that the round is reproduced from its seed. The inventory
+/// takes the number of slots of the difficulty and upgrades of this round.
+#[allow(clippy::too_many_arguments)]
+fn reset_round_system(
+    mut commands: Commands,
+    game_data: Res<GameData>,
+    materials: Res<GameplayMaterials>,
+    mut rng: ResMut<GameRng>,
+    config: Res<DifficultyConfig>,
+    upgrades: Res<InventoryUpgrades>,
+    mut score: ResMut<Score>,
+    mut happiness_timer: ResMut<HappinessTimer>,
+    items: Query<Entity, With<Item>>,
+    mut baobei_query: Query<(
+        &mut AskingItem,
+        &mut Cooldown,
+        &mut Happiness,
+        &mut Modifiers,
+    )>,
+    mut asked_item_materials: Query<&mut Handle<ColorMaterial>, With<AskedItem>>,
+    mut workstations: Query<&mut Workstation>,
+) {
+    *score = Score::default();
+    happiness_timer.0.reset();
+
+    commands
+        .entity(game_data.didi_entity)
+        .insert(didi_start_position())
+        .insert(Facing::default())
+        .insert(Inventory::new(upgrades.slots(&config)))
+        .remove::<Carrying>();
+    for item in items.iter() {
+        commands.entity(item).despawn();
+    }
+    for mut workstation in workstations.iter_mut() {
+        *workstation = Workstation::default();
+    }
+
+    rng.reseed();
+    let asked_item = Item::random(&mut *rng, config.item_count);
+    for (mut asking_item, mut cooldown, mut happiness, mut modifiers) in baobei_query.iter_mut() {
+        asking_item.0 = asked_item;
+        *cooldown = request_cooldown(&config);
+        *happiness = Happiness::happy();
+        *modifiers = Modifiers::default();
+    }
+    for mut item_material in asked_item_materials.iter_mut() {
+        *item_material = materials.item_sprite_for(asked_item);
+    }
+}
@@ -0,0 +1,761 @@
+//! Save of the round in progress, to continue it later.
+//!
+//! The round state is copied in a separate world and written as a scene,
+//! relying on the reflection of its components.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    app::AppExit,
+    ecs::{entity::EntityMap, schedule::ShouldRun, system::SystemParam},
+    prelude::*,
+    reflect::TypeRegistry,
+    scene::serde::SceneDeserializer,
+};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use crate::{
+    collisions::{Position, TriggerArea},
+    constants::GameState,
+    cooldown::Cooldown,
+    difficulty::DifficultyConfig,
+    rng::GameRng,
+    storage,
+};
+
+use super::{
+    chores::{spawn_mess, Mess, MessKind, MessTimer},
+    crafting::{Preparation, Workstation},
+    entities::GameData,
+    freshness::Freshness,
+    furniture::{spawn_table_item, Interactable, Napping, Rest},
+    happiness::{Happiness, HappinessTimer},
+    inventory::{Inventory, InventoryUpgrades},
+    items::{
+        spawn_carried_item, spawn_dropped_item, AskedItem, AskingItem, CarriedItem, Carrying, Item,
+        ItemProducer, PickAndDropCooldown,
+    },
+    materials::GameplayMaterials,
+    modifiers::{Modifier, ModifierKind, Modifiers},
+    round::NewRoundSystems,
+    score::Score,
+    stock::Stock,
+    Baobei, Didi,
+};
+
+/// Name of the save file in the user data directory.
+const SAVE_FILE: &str = "round.scn.ron";
+
+/// Plugin saving the round when leaving it and continuing it on demand.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if let Some(path) = storage::data_file(SAVE_FILE) {
+            app.insert_resource(RoundSaveFile(path));
+        }
+
+        app.register_type::<Cooldown>()
+            .register_type::<SavedRound>()
+            .register_type::<SavedBuff>()
+            .register_type::<ItemPlace>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .with_system(continue_round_system.system().after(NewRoundSystems)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame).with_system(save_round_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::RoundOver).with_system(delete_save_system.system()),
+            )
+            .add_system_to_stage(
+                CoreStage::Last,
+                save_round_system
+                    .system()
+                    .with_run_criteria(quitting_round_criteria.system()),
+            );
+    }
+}
+
+/// File where the round is saved.
+pub struct RoundSaveFile(pub PathBuf);
+
+/// Resource requesting to continue the saved round when entering the game.
+pub struct ContinueRound;
+
+/// Progress of the round stored in resources rather than in components.
+#[derive(Debug, Clone, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct SavedRound {
+    /// Number of items given to Baobei as asked.
+    deliveries: u32,
+    /// Time spent in the round, in seconds.
+    time_survived: f32,
+    /// Time elapsed in the happiness timer, in seconds.
+    happiness_timer: f32,
+    /// Time elapsed since the last request of Baobei, in seconds.
+    request_timer: f32,
+    /// Cooldown of the item actions.
+    action_cooldown: Cooldown,
+    /// Time elapsed since the last mess, in seconds.
+    mess_timer: f32,
+    /// Seed of the round, to continue its random generator.
+    rng_seed: u64,
+    /// Position of the random generator in the round.
+    rng_position: u128,
+}
+
+/// Component on the saved items whose buff is active on Baobei.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+struct SavedBuff {
+    /// Factor of the decay while the buff is active.
+    decay_factor: f32,
+    /// Duration of the buff, in seconds.
+    duration: f32,
+    /// Time elapsed since the buff started, in seconds.
+    elapsed: f32,
+}
+
+impl SavedBuff {
+    /// Returns the saved buff of the modifier, if it is the buff of an item.
+    fn of(modifier: &Modifier) -> Option<(Item, Self)> {
+        match (modifier.kind, &modifier.timer) {
+            (ModifierKind::Buff(item), Some(timer)) => Some((
+                item,
+                Self {
+                    decay_factor: modifier.decay_factor,
+                    duration: timer.duration().as_secs_f32(),
+                    elapsed: timer.elapsed_secs(),
+                },
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the modifier of the buff given by the item, resumed.
+    fn modifier(self, item: Item) -> Modifier {
+        let mut timer = Timer::from_seconds(self.duration, false);
+        timer.set_elapsed(Duration::from_secs_f32(self.elapsed));
+        Modifier {
+            kind: ModifierKind::Buff(item),
+            decay_factor: self.decay_factor,
+            timer: Some(timer),
+        }
+    }
+}
+
+/// Component on the saved items without a position, telling where they are.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+#[reflect_value(Component, PartialEq, Serialize, Deserialize)]
+enum ItemPlace {
+    /// In the inventory of Didi, besides the hand.
+    Inventory,
+    /// On the table.
+    Table,
+    /// Deposited in the workstation.
+    Ingredient,
+    /// Being prepared in the workstation.
+    Preparation,
+    /// Prepared in the workstation, waiting to be collected.
+    Product,
+}
+
+/// Only needed by the reflection, to create the component before applying
+/// its saved value.
+impl Default for ItemPlace {
+    fn default() -> Self {
+        Self::Inventory
+    }
+}
+
+/// State of a round, enough to continue it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundSave {
+    /// Position of Didi.
+    didi_position: Position,
+    /// Item carried by Didi with its freshness, if any.
+    carried_item: Option<(Item, Freshness)>,
+    /// Items stored in the other slots of the inventory.
+    stored_items: Vec<(Item, Freshness)>,
+    /// Item asked by Baobei.
+    asking_item: AskingItem,
+    /// Happiness of Baobei.
+    happiness: Happiness,
+    /// Rest of Baobei.
+    rest: Rest,
+    /// Whether Baobei is napping.
+    napping: bool,
+    /// Buffs of the items given to Baobei, still active.
+    buffs: Vec<(Item, SavedBuff)>,
+    /// Items dropped on the ground.
+    dropped_items: Vec<(Item, Freshness, Position, TriggerArea)>,
+    /// Items left on the table, from the bottom to the top.
+    table_items: Vec<(Item, Freshness)>,
+    /// Content of the workstation.
+    workstation: Workstation,
+    /// Messes waiting to be cleaned.
+    messes: Vec<(MessKind, Position)>,
+    /// Stocks of the item producers, by produced item.
+    stocks: Vec<(Item, Stock)>,
+    /// Progress stored in resources.
+    round: SavedRound,
+}
+
+impl RoundSave {
+    /// Loads the round saved in the file, warning if it is invalid.
+    pub fn load(path: &Path, registry: &TypeRegistry) -> Option<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|error| warn!("Fail to read {}: {}", path.display(), error))
+            .ok()?;
+
+        Self::deserialize(&content, registry)
+            .map_err(|error| warn!("Invalid save {}: {}", path.display(), error))
+            .ok()
+    }
+
+    /// Saves the round in the file.
+    pub fn save(&self, path: &Path, registry: &TypeRegistry) -> io::Result<()> {
+        storage::write(path, &self.serialize(registry)?)
+    }
+
+    /// Writes the round as a RON scene.
+    pub fn serialize(&self, registry: &TypeRegistry) -> io::Result<String> {
+        let mut world = World::new();
+
+        let mut didi = world.spawn();
+        didi.insert_bundle((Didi, self.didi_position));
+        if let Some((item, freshness)) = self.carried_item {
+            didi.insert_bundle((Carrying(item), freshness));
+        }
+        for (item, freshness) in &self.stored_items {
+            world
+                .spawn()
+                .insert_bundle((*item, *freshness, ItemPlace::Inventory));
+        }
+
+        let mut baobei = world.spawn();
+        baobei.insert_bundle((Baobei, self.asking_item, self.happiness, self.rest));
+        if self.napping {
+            baobei.insert(Napping);
+        }
+        for (item, buff) in &self.buffs {
+            world.spawn().insert_bundle((*item, *buff));
+        }
+
+        for (item, freshness, position, trigger_area) in &self.dropped_items {
+            world
+                .spawn()
+                .insert_bundle((*item, *freshness, *position, trigger_area.clone()));
+        }
+        for (item, freshness) in &self.table_items {
+            world
+                .spawn()
+                .insert_bundle((*item, *freshness, ItemPlace::Table));
+        }
+
+        for (item, freshness) in &self.workstation.ingredients {
+            world
+                .spawn()
+                .insert_bundle((*item, *freshness, ItemPlace::Ingredient));
+        }
+        if let Some(preparation) = &self.workstation.preparation {
+            world.spawn().insert_bundle((
+                preparation.product,
+                ItemPlace::Preparation,
+                preparation.timer.clone(),
+            ));
+        }
+        if let Some(item) = self.workstation.product {
+            world.spawn().insert_bundle((item, ItemPlace::Product));
+        }
+
+        for (kind, position) in &self.messes {
+            world.spawn().insert_bundle((*kind, *position));
+        }
+        for (item, stock) in &self.stocks {
+            world.spawn().insert_bundle((*item, stock.clone()));
+        }
+
+        world.spawn().insert(self.round.clone());
+
+        DynamicScene::from_world(&world, registry)
+            .serialize_ron(registry)
+            .map_err(|error| invalid_data(&error.to_string()))
+    }
+
+    /// Reads a round written by `serialize`.
+    pub fn deserialize(content: &str, registry: &TypeRegistry) -> io::Result<Self> {
+        let mut deserializer = ron::de::Deserializer::from_str(content)
+            .map_err(|error| invalid_data(&error.to_string()))?;
+        let scene = SceneDeserializer {
+            type_registry: &registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .map_err(|error| invalid_data(&error.to_string()))?;
+
+        // The scene is spawned in a separate world to read its components.
+        let mut world = World::new();
+        world.insert_resource(registry.clone());
+        scene
+            .write_to_world(&mut world, &mut EntityMap::default())
+            .map_err(|error| invalid_data(&error.to_string()))?;
+
+        // The components added after the first saves are optional, the older
+        // saves get their default value.
+        let (didi_position, carried_item) = world
+            .query_filtered::<(&Position, Option<&Carrying>, Option<&Freshness>), With<Didi>>()
+            .iter(&world)
+            .next()
+            .map(|(position, carrying, freshness)| {
+                let freshness = freshness.copied().unwrap_or_default();
+                (*position, carrying.map(|carrying| (carrying.0, freshness)))
+            })
+            .ok_or_else(|| invalid_data("missing Didi"))?;
+
+        let (asking_item, happiness, rest, napping) = world
+            .query_filtered::<(&AskingItem, &Happiness, Option<&Rest>, Option<&Napping>), With<Baobei>>()
+            .iter(&world)
+            .next()
+            .map(|(asking_item, happiness, rest, napping)| {
+                (
+                    *asking_item,
+                    *happiness,
+                    rest.copied().unwrap_or_default(),
+                    napping.is_some(),
+                )
+            })
+            .ok_or_else(|| invalid_data("missing Baobei"))?;
+
+        let buffs = world
+            .query::<(&Item, &SavedBuff)>()
+            .iter(&world)
+            .map(|(item, buff)| (*item, *buff))
+            .collect();
+
+        let dropped_items = world
+            .query::<(&Item, Option<&Freshness>, &Position, &TriggerArea)>()
+            .iter(&world)
+            .map(|(item, freshness, position, trigger_area)| {
+                let freshness = freshness.copied().unwrap_or_default();
+                (*item, freshness, *position, trigger_area.clone())
+            })
+            .collect();
+
+        let mut stored_items = Vec::new();
+        let mut table_items = Vec::new();
+        let mut workstation = Workstation::default();
+        for (item, place, freshness, timer) in world
+            .query::<(&Item, &ItemPlace, Option<&Freshness>, Option<&Cooldown>)>()
+            .iter(&world)
+        {
+            let freshness = freshness.copied().unwrap_or_default();
+            match place {
+                ItemPlace::Inventory => stored_items.push((*item, freshness)),
+                ItemPlace::Table => table_items.push((*item, freshness)),
+                ItemPlace::Ingredient => workstation.ingredients.push((*item, freshness)),
+                ItemPlace::Preparation => {
+                    workstation.preparation = Some(Preparation {
+                        product: *item,
+                        timer: timer
+                            .cloned()
+                            .ok_or_else(|| invalid_data("missing preparation timer"))?,
+                    });
+                }
+                ItemPlace::Product => workstation.product = Some(*item),
+            }
+        }
+
+        let messes = world
+            .query::<(&MessKind, &Position)>()
+            .iter(&world)
+            .map(|(kind, position)| (*kind, *position))
+            .collect();
+
+        let stocks = world
+            .query::<(&Item, &Stock)>()
+            .iter(&world)
+            .map(|(item, stock)| (*item, stock.clone()))
+            .collect();
+
+        let round = world
+            .query::<&SavedRound>()
+            .iter(&world)
+            .next()
+            .cloned()
+            .ok_or_else(|| invalid_data("missing round"))?;
+
+        Ok(Self {
+            didi_position,
+            carried_item,
+            stored_items,
+            asking_item,
+            happiness,
+            rest,
+            napping,
+            buffs,
+            dropped_items,
+            table_items,
+            workstation,
+            messes,
+            stocks,
+            round,
+        })
+    }
+}
+
+/// Returns an error about invalid content in a save file.
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid save: {}", message),
+    )
+}
+
+/// Runs the save when the game exits during a round.
+fn quitting_round_criteria(
+    state: Res<State<GameState>>,
+    mut exit_events: EventReader<AppExit>,
+) -> ShouldRun {
+    if exit_events.iter().next().is_some() && *state.current() == GameState::InGame {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Resources with the progress of the round, saved with it.
+#[derive(SystemParam)]
+pub struct RoundProgress<'a> {
+    /// Score of the round.
+    score: ResMut<'a, Score>,
+    /// Timer of the happiness decay.
+    happiness_timer: ResMut<'a, HappinessTimer>,
+    /// Cooldown of the item actions.
+    cooldown: ResMut<'a, PickAndDropCooldown>,
+    /// Time before the next mess.
+    mess_timer: ResMut<'a, MessTimer>,
+    /// Random generator of the round.
+    rng: ResMut<'a, GameRng>,
+}
+
+/// Components of Baobei saved with the round.
+type SavedBaobei<'a> = (
+    &'a AskingItem,
+    &'a Cooldown,
+    &'a Happiness,
+    &'a Rest,
+    Option<&'a Napping>,
+    &'a Modifiers,
+);
+
+/// Saves the round in progress in the save file.
+#[allow(clippy::too_many_arguments)]
+fn save_round_system(
+    save_file: Option<Res<RoundSaveFile>>,
+    registry: Res<TypeRegistry>,
+    progress: RoundProgress,
+    didi_query: Query<(&Position, Option<&Carrying>, &Inventory), With<Didi>>,
+    carried_items: Query<&Freshness, With<CarriedItem>>,
+    baobei_query: Query<SavedBaobei, With<Baobei>>,
+    dropped_items: Query<(&Item, Option<&Freshness>, &Position, &TriggerArea)>,
+    items: Query<(&Item, Option<&Freshness>)>,
+    interactables: Query<&Interactable>,
+    workstations: Query<&Workstation>,
+    messes: Query<(&Mess, &Position)>,
+    stocks: Query<(&ItemProducer, &Stock)>,
+) {
+    let save_file = match save_file {
+        Some(save_file) => save_file,
+        None => return,
+    };
+    let (didi_position, carrying, inventory) = match didi_query.iter().next() {
+        Some(didi) => didi,
+        None => return,
+    };
+    let (asking_item, request_cooldown, happiness, rest, napping, modifiers) =
+        match baobei_query.iter().next() {
+            Some(baobei) => baobei,
+            None => return,
+        };
+
+    let save = RoundSave {
+        didi_position: *didi_position,
+        carried_item: carrying.map(|carrying| {
+            let freshness = carried_items.iter().next().copied().unwrap_or_default();
+            (carrying.0, freshness)
+        }),
+        stored_items: inventory.stored.clone(),
+        asking_item: *asking_item,
+        happiness: *happiness,
+        rest: *rest,
+        napping: napping.is_some(),
+        buffs: modifiers.0.iter().filter_map(SavedBuff::of).collect(),
+        dropped_items: dropped_items
+            .iter()
+            .map(|(item, freshness, position, trigger_area)| {
+                let freshness = freshness.copied().unwrap_or_default();
+                (*item, freshness, *position, trigger_area.clone())
+            })
+            .collect(),
+        table_items: interactables
+            .iter()
+            .filter_map(|interactable| match interactable {
+                Interactable::Table { items, .. } => Some(items),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|entity| items.get(*entity).ok())
+            .map(|(item, freshness)| (*item, freshness.copied().unwrap_or_default()))
+            .collect(),
+        workstation: workstations.iter().next().cloned().unwrap_or_default(),
+        messes: messes
+            .iter()
+            .map(|(mess, position)| (mess.kind, *position))
+            .collect(),
+        stocks: stocks
+            .iter()
+            .map(|(ItemProducer(item), stock)| (*item, stock.clone()))
+            .collect(),
+        round: SavedRound {
+            deliveries: progress.score.deliveries,
+            time_survived: progress.score.time_survived,
+            happiness_timer: progress.happiness_timer.0.elapsed_secs(),
+            request_timer: request_cooldown.elapsed(),
+            action_cooldown: progress.cooldown.0.clone(),
+            mess_timer: progress.mess_timer.0.elapsed(),
+            rng_seed: progress.rng.seed(),
+            rng_position: progress.rng.position(),
+        },
+    };
+
+    match save.save(&save_file.0, &registry) {
+        Ok(()) => info!("Round saved in {}", save_file.0.display()),
+        Err(error) => error!("Fail to save the round: {}", error),
+    }
+}
+
+/// Deletes the save of the round that is over, it cannot be continued.
+fn delete_save_system(save_file: Option<Res<RoundSaveFile>>) {
+    if let Some(save_file) = save_file {
+        match fs::remove_file(&save_file.0) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                warn!("Fail to delete the save of the round: {}", error);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Restores the saved round in the new round when the player chose to
+/// continue it.
+#[allow(clippy::too_many_arguments)]
+fn continue_round_system(
+    mut commands: Commands,
+    continue_round: Option<Res<ContinueRound>>,
+    save_file: Option<Res<RoundSaveFile>>,
+    registry: Res<TypeRegistry>,
+    game_data: Res<GameData>,
+    materials: Res<GameplayMaterials>,
+    config: Res<DifficultyConfig>,
+    upgrades: Res<InventoryUpgrades>,
+    mut progress: RoundProgress,
+    mut request_cooldowns: Query<&mut Cooldown, With<Baobei>>,
+    mut asked_item_materials: Query<&mut Handle<ColorMaterial>, With<AskedItem>>,
+    mut interactables: Query<(&mut Interactable, &Position)>,
+    mut workstations: Query<&mut Workstation>,
+    mut stocks: Query<(&ItemProducer, &mut Stock)>,
+) {
+    if continue_round.is_none() {
+        return;
+    }
+    commands.remove_resource::<ContinueRound>();
+
+    let save = match save_file.and_then(|file| RoundSave::load(&file.0, &registry)) {
+        Some(save) => save,
+        None => return,
+    };
+    info!("Continue the saved round");
+
+    // The new round is already set up, without any item nor mess. Its empty
+    // inventory is inserted with commands, so is the saved one.
+    let didi = game_data.didi_entity;
+    commands
+        .entity(didi)
+        .insert(save.didi_position)
+        .insert(Inventory {
+            capacity: upgrades.slots(&config),
+            stored: save.stored_items,
+        });
+    if let Some((item, freshness)) = save.carried_item {
+        let item_in_hand = spawn_carried_item(&mut commands, &materials, didi, item);
+        commands.entity(item_in_hand).insert(freshness);
+    }
+    for (item, freshness, position, trigger_area) in save.dropped_items {
+        spawn_dropped_item(
+            &mut commands,
+            &materials,
+            item,
+            freshness,
+            position,
+            trigger_area,
+        );
+    }
+    for (mut interactable, position) in interactables.iter_mut() {
+        if let Interactable::Table { items, .. } = &mut *interactable {
+            for (item, freshness) in &save.table_items {
+                spawn_table_item(
+                    &mut commands,
+                    &materials,
+                    items,
+                    position.0,
+                    *item,
+                    *freshness,
+                );
+            }
+        }
+    }
+    for mut workstation in workstations.iter_mut() {
+        *workstation = save.workstation.clone();
+    }
+    for (kind, position) in save.messes {
+        spawn_mess(&mut commands, &materials, kind, position.0);
+    }
+    for (ItemProducer(item), mut stock) in stocks.iter_mut() {
+        if let Some((_, saved)) = save
+            .stocks
+            .iter()
+            .find(|(saved_item, _)| saved_item == item)
+        {
+            stock.count = saved.count.min(stock.capacity);
+            stock.refilling = saved.refilling;
+        }
+    }
+
+    let mut baobei = commands.entity(game_data.baobei_entity);
+    baobei
+        .insert(save.asking_item)
+        .insert(save.happiness)
+        .insert(save.rest)
+        .insert(Modifiers(
+            save.buffs
+                .iter()
+                .map(|(item, buff)| buff.modifier(*item))
+                .collect(),
+        ));
+    if save.napping {
+        baobei.insert(Napping);
+    }
+    for mut item_material in asked_item_materials.iter_mut() {
+        *item_material = materials.item_sprite_for(save.asking_item.0);
+    }
+
+    progress.score.deliveries = save.round.deliveries;
+    progress.score.time_survived = save.round.time_survived;
+    progress
+        .happiness_timer
+        .0
+        .set_elapsed(Duration::from_secs_f32(save.round.happiness_timer));
+    for mut request_cooldown in request_cooldowns.iter_mut() {
+        request_cooldown.set_elapsed(save.round.request_timer);
+    }
+    progress.cooldown.0 = save.round.action_cooldown;
+    progress.mess_timer.0.set_elapsed(save.round.mess_timer);
+    // The new round was seeded with the next seed, the saved round continues
+    // with its own.
+    progress
+        .rng
+        .restore(save.round.rng_seed, save.round.rng_position);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{prelude::*, reflect::TypeRegistry};
+
+    use super::{RoundSave, SavedBuff, SavedRound};
+    use crate::{
+        collisions::{Position, TriggerArea},
+        cooldown::Cooldown,
+        gameplay::{
+            chores::MessKind,
+            crafting::{Preparation, Workstation},
+            freshness::Freshness,
+            furniture::Rest,
+            happiness::Happiness,
+            items::{AskingItem, Item},
+            stock::Stock,
+        },
+        testing::TestApp,
+    };
+
+    #[test]
+    fn test_round_save_format() {
+        let mut app = TestApp::new();
+        let registry = app.resource_mut::<TypeRegistry>().clone();
+
+        let save = RoundSave {
+            didi_position: Position(Vec3::new(640.0, 260.0, 0.0)),
+            carried_item: Some((Item::Chips, Freshness(0.75))),
+            stored_items: vec![(Item::Bread, Freshness(0.5))],
+            asking_item: AskingItem(Item::WaterGlass),
+            happiness: Happiness::happy(),
+            rest: Rest(0.25),
+            napping: true,
+            buffs: vec![(
+                Item::IceCream,
+                SavedBuff {
+                    decay_factor: 0.5,
+                    duration: 10.0,
+                    elapsed: 2.5,
+                },
+            )],
+            dropped_items: vec![(
+                Item::IceCream,
+                Freshness(0.125),
+                Position(Vec3::new(300.0, 400.0, 0.0)),
+                TriggerArea::new(75.0, 100.0),
+            )],
+            table_items: vec![(Item::Chips, Freshness(1.0)), (Item::Tea, Freshness(0.5))],
+            workstation: Workstation {
+                ingredients: vec![(Item::Bread, Freshness(0.75))],
+                preparation: Some(Preparation {
+                    product: Item::Tea,
+                    timer: Cooldown::from_seconds(4.0),
+                }),
+                product: Some(Item::Sandwich),
+            },
+            messes: vec![(MessKind::Crumbs, Position(Vec3::new(900.0, 250.0, 0.0)))],
+            stocks: vec![(
+                Item::IceCream,
+                Stock {
+                    count: 1,
+                    capacity: 3,
+                    refilling: 4.5,
+                    restocking: 0.0,
+                },
+            )],
+            round: SavedRound {
+                deliveries: 3,
+                time_survived: 42.5,
+                happiness_timer: 0.25,
+                request_timer: 12.5,
+                action_cooldown: Cooldown::from_seconds(0.2),
+                mess_timer: 7.5,
+                rng_seed: 42,
+                rng_position: 96,
+            },
+        };
+
+        let content = save.serialize(&registry).unwrap();
+        // Only the progress of the cooldowns is saved, not their setup.
+        assert!(content.contains("remaining"));
+        assert!(!content.contains("just_finished") && !content.contains("repeating"));
+        assert_eq!(RoundSave::deserialize(&content, &registry).unwrap(), save);
+        assert!(RoundSave::deserialize("not a save", &registry).is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! Score of the current round.
+
+use bevy::prelude::*;
+
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    constants::GameState,
+};
+
+/// Plugin counting the score of the round.
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Score>().add_system_set_to_stage(
+            FixedUpdateStage,
+            SystemSet::on_update(GameState::InGame).with_system(survive_system.system()),
+        );
+    }
+}
+
+/// Score of the current round.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Score {
+    /// Number of items given to Baobei as asked.
+    pub deliveries: u32,
+    /// Time spent in the round, in seconds.
+    pub time_survived: f32,
+}
+
+/// Counts the time spent in the round.
+fn survive_system(mut score: ResMut<Score>) {
+    score.time_survived += STEP.as_secs_f32();
+}
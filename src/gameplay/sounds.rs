@@ -0,0 +1,112 @@
+//! Sound effects of the actions and of the emotions of Baobei.
+
+use bevy::prelude::*;
+
+use crate::{
+    audio::{Mixer, Sound},
+    clock::FixedUpdateStage,
+    collisions::{CollisionSystems, Contact, ContactEvent},
+    constants::GameState,
+};
+
+use super::{
+    entities::GameData,
+    happiness::{Happiness, HappinessSystems},
+    items::{ActionEvent, AskingItem, ItemProducer, ItemSystems},
+    stock::ProducerEmptyEvent,
+    Baobei,
+};
+
+/// Plugin asking the mixer to play the sounds of the gameplay.
+pub struct GameplaySoundsPlugin;
+
+impl Plugin for GameplaySoundsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set_to_stage(
+            FixedUpdateStage,
+            SystemSet::on_update(GameState::InGame)
+                .with_system(
+                    action_sounds_system
+                        .system()
+                        .after("item_actions")
+                        .before("handle_actions"),
+                )
+                .with_system(producer_sound_system.system().after(CollisionSystems))
+                .with_system(producer_empty_sound_system.system().after(ItemSystems))
+                .with_system(crying_sound_system.system().after(HappinessSystems)),
+        );
+    }
+}
+
+/// Plays the sound of the actions of the player.
+///
+/// It runs before the actions are handled, to know whether the given item is
+/// the one Baobei asks for.
+fn action_sounds_system(
+    mut mixer: ResMut<Mixer>,
+    mut action_events: EventReader<ActionEvent>,
+    item_askers: Query<&AskingItem, With<Baobei>>,
+) {
+    for action in action_events.iter() {
+        let sound = match action {
+            ActionEvent::Take(_, _) | ActionEvent::Collect(_, _) => Sound::Take,
+            ActionEvent::PutAway(_) | ActionEvent::Deposit(_, _) | ActionEvent::Restock(_) => {
+                Sound::PutAway
+            }
+            ActionEvent::Drop(_) | ActionEvent::ThrowAway(_) => Sound::Drop,
+            ActionEvent::PickUp(_, _) | ActionEvent::Retrieve(_) => Sound::PickUp,
+            ActionEvent::Store(_, _) => Sound::PutAway,
+            ActionEvent::Keep(_) | ActionEvent::Nap(_) | ActionEvent::Prepare(_) => continue,
+            ActionEvent::Clean(_) | ActionEvent::Wash(_, _) => Sound::Clean,
+            ActionEvent::Give(item) => {
+                if item_askers.iter().any(|asking_item| asking_item.0 == *item) {
+                    Sound::GiveCorrect
+                } else {
+                    Sound::GiveWrong
+                }
+            }
+        };
+        mixer.play(sound);
+    }
+}
+
+/// Plays a sound when Didi comes in front of an item producer.
+fn producer_sound_system(
+    game_data: Res<GameData>,
+    mut mixer: ResMut<Mixer>,
+    mut contact_events: EventReader<ContactEvent>,
+    item_producers: Query<(), With<ItemProducer>>,
+) {
+    for event in contact_events.iter() {
+        if let ContactEvent::Started(Contact(entity, other)) = event {
+            if *entity == game_data.didi_entity && item_producers.get(*other).is_ok() {
+                mixer.play(Sound::Producer);
+            }
+        }
+    }
+}
+
+/// Plays a sound when Didi takes the last item of a producer.
+fn producer_empty_sound_system(
+    mut mixer: ResMut<Mixer>,
+    mut empty_events: EventReader<ProducerEmptyEvent>,
+) {
+    for _ in empty_events.iter() {
+        mixer.play(Sound::Empty);
+    }
+}
+
+/// Makes Baobei cry when its emotion gets sadder.
+fn crying_sound_system(
+    mut mixer: ResMut<Mixer>,
+    mut last_emotion: Local<Option<usize>>,
+    happiness_values: Query<&Happiness, (With<Baobei>, Changed<Happiness>)>,
+) {
+    for happiness in happiness_values.iter() {
+        let emotion = happiness.emotion();
+        if matches!(*last_emotion, Some(last) if emotion < last) {
+            mixer.play(Sound::Crying);
+        }
+        *last_emotion = Some(emotion);
+    }
+}
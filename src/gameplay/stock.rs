@@ -0,0 +1,315 @@
+//! Stock of the item producers: taking an item empties them little by
+//! little, they refill slowly or at once when Didi restocks them.
+
+use bevy::prelude::*;
+
+use crate::{
+    clock::{FixedUpdateStage, STEP},
+    constants::GameState,
+};
+
+use super::{
+    items::{ActionEvent, HeldAction, Item, ItemProducer, ItemSystems},
+    round::NewRoundSystems,
+};
+
+/// Plugin managing the stock of the item producers.
+pub struct StockPlugin;
+
+impl Plugin for StockPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_event::<ProducerEmptyEvent>()
+            .init_resource::<StockConfig>()
+            .register_type::<Stock>()
+            .add_system_set_to_stage(
+                FixedUpdateStage,
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(
+                        hold_to_restock_system
+                            .system()
+                            .label("item_actions")
+                            .after("held_action"),
+                    )
+                    .with_system(
+                        handle_stock_actions_system
+                            .system()
+                            .label(ItemSystems)
+                            .after("item_actions"),
+                    )
+                    .with_system(refill_stocks_system.system().after(ItemSystems)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(update_stock_badges_system.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .label(NewRoundSystems)
+                    .with_system(reset_stocks_system.system()),
+            );
+    }
+}
+
+/// Parameters of the stock of the item producers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockConfig {
+    /// Number of items each producer holds when full, the producers missing
+    /// here produce infinitely.
+    pub capacities: Vec<(Item, usize)>,
+    /// Seconds for a producer to get back one item by itself.
+    pub refill_interval: f32,
+    /// Seconds to hold interact to restock an empty producer.
+    pub restock_duration: f32,
+}
+
+impl Default for StockConfig {
+    fn default() -> Self {
+        Self {
+            capacities: vec![
+                (Item::IceCream, 3),
+                (Item::WaterGlass, 4),
+                (Item::Chips, 5),
+                (Item::Bread, 5),
+            ],
+            refill_interval: 20.0,
+            restock_duration: 2.0,
+        }
+    }
+}
+
+impl StockConfig {
+    /// Returns the number of items the producer of the item holds, if limited.
+    pub fn capacity(&self, item: Item) -> Option<usize> {
+        self.capacities
+            .iter()
+            .find(|(stocked_item, _)| *stocked_item == item)
+            .map(|(_, capacity)| *capacity)
+    }
+}
+
+/// Component on item producers with a limited number of items.
+#[derive(Debug, Clone, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct Stock {
+    /// Number of items left.
+    pub count: usize,
+    /// Number of items when full.
+    pub capacity: usize,
+    /// Seconds since the last item came back by itself.
+    pub refilling: f32,
+    /// Seconds Didi spent restocking the producer without interruption.
+    pub restocking: f32,
+}
+
+impl Stock {
+    /// Returns a full stock of the given capacity.
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            count: capacity,
+            capacity,
+            refilling: 0.0,
+            restocking: 0.0,
+        }
+    }
+
+    /// Returns true if no item is left.
+    pub const fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns true if no item is missing.
+    pub const fn is_full(&self) -> bool {
+        self.count >= self.capacity
+    }
+
+    /// Adds an item back, if not full.
+    pub fn put_back(&mut self) {
+        self.count = (self.count + 1).min(self.capacity);
+    }
+}
+
+/// An event sent when Didi takes the last item of a producer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProducerEmptyEvent {
+    /// Entity of the producer.
+    pub producer: Entity,
+    /// Item missing in the producer.
+    pub item: Item,
+}
+
+/// Tag the badge showing the stock over a producer.
+struct StockBadge;
+
+/// Adds the stock to the producer, with its badge.
+pub fn insert_stock(
+    commands: &mut Commands,
+    producer: Entity,
+    capacity: usize,
+    font: Handle<Font>,
+) {
+    commands
+        .entity(producer)
+        .insert(Stock::new(capacity))
+        .insert(Transform::default())
+        .insert(GlobalTransform::default())
+        .with_children(|parent| {
+            parent
+                .spawn()
+                .insert(StockBadge)
+                .insert_bundle(Text2dBundle {
+                    text: Text::with_section(
+                        capacity.to_string(),
+                        TextStyle {
+                            font,
+                            font_size: 30.0,
+                            color: Color::WHITE,
+                        },
+                        TextAlignment {
+                            vertical: VerticalAlign::Center,
+                            horizontal: HorizontalAlign::Center,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, 70.0, 0.1),
+                    ..Text2dBundle::default()
+                });
+        });
+}
+
+/// Restocks the empty producer targeted by Didi while the interact button
+/// is held with empty hands.
+fn hold_to_restock_system(
+    config: Res<StockConfig>,
+    held_action: Res<HeldAction>,
+    mut action_events: EventWriter<ActionEvent>,
+    mut stocks: Query<(Entity, &mut Stock)>,
+) {
+    let target = match held_action.0 {
+        Some(ActionEvent::Restock(producer)) => Some(producer),
+        _ => None,
+    };
+
+    for (entity, mut stock) in stocks.iter_mut() {
+        if Some(entity) == target {
+            stock.restocking += STEP.as_secs_f32();
+            if stock.restocking >= config.restock_duration {
+                action_events.send(ActionEvent::Restock(entity));
+            }
+        } else if stock.restocking > 0.0 {
+            stock.restocking = 0.0;
+        }
+    }
+}
+
+/// Handles the actions on stocked producers:
+/// - Remove an item from the stock when taking, sending an event once empty
+/// - Add an item back to the stock when putting away
+/// - Add the clean item back to its stock when washing a dirty one
+/// - Fill the stock when restocking
+fn handle_stock_actions_system(
+    mut action_events: EventReader<ActionEvent>,
+    mut empty_events: EventWriter<ProducerEmptyEvent>,
+    mut stocks: Query<(Entity, &ItemProducer, &mut Stock)>,
+) {
+    for action in action_events.iter() {
+        match action {
+            ActionEvent::Take(entity, item) => {
+                if let Ok((_, _, mut stock)) = stocks.get_mut(*entity) {
+                    stock.count = stock.count.saturating_sub(1);
+                    if stock.is_empty() {
+                        info!("No more {:?} in the producer", item);
+                        empty_events.send(ProducerEmptyEvent {
+                            producer: *entity,
+                            item: *item,
+                        });
+                    }
+                }
+            }
+            ActionEvent::PutAway(item) => {
+                let producer = stocks
+                    .iter_mut()
+                    .find(|(_, ItemProducer(produced_item), _)| produced_item == item);
+                if let Some((_, _, mut stock)) = producer {
+                    stock.put_back();
+                }
+            }
+            ActionEvent::Wash(_, item) => {
+                let producer = item.washed().and_then(|clean_item| {
+                    stocks
+                        .iter_mut()
+                        .find(|(_, ItemProducer(produced_item), _)| *produced_item == clean_item)
+                });
+                if let Some((_, _, mut stock)) = producer {
+                    stock.put_back();
+                }
+            }
+            ActionEvent::Restock(entity) => {
+                if let Ok((_, ItemProducer(item), mut stock)) = stocks.get_mut(*entity) {
+                    info!("Restock the producer of {:?}", item);
+                    *stock = Stock::new(stock.capacity);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Brings back the items of the producers one by one over time.
+fn refill_stocks_system(config: Res<StockConfig>, mut stocks: Query<&mut Stock>) {
+    for mut stock in stocks.iter_mut() {
+        if stock.is_full() {
+            continue; // Avoid to trigger the change detection.
+        }
+        stock.refilling += STEP.as_secs_f32();
+        if stock.refilling >= config.refill_interval {
+            stock.refilling = 0.0;
+            stock.count += 1;
+        }
+    }
+}
+
+/// Shows the number of items left over the producers, in red when empty.
+fn update_stock_badges_system(
+    stocks: Query<(&Stock, &Children), Changed<Stock>>,
+    mut badges: Query<&mut Text, With<StockBadge>>,
+) {
+    for (stock, children) in stocks.iter() {
+        for child in children.iter() {
+            if let Ok(mut text) = badges.get_mut(*child) {
+                let section = &mut text.sections[0];
+                section.value = stock.count.to_string();
+                section.style.color = if stock.is_empty() {
+                    Color::RED
+                } else {
+                    Color::WHITE
+                };
+            }
+        }
+    }
+}
+
+/// Fills the stocks for the new round.
+fn reset_stocks_system(mut stocks: Query<&mut Stock>) {
+    for mut stock in stocks.iter_mut() {
+        *stock = Stock::new(stock.capacity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Item, Stock, StockConfig};
+
+    #[test]
+    fn test_stock() {
+        let config = StockConfig::default();
+        assert_eq!(config.capacity(Item::IceCream), Some(3));
+        assert_eq!(config.capacity(Item::Sponge), None);
+
+        let mut stock = Stock::new(2);
+        assert!(stock.is_full());
+        stock.put_back();
+        assert_eq!(stock.count, 2);
+        stock.count = 0;
+        assert!(stock.is_empty());
+        assert!(!stock.is_full());
+    }
+}
@@ -0,0 +1,1527 @@
+//! Scenario tests of the gameplay, driven by the `TestApp`.
+
+use std::{env, fs, time::Duration};
+
+use bevy::{app::Events, prelude::*};
+
+use crate::{
+    animation::Animation,
+    audio::{Mixer, Music, PlayedSounds, Sound},
+    clock::{GameClock, STEP},
+    collisions::{Contact, Position, TriggerArea},
+    constants::GameState,
+    controllers::{InputDevice, InteractAction},
+    cooldown::{Cooldown, CooldownFinishedEvent},
+    difficulty::{Difficulty, DifficultyConfig},
+    rng::GameRng,
+    settings::{Language, Settings},
+    testing::{TestApp, SEED},
+};
+
+use super::{
+    chores::{ChoreConfig, Mess, MessKind},
+    crafting::Workstation,
+    entities::didi_start_position,
+    freshness::{Freshness, FreshnessConfig, TrashBin},
+    furniture::{Napping, OnTable, Rest},
+    happiness::Happiness,
+    hold_ring::HoldDot,
+    hud::{DisplayedHappiness, Popup},
+    inventory::{Inventory, InventoryUpgrades},
+    items::{dropped_item_area, AskingItem, CarriedItem, Carrying, Item, ItemProducer},
+    modifiers::{Modifier, ModifierConfig, ModifierKind, Modifiers},
+    movement::Facing,
+    patience::DOT_COUNT,
+    prompts::{InteractionPrompt, TargetHighlight},
+    save::{ContinueRound, RoundSaveFile},
+    score::Score,
+    stock::Stock,
+};
+
+/// Position of Didi in the middle of the room, far from any trigger area.
+fn middle_of_the_room() -> Vec3 {
+    Vec3::new(640.0, 260.0, 0.0)
+}
+
+/// Position of Didi in front of Baobei.
+fn near_baobei() -> Vec3 {
+    Vec3::new(1050.0, 230.0, 0.0)
+}
+
+/// Position of Didi in front of the producer of the given item.
+fn near_producer(item: Item) -> Vec3 {
+    match item {
+        Item::IceCream => Vec3::new(720.0, 440.0, 0.0),
+        Item::WaterGlass => Vec3::new(990.0, 460.0, 0.0),
+        Item::Chips => Vec3::new(210.0, 450.0, 0.0),
+        Item::Bread => Vec3::new(120.0, 450.0, 0.0),
+        Item::Sponge => Vec3::new(880.0, 465.0, 0.0),
+        Item::Tea | Item::Sandwich | Item::Trash | Item::DirtyGlass => {
+            panic!("No producer of {:?}", item)
+        }
+    }
+}
+
+/// Position of Didi in front of the basin of the sink.
+fn near_sink() -> Vec3 {
+    Vec3::new(1110.0, 460.0, 0.0)
+}
+
+/// Position of Didi in front of the workstation of the kitchen.
+fn near_workstation() -> Vec3 {
+    Vec3::new(300.0, 450.0, 0.0)
+}
+
+/// Position of Didi next to the trash bin.
+fn near_trash_bin() -> Vec3 {
+    Vec3::new(170.0, 110.0, 0.0)
+}
+
+/// Position of Didi in front of the table.
+fn near_table() -> Vec3 {
+    Vec3::new(300.0, 170.0, 0.0)
+}
+
+/// Position of Didi at the end of the couch, away from Baobei.
+fn near_couch() -> Vec3 {
+    Vec3::new(1180.0, 230.0, 0.0)
+}
+
+/// Moves Didi to the given position and waits for contacts to be detected.
+fn walk_to(app: &mut TestApp, position: Vec3) {
+    let didi = app.didi();
+    app.teleport(didi, position);
+    app.run_frames(1);
+}
+
+/// Presses the interact key and waits for the cooldown of the action.
+fn interact(app: &mut TestApp) {
+    app.tap(KeyCode::Space);
+    app.run_seconds(0.3);
+}
+
+/// Holds the interact key for the given duration, then releases it.
+fn hold_interact(app: &mut TestApp, seconds: f32) {
+    app.press(KeyCode::Space);
+    app.run_seconds(seconds);
+    app.release(KeyCode::Space);
+    app.run_frames(1);
+}
+
+/// Returns the item asked by Baobei.
+fn asked_item(app: &mut TestApp) -> Item {
+    let baobei = app.baobei();
+    app.get::<AskingItem>(baobei).unwrap().0
+}
+
+/// Makes Baobei ask for the given item.
+fn ask_for(app: &mut TestApp, item: Item) {
+    let baobei = app.baobei();
+    app.world().get_mut::<AskingItem>(baobei).unwrap().0 = item;
+}
+
+/// Returns the happiness of Baobei.
+fn happiness(app: &mut TestApp) -> f32 {
+    let baobei = app.baobei();
+    app.get::<Happiness>(baobei).unwrap().value()
+}
+
+/// Returns the item carried by Didi.
+fn carried_item(app: &mut TestApp) -> Option<Item> {
+    let didi = app.didi();
+    app.get::<Carrying>(didi).map(|carrying| carrying.0)
+}
+
+/// Returns an item different than the given one.
+const fn other_item(item: Item) -> Item {
+    match item {
+        Item::IceCream => Item::Chips,
+        _ => Item::IceCream,
+    }
+}
+
+#[test]
+fn test_didi_moves_toward_direction() {
+    let mut app = TestApp::new();
+    let didi = app.didi();
+    let start = app.get::<Position>(didi).unwrap().0;
+
+    for _ in 0..5 {
+        app.send_direction(Vec3::X);
+        app.run_frames(1);
+    }
+
+    // 6 steps of 1/60s during 5 frames of 20ms, at 750 px/s
+    let moved = app.get::<Position>(didi).unwrap().0 - start;
+    assert!((moved.x - 75.0).abs() < 0.01);
+    assert!(moved.y.abs() < 0.01);
+}
+
+/// Moves Didi up right during half a second at the given frame rate, and
+/// returns the position of Didi and the happiness of Baobei.
+fn walk_at_frame_rate(frame_duration: Duration, frames: u32) -> (Vec3, f32) {
+    let mut app = TestApp::new();
+    for _ in 0..frames {
+        app.send_direction(Vec3::new(1.0, 1.0, 0.0));
+        app.run_frame(frame_duration);
+    }
+    let didi = app.didi();
+    (app.get::<Position>(didi).unwrap().0, happiness(&mut app))
+}
+
+#[test]
+fn test_same_simulation_at_any_frame_rate() {
+    let at_30_fps = walk_at_frame_rate(Duration::from_nanos(33_333_334), 15);
+    let at_240_fps = walk_at_frame_rate(Duration::from_nanos(4_166_667), 120);
+
+    assert_eq!(at_30_fps, at_240_fps);
+}
+
+#[test]
+fn test_long_frame_runs_limited_steps() {
+    let mut app = TestApp::new();
+    let didi = app.didi();
+    let start = app.get::<Position>(didi).unwrap().0;
+
+    app.send_direction(Vec3::X);
+    app.run_frame(Duration::from_secs(1));
+
+    // Only 10 steps of 1/60s are simulated after a stall, at 750 px/s
+    let moved = app.get::<Position>(didi).unwrap().0 - start;
+    assert!((moved.x - 125.0).abs() < 0.01);
+}
+
+#[test]
+fn test_contact_with_trigger_area() {
+    let mut app = TestApp::new();
+    let didi = app.didi();
+    assert!(app.contacts_of(didi).is_empty());
+
+    walk_to(&mut app, near_baobei());
+    let baobei = app.baobei();
+    assert_eq!(app.contacts_of(didi), vec![baobei]);
+
+    walk_to(&mut app, middle_of_the_room());
+    assert!(app.contacts_of(didi).is_empty());
+}
+
+#[test]
+fn test_take_and_put_away_item() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::IceCream));
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::IceCream));
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+}
+
+#[test]
+fn test_tap_during_cooldown() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::IceCream));
+
+    // The second tap puts the ice cream away once the cooldown is over.
+    app.tap(KeyCode::Space);
+    app.run_frames(1);
+    app.tap(KeyCode::Space);
+    app.run_seconds(0.5);
+    assert_eq!(carried_item(&mut app), None);
+    assert_eq!(stock_left(&mut app, Item::IceCream), 3);
+}
+
+#[test]
+fn test_tap_between_two_steps() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::Chips));
+
+    // Frames far shorter than a step, as at a high frame rate or a low speed,
+    // starting right after a step.
+    *app.resource_mut::<GameClock>() = GameClock::manual();
+    let short_frame = Duration::from_millis(2);
+    app.press(KeyCode::Space);
+    app.run_frame(short_frame);
+    app.release(KeyCode::Space);
+    app.run_frame(short_frame);
+    assert!(!app.resource_mut::<InteractAction>().pressed);
+
+    // The next steps still see the tap.
+    app.run_seconds(0.1);
+    assert_eq!(carried_item(&mut app), Some(Item::Chips));
+}
+
+/// Returns the items stored in the other slots of the inventory of Didi.
+fn stored_items(app: &mut TestApp) -> Vec<Item> {
+    let didi = app.didi();
+    app.get::<Inventory>(didi)
+        .unwrap()
+        .stored
+        .iter()
+        .map(|(item, _)| *item)
+        .collect()
+}
+
+/// Presses the cycle key and waits for the cooldown of the action.
+fn cycle(app: &mut TestApp) {
+    app.tap(KeyCode::Tab);
+    app.run_seconds(0.3);
+}
+
+#[test]
+fn test_keep_item_when_inventory_is_full() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::WaterGlass));
+    assert_eq!(stored_items(&mut app), vec![Item::Chips]);
+
+    walk_to(&mut app, near_producer(Item::IceCream));
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::WaterGlass));
+    assert_eq!(stored_items(&mut app), vec![Item::Chips]);
+
+    // Putting away the item in hand takes the stored one in hand.
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::Chips));
+    assert!(stored_items(&mut app).is_empty());
+}
+
+#[test]
+fn test_cycle_and_give_stored_item() {
+    let mut app = TestApp::new();
+    let item = Item::WaterGlass;
+    ask_for(&mut app, item);
+    walk_to(&mut app, near_producer(item));
+    interact(&mut app);
+    walk_to(&mut app, near_producer(other_item(item)));
+    interact(&mut app);
+
+    cycle(&mut app);
+    assert_eq!(carried_item(&mut app), Some(item));
+    assert_eq!(stored_items(&mut app), vec![other_item(item)]);
+    cycle(&mut app);
+    assert_eq!(carried_item(&mut app), Some(other_item(item)));
+
+    // The asked item is given from its slot.
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(other_item(item)));
+    assert!(stored_items(&mut app).is_empty());
+    assert_eq!(app.resource_mut::<Score>().deliveries, 1);
+}
+
+#[test]
+fn test_cycle_between_two_steps() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+    app.run_seconds(0.3);
+
+    *app.resource_mut::<GameClock>() = GameClock::manual();
+    let short_frame = Duration::from_millis(2);
+    app.press(KeyCode::Tab);
+    app.run_frame(short_frame);
+    app.release(KeyCode::Tab);
+    app.run_frame(short_frame);
+
+    app.run_seconds(0.1);
+    assert_eq!(carried_item(&mut app), Some(Item::WaterGlass));
+}
+
+#[test]
+fn test_drop_and_pick_up_item() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+
+    walk_to(&mut app, middle_of_the_room());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::WaterGlass));
+
+    // With an item in hand, the item is picked up if a slot is free.
+    interact(&mut app);
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+    walk_to(&mut app, middle_of_the_room());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::WaterGlass));
+    assert_eq!(stored_items(&mut app), vec![Item::Chips]);
+
+    // Otherwise the item in hand is dropped.
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::Chips));
+    assert!(stored_items(&mut app).is_empty());
+}
+
+/// Puts the item on the ground at the given position.
+fn spawn_item_on_ground(app: &mut TestApp, item: Item, position: Vec3) {
+    app.world()
+        .spawn()
+        .insert(item)
+        .insert(Freshness::default())
+        .insert(Position(position))
+        .insert(dropped_item_area());
+}
+
+#[test]
+fn test_pick_up_item_in_front() {
+    for &(facing, item_in_front) in &[(Facing::Left, Item::Chips), (Facing::Right, Item::Bread)] {
+        let mut app = TestApp::new();
+        let position = middle_of_the_room();
+        spawn_item_on_ground(&mut app, Item::Chips, position - Vec3::new(50.0, 0.0, 0.0));
+        spawn_item_on_ground(&mut app, Item::Bread, position + Vec3::new(50.0, 0.0, 0.0));
+        let didi = app.didi();
+        *app.world().get_mut::<Facing>(didi).unwrap() = facing;
+        walk_to(&mut app, position);
+
+        interact(&mut app);
+        assert_eq!(carried_item(&mut app), Some(item_in_front));
+        assert_eq!(dropped_items(&mut app).len(), 1);
+    }
+}
+
+#[test]
+fn test_producer_before_nearer_item() {
+    let mut app = TestApp::new();
+    let position = near_producer(Item::IceCream);
+    spawn_item_on_ground(&mut app, Item::Chips, position - Vec3::new(50.0, 0.0, 0.0));
+    walk_to(&mut app, position);
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::IceCream));
+    assert_eq!(
+        dropped_items(&mut app),
+        vec![(Item::Chips, position - Vec3::new(50.0, 0.0, 0.0))]
+    );
+}
+
+#[test]
+fn test_give_before_throwing_away() {
+    let mut app = TestApp::new();
+    ask_for(&mut app, Item::IceCream);
+    walk_to(&mut app, near_producer(Item::IceCream));
+    interact(&mut app);
+
+    // A trash bin left of Baobei, both in reach of Didi.
+    app.world()
+        .spawn()
+        .insert(TrashBin)
+        .insert(Position(Vec3::new(900.0, 100.0, 0.0)))
+        .insert(TriggerArea::new(130.0, 110.0));
+    walk_to(&mut app, Vec3::new(955.0, 100.0, 0.0));
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+    assert_eq!(app.resource_mut::<Score>().deliveries, 1);
+}
+
+#[test]
+fn test_didi_faces_its_movement() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+
+    walk_to(&mut app, middle_of_the_room());
+    app.send_direction(Vec3::X);
+    app.run_frames(2);
+    let didi = app.didi();
+    assert_eq!(app.get::<Facing>(didi), Some(&Facing::Right));
+    assert!(app.get::<Transform>(didi).unwrap().scale.x < 0.0);
+
+    interact(&mut app);
+    let didi_position = app.get::<Position>(didi).unwrap().0;
+    let dropped = dropped_items(&mut app);
+    assert_eq!(dropped.len(), 1);
+    assert!(dropped[0].1.x > didi_position.x);
+}
+
+#[test]
+fn test_give_asked_item() {
+    let mut app = TestApp::new();
+    app.run_seconds(3.5);
+    let initial_happiness = happiness(&mut app);
+
+    let item = Item::WaterGlass;
+    ask_for(&mut app, item);
+    walk_to(&mut app, near_producer(item));
+    interact(&mut app);
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+
+    assert_eq!(carried_item(&mut app), None);
+    assert_ne!(asked_item(&mut app), item);
+    assert!(happiness(&mut app) > initial_happiness);
+}
+
+/// Returns the texts of the happiness popups.
+fn popup_texts(app: &mut TestApp) -> Vec<String> {
+    let world = app.world();
+    world
+        .query_filtered::<&Text, With<Popup>>()
+        .iter(world)
+        .map(|text| text.sections[0].value.clone())
+        .collect()
+}
+
+#[test]
+fn test_happiness_popups() {
+    let mut app = TestApp::new();
+    let item = Item::WaterGlass;
+    ask_for(&mut app, item);
+    walk_to(&mut app, near_producer(other_item(item)));
+    interact(&mut app);
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+    assert_eq!(popup_texts(&mut app), vec!["-15%"]);
+
+    walk_to(&mut app, near_producer(other_item(item)));
+    interact(&mut app);
+    walk_to(&mut app, near_producer(item));
+    interact(&mut app);
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+    assert!(popup_texts(&mut app).contains(&"+15%".to_string()));
+
+    app.run_seconds(1.5);
+    assert!(popup_texts(&mut app).is_empty());
+}
+
+#[test]
+fn test_hud_reset_between_rounds() {
+    let mut app = TestApp::new();
+    let item = Item::WaterGlass;
+    ask_for(&mut app, item);
+    walk_to(&mut app, near_producer(other_item(item)));
+    interact(&mut app);
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+    assert_eq!(popup_texts(&mut app), vec!["-15%"]);
+
+    app.resource_mut::<State<GameState>>()
+        .set(GameState::Menu)
+        .unwrap();
+    app.run_frames(1);
+    assert!(popup_texts(&mut app).is_empty());
+
+    app.resource_mut::<State<GameState>>()
+        .set(GameState::InGame)
+        .unwrap();
+    app.run_frames(1);
+    assert_eq!(
+        app.world().get_resource::<DisplayedHappiness>().unwrap().0,
+        Happiness::happy().value()
+    );
+}
+
+#[test]
+fn test_give_wrong_item() {
+    let mut app = TestApp::new();
+    let item = other_item(asked_item(&mut app));
+    walk_to(&mut app, near_producer(item));
+    interact(&mut app);
+    walk_to(&mut app, near_baobei());
+
+    let initial_happiness = happiness(&mut app);
+    app.tap(KeyCode::Space);
+    app.run_frames(1);
+
+    assert_eq!(carried_item(&mut app), Some(item));
+    assert!((initial_happiness - happiness(&mut app) - 0.15).abs() < 1e-4);
+}
+
+/// Returns the number of dots shown in the ring of the timed action.
+fn hold_ring_dots(app: &mut TestApp) -> usize {
+    let world = app.world();
+    world
+        .query_filtered::<&Visible, With<HoldDot>>()
+        .iter(world)
+        .filter(|visible| visible.is_visible)
+        .count()
+}
+
+#[test]
+fn test_holding_does_not_repeat_actions() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::IceCream));
+    hold_interact(&mut app, 1.0);
+    assert_eq!(carried_item(&mut app), None);
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::IceCream));
+    hold_interact(&mut app, 1.0);
+    assert_eq!(carried_item(&mut app), Some(Item::IceCream));
+}
+
+#[test]
+fn test_prepare_tea() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    walk_to(&mut app, near_workstation());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+
+    // Nothing to collect until the preparation is done by holding interact.
+    app.run_seconds(5.0);
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+
+    app.press(KeyCode::Space);
+    app.run_seconds(2.0);
+    let dots = hold_ring_dots(&mut app);
+    assert!(dots > 0 && dots < DOT_COUNT);
+    app.run_seconds(2.5);
+    app.release(KeyCode::Space);
+    app.run_frames(1);
+    assert_eq!(hold_ring_dots(&mut app), 0);
+    assert_eq!(carried_item(&mut app), None);
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::Tea));
+}
+
+#[test]
+fn test_prepare_or_clean_the_target() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    walk_to(&mut app, near_workstation());
+    interact(&mut app);
+    assert_eq!(
+        shown_prompt(&mut app).as_deref(),
+        Some("Hold Space: Prepare")
+    );
+
+    // A mess next to the kitchen is cleaned first, the tea waiting for it.
+    app.world()
+        .spawn()
+        .insert(Mess {
+            kind: MessKind::Crumbs,
+            cleaning: 0.0,
+        })
+        .insert(Position(near_workstation() + Vec3::new(30.0, -40.0, 0.0)))
+        .insert(TriggerArea::new(80.0, 60.0));
+    walk_to(&mut app, near_workstation() + Vec3::new(0.0, -10.0, 0.0));
+    assert_eq!(shown_prompt(&mut app).as_deref(), Some("Hold Space: Clean"));
+    hold_interact(&mut app, 2.5);
+    assert!(mess_positions(&mut app).is_empty());
+    let world = app.world();
+    let progress = world
+        .query::<&Workstation>()
+        .iter(world)
+        .find_map(Workstation::progress)
+        .unwrap();
+    assert!(progress > 0.0 && progress < 0.25);
+
+    hold_interact(&mut app, 4.0);
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::Tea));
+}
+
+#[test]
+fn test_prepare_sandwich() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::Bread));
+    interact(&mut app);
+    walk_to(&mut app, near_workstation());
+    let world = app.world();
+    for mut freshness in world
+        .query_filtered::<&mut Freshness, With<CarriedItem>>()
+        .iter_mut(world)
+    {
+        freshness.0 = 0.25;
+    }
+    interact(&mut app);
+
+    // An ingredient can be taken back before the preparation, as stale as
+    // it was.
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::Bread));
+    let freshness = carried_freshness(&mut app).unwrap();
+    assert!(freshness.0 > 0.2 && freshness.0 <= 0.25);
+    interact(&mut app);
+
+    // The preparation starts once all the ingredients are deposited.
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+    walk_to(&mut app, near_workstation());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+
+    hold_interact(&mut app, 6.5);
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::Sandwich));
+}
+
+/// Takes the item in its producer, or prepares it in the workstation when it
+/// is crafted, starting with empty hands.
+fn fetch(app: &mut TestApp, item: Item) {
+    let (ingredients, duration) = match item {
+        Item::Tea => (vec![Item::WaterGlass], 4.5),
+        Item::Sandwich => (vec![Item::Bread, Item::Chips], 6.5),
+        _ => {
+            walk_to(app, near_producer(item));
+            interact(app);
+            return;
+        }
+    };
+    for ingredient in ingredients {
+        walk_to(app, near_producer(ingredient));
+        interact(app);
+        walk_to(app, near_workstation());
+        interact(app);
+    }
+    hold_interact(app, duration);
+    interact(app);
+}
+
+#[test]
+fn test_crafted_items_are_asked() {
+    for count in 2..=Item::PRODUCED.len() {
+        let pool = Item::pool(count);
+        assert_eq!(pool.len(), count + Item::CRAFTED.len());
+        assert!(pool.contains(&Item::Tea) && pool.contains(&Item::Sandwich));
+    }
+
+    let mut app = TestApp::new();
+    let item = Item::Sandwich;
+    ask_for(&mut app, item);
+    fetch(&mut app, item);
+    assert_eq!(carried_item(&mut app), Some(item));
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+    assert_eq!(app.resource_mut::<Score>().deliveries, 1);
+}
+
+/// Returns the freshness of the item carried by Didi.
+fn carried_freshness(app: &mut TestApp) -> Option<Freshness> {
+    let world = app.world();
+    world
+        .query_filtered::<&Freshness, With<CarriedItem>>()
+        .iter(world)
+        .next()
+        .copied()
+}
+
+#[test]
+fn test_items_spoil_into_trash() {
+    let mut app = TestApp::with_setup(SEED, |app| {
+        app.insert_resource(FreshnessConfig {
+            lifetimes: Vec::new(),
+            default_lifetime: 2.0,
+            ..FreshnessConfig::default()
+        });
+    });
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    assert!(!carried_freshness(&mut app).unwrap().is_stale());
+
+    app.run_seconds(0.8);
+    assert!(carried_freshness(&mut app).unwrap().is_stale());
+    assert_eq!(carried_item(&mut app), Some(Item::WaterGlass));
+
+    app.run_seconds(1.0);
+    assert_eq!(carried_item(&mut app), Some(Item::Trash));
+
+    // Without a free slot to store it, the trash can't be put back, it must
+    // be thrown away.
+    let didi = app.didi();
+    app.world().get_mut::<Inventory>(didi).unwrap().capacity = 1;
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::Trash));
+    walk_to(&mut app, near_trash_bin());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+    assert!(dropped_items(&mut app).is_empty());
+}
+
+/// Returns the positions of the messes.
+fn mess_positions(app: &mut TestApp) -> Vec<Vec3> {
+    let world = app.world();
+    world
+        .query_filtered::<&Position, With<Mess>>()
+        .iter(world)
+        .map(|position| position.0)
+        .collect()
+}
+
+/// Builds a test app where a single mess appears next to Baobei.
+fn app_with_a_mess() -> TestApp {
+    let mut app = TestApp::with_setup(SEED, |app| {
+        app.insert_resource(ChoreConfig {
+            interval: 1.0,
+            near_dropped_item_chance: 0.0,
+            ..ChoreConfig::default()
+        });
+    });
+    app.run_seconds(1.1);
+    app.resource_mut::<ChoreConfig>().interval = 100.0;
+    app
+}
+
+#[test]
+fn test_hold_to_clean_mess() {
+    let mut app = app_with_a_mess();
+    let messes = mess_positions(&mut app);
+    assert_eq!(messes.len(), 1);
+    let baobei = app.baobei();
+    let modifiers = app.get::<Modifiers>(baobei).unwrap();
+    assert!(modifiers.contains(ModifierKind::Messes));
+
+    // A short press is not enough to clean the mess.
+    walk_to(&mut app, messes[0] + Vec3::new(0.0, 10.0, 0.0));
+    interact(&mut app);
+    assert_eq!(mess_positions(&mut app).len(), 1);
+
+    app.press(KeyCode::Space);
+    app.run_seconds(1.9);
+    app.release(KeyCode::Space);
+    app.run_frames(1);
+    assert!(mess_positions(&mut app).is_empty());
+    let modifiers = app.get::<Modifiers>(baobei).unwrap();
+    assert!(!modifiers.contains(ModifierKind::Messes));
+}
+
+#[test]
+fn test_hold_cleans_only_the_targeted_mess() {
+    let mut app = TestApp::new();
+    // A mess in front of the fridge, where taking an ice cream wins.
+    let mess = app
+        .world()
+        .spawn()
+        .insert(Mess {
+            kind: MessKind::Crumbs,
+            cleaning: 0.0,
+        })
+        .insert(Position(
+            near_producer(Item::IceCream) + Vec3::new(30.0, -40.0, 0.0),
+        ))
+        .insert(TriggerArea::new(80.0, 60.0))
+        .id();
+    walk_to(&mut app, near_producer(Item::IceCream));
+    let didi = app.didi();
+    let world = app.world();
+    assert!(world
+        .query::<&Contact>()
+        .iter(world)
+        .any(|contact| contact.0 == didi && contact.1 == mess));
+
+    hold_interact(&mut app, 2.5);
+    assert_eq!(mess_positions(&mut app).len(), 1);
+}
+
+#[test]
+fn test_clean_mess_with_sponge() {
+    let mut app = app_with_a_mess();
+    let messes = mess_positions(&mut app);
+    walk_to(&mut app, near_producer(Item::Sponge));
+    interact(&mut app);
+
+    // Left of the mess, out of the reach of Baobei.
+    walk_to(&mut app, messes[0] + Vec3::new(-30.0, 10.0, 0.0));
+    interact(&mut app);
+    assert!(mess_positions(&mut app).is_empty());
+    assert_eq!(carried_item(&mut app), Some(Item::Sponge));
+}
+
+/// Returns the number of items left in the producer of the item.
+fn stock_left(app: &mut TestApp, item: Item) -> usize {
+    let world = app.world();
+    world
+        .query::<(&ItemProducer, &Stock)>()
+        .iter(world)
+        .find(|(producer, _)| producer.0 == item)
+        .map(|(_, stock)| stock.count)
+        .unwrap()
+}
+
+/// Returns where to drop the item of the given index, apart from the others
+/// so that the next one is not picked up.
+fn drop_spot(index: usize) -> Vec3 {
+    middle_of_the_room() - Vec3::new(150.0 * index as f32, 0.0, 0.0)
+}
+
+/// Takes the three ice creams of the fridge and drops them.
+fn empty_fridge(app: &mut TestApp) {
+    for index in 0..3 {
+        walk_to(app, near_producer(Item::IceCream));
+        interact(app);
+        walk_to(app, drop_spot(index));
+        interact(app);
+    }
+}
+
+#[test]
+fn test_restock_empty_producer() {
+    let mut app = TestApp::new();
+    empty_fridge(&mut app);
+    assert_eq!(stock_left(&mut app, Item::IceCream), 0);
+
+    // Nothing to take until the fridge is restocked.
+    walk_to(&mut app, near_producer(Item::IceCream));
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+
+    // Holding interact restocks the fridge, a tap takes an ice cream.
+    hold_interact(&mut app, 2.4);
+    assert_eq!(carried_item(&mut app), None);
+    assert_eq!(stock_left(&mut app, Item::IceCream), 3);
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::IceCream));
+    assert_eq!(stock_left(&mut app, Item::IceCream), 2);
+}
+
+#[test]
+fn test_restock_with_empty_hands() {
+    let mut app = TestApp::new();
+    empty_fridge(&mut app);
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+
+    // Didi keeps the chips in hand instead of restocking the fridge.
+    walk_to(&mut app, near_producer(Item::IceCream));
+    assert_eq!(shown_prompt(&mut app).as_deref(), Some("Space: Keep Chips"));
+    hold_interact(&mut app, 2.4);
+    assert_eq!(carried_item(&mut app), Some(Item::Chips));
+    assert_eq!(stock_left(&mut app, Item::IceCream), 0);
+}
+
+/// Returns the freshness of the items on the table.
+fn freshness_on_table(app: &mut TestApp) -> Vec<f32> {
+    let world = app.world();
+    world
+        .query_filtered::<&Freshness, With<OnTable>>()
+        .iter(world)
+        .map(|freshness| freshness.0)
+        .collect()
+}
+
+#[test]
+fn test_leave_item_on_table() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, near_producer(Item::IceCream));
+    interact(&mut app);
+    walk_to(&mut app, near_table());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+    assert!(dropped_items(&mut app).is_empty());
+
+    // Items on the table spoil as slowly as carried ones.
+    app.run_seconds(9.0);
+    let freshness = freshness_on_table(&mut app);
+    assert_eq!(freshness.len(), 1);
+    assert!(freshness[0] > 0.7);
+
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::IceCream));
+    assert!(freshness_on_table(&mut app).is_empty());
+}
+
+#[test]
+fn test_wash_dirty_glass() {
+    let mut app = TestApp::new();
+    let baobei = app.baobei();
+    app.world().get_mut::<AskingItem>(baobei).unwrap().0 = Item::WaterGlass;
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    assert_eq!(stock_left(&mut app, Item::WaterGlass), 3);
+
+    // Baobei leaves the glass once drunk.
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+    let (_, glass_position) = dropped_items(&mut app)
+        .into_iter()
+        .find(|(item, _)| *item == Item::DirtyGlass)
+        .unwrap();
+    // Next to the glass, as an area fully containing Didi is not in contact.
+    walk_to(&mut app, glass_position - Vec3::new(50.0, 0.0, 0.0));
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), Some(Item::DirtyGlass));
+
+    walk_to(&mut app, near_sink());
+    interact(&mut app);
+    assert_eq!(carried_item(&mut app), None);
+    assert_eq!(stock_left(&mut app, Item::WaterGlass), 4);
+}
+
+#[test]
+fn test_dirty_glasses_side_by_side() {
+    let mut app = TestApp::new();
+    let baobei = app.baobei();
+    for _ in 0..6 {
+        app.world().get_mut::<AskingItem>(baobei).unwrap().0 = Item::WaterGlass;
+        walk_to(&mut app, near_producer(Item::WaterGlass));
+        interact(&mut app);
+        walk_to(&mut app, near_baobei());
+        interact(&mut app);
+    }
+
+    let glass_positions: Vec<Vec3> = dropped_items(&mut app)
+        .into_iter()
+        .filter(|(item, _)| *item == Item::DirtyGlass)
+        .map(|(_, position)| position)
+        .collect();
+    assert_eq!(glass_positions.len(), 4);
+    for (index, position) in glass_positions.iter().enumerate() {
+        assert!(glass_positions[..index]
+            .iter()
+            .all(|other| other.distance(*position) > 50.0));
+    }
+}
+
+#[test]
+fn test_baobei_naps_on_couch() {
+    let mut app = TestApp::new();
+    let baobei = app.baobei();
+    app.world().get_mut::<Rest>(baobei).unwrap().0 = 0.2;
+    app.run_frames(1);
+    let tired = |app: &mut TestApp| {
+        app.get::<Modifiers>(baobei)
+            .unwrap()
+            .contains(ModifierKind::Tired)
+    };
+    assert!(tired(&mut app));
+
+    walk_to(&mut app, near_couch());
+    interact(&mut app);
+    assert!(app.get::<Napping>(baobei).is_some());
+    assert_eq!(app.get::<Animation>(baobei).unwrap().clip(), "sleep");
+
+    app.run_seconds(5.0);
+    assert!(app.get::<Napping>(baobei).is_none());
+    assert!(!tired(&mut app));
+    assert_eq!(app.get::<Animation>(baobei).unwrap().clip(), "happy");
+}
+
+#[test]
+fn test_rested_baobei_refuses_to_nap() {
+    let mut app = TestApp::new();
+    let baobei = app.baobei();
+    app.world().get_mut::<Rest>(baobei).unwrap().0 = 0.5;
+
+    walk_to(&mut app, near_couch());
+    assert_eq!(shown_prompt(&mut app), None);
+    interact(&mut app);
+    assert!(app.get::<Napping>(baobei).is_none());
+}
+
+/// Waits for the prompt to be updated and returns the shown one, if any.
+fn shown_prompt(app: &mut TestApp) -> Option<String> {
+    app.run_frames(1);
+    let world = app.world();
+    world
+        .query_filtered::<(&Text, &Visible), With<InteractionPrompt>>()
+        .iter(world)
+        .find(|(_, visible)| visible.is_visible)
+        .map(|(text, _)| text.sections[0].value.clone())
+}
+
+/// Returns true if the target of the interaction is highlighted.
+fn is_highlighted(app: &mut TestApp) -> bool {
+    let world = app.world();
+    world
+        .query_filtered::<&Visible, With<TargetHighlight>>()
+        .iter(world)
+        .any(|visible| visible.is_visible)
+}
+
+#[test]
+fn test_interaction_prompts() {
+    let mut app = TestApp::new();
+    walk_to(&mut app, middle_of_the_room());
+    assert_eq!(shown_prompt(&mut app), None);
+    assert!(!is_highlighted(&mut app));
+
+    walk_to(&mut app, near_producer(Item::IceCream));
+    assert_eq!(
+        shown_prompt(&mut app).as_deref(),
+        Some("Space: Take Ice Cream")
+    );
+    assert!(is_highlighted(&mut app));
+
+    interact(&mut app);
+    assert_eq!(
+        shown_prompt(&mut app).as_deref(),
+        Some("Space: Put away Ice Cream")
+    );
+
+    walk_to(&mut app, near_baobei());
+    assert_eq!(
+        shown_prompt(&mut app).as_deref(),
+        Some("Space: Give Ice Cream")
+    );
+
+    // Dropping has no target to highlight.
+    walk_to(&mut app, middle_of_the_room());
+    assert_eq!(
+        shown_prompt(&mut app).as_deref(),
+        Some("Space: Drop Ice Cream")
+    );
+    assert!(!is_highlighted(&mut app));
+}
+
+#[test]
+fn test_hold_prompts() {
+    let mut app = app_with_a_mess();
+    let mess = mess_positions(&mut app)[0];
+    walk_to(&mut app, mess + Vec3::new(0.0, 10.0, 0.0));
+    assert_eq!(shown_prompt(&mut app).as_deref(), Some("Hold Space: Clean"));
+
+    empty_fridge(&mut app);
+    walk_to(&mut app, near_producer(Item::IceCream));
+    assert_eq!(
+        shown_prompt(&mut app).as_deref(),
+        Some("Hold Space: Restock")
+    );
+
+    // The prompt follows the language and the device of the player.
+    app.resource_mut::<Settings>().language = Language::French;
+    *app.resource_mut::<InputDevice>() = InputDevice::Gamepad;
+    app.run_frames(1);
+    assert_eq!(
+        shown_prompt(&mut app).as_deref(),
+        Some("Maintenir A: Remplir")
+    );
+}
+
+/// Returns the sounds played since the last call.
+fn played_sounds(app: &mut TestApp) -> Vec<Sound> {
+    app.resource_mut::<PlayedSounds>().0.drain(..).collect()
+}
+
+#[test]
+fn test_gameplay_sounds() {
+    let mut app = TestApp::new();
+    let item = other_item(asked_item(&mut app));
+    walk_to(&mut app, near_producer(item));
+    interact(&mut app);
+    assert_eq!(played_sounds(&mut app), vec![Sound::Producer, Sound::Take]);
+
+    walk_to(&mut app, near_baobei());
+    interact(&mut app);
+    assert_eq!(played_sounds(&mut app), vec![Sound::GiveWrong]);
+
+    // The penalty leaves Baobei happy, the decrease over time makes it cry.
+    assert!(happiness(&mut app) > 0.8);
+    app.run_seconds(2.5);
+    assert!(played_sounds(&mut app).contains(&Sound::Crying));
+}
+
+#[test]
+fn test_empty_producer_sound() {
+    let mut app = TestApp::new();
+    // The fridge holds three ice creams.
+    for taken in 1..=3 {
+        walk_to(&mut app, near_producer(Item::IceCream));
+        interact(&mut app);
+        assert_eq!(played_sounds(&mut app).contains(&Sound::Empty), taken == 3);
+        walk_to(&mut app, drop_spot(taken));
+        interact(&mut app);
+    }
+}
+
+/// Delivers the asked items and returns the sequence of asked items.
+fn deliver_asked_items(app: &mut TestApp, deliveries: usize) -> Vec<Item> {
+    let mut asked_items = vec![asked_item(app)];
+    for _ in 0..deliveries {
+        let item = asked_item(app);
+        fetch(app, item);
+        walk_to(app, near_baobei());
+        interact(app);
+        asked_items.push(asked_item(app));
+    }
+    asked_items
+}
+
+#[test]
+fn test_same_seed_same_requests() {
+    let asked_items = deliver_asked_items(&mut TestApp::with_seed(7), 5);
+
+    assert_eq!(
+        deliver_asked_items(&mut TestApp::with_seed(7), 5),
+        asked_items
+    );
+}
+
+#[test]
+fn test_happiness_decreases_over_time() {
+    let mut app = TestApp::new();
+    assert!((happiness(&mut app) - 1.0).abs() < 1e-4);
+
+    app.run_seconds(1.5);
+    assert!((happiness(&mut app) - 0.95).abs() < 1e-4);
+}
+
+/// Returns the positions of the items on the ground.
+fn dropped_items(app: &mut TestApp) -> Vec<(Item, Vec3)> {
+    let world = app.world();
+    world
+        .query_filtered::<(&Item, &Position), Without<OnTable>>()
+        .iter(world)
+        .map(|(item, position)| (*item, position.0))
+        .collect()
+}
+
+#[test]
+fn test_continue_saved_round() {
+    let save_file = env::temp_dir().join(format!("baobei-needs-{}.scn.ron", std::process::id()));
+    let with_save_file = |app: &mut AppBuilder| {
+        app.insert_resource(RoundSaveFile(save_file.clone()));
+    };
+
+    let mut app = TestApp::with_setup(SEED, with_save_file);
+    deliver_asked_items(&mut app, 1);
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+    walk_to(&mut app, middle_of_the_room());
+    interact(&mut app);
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    let baobei = app.baobei();
+    let buff = ModifierConfig::default().buff(Item::IceCream).unwrap();
+    app.world()
+        .entity_mut(baobei)
+        .insert(Napping)
+        .insert(Modifiers(vec![Modifier::from_buff(buff)]));
+    app.run_frames(1);
+
+    // Leaving the round saves it.
+    app.resource_mut::<State<GameState>>()
+        .set(GameState::Menu)
+        .unwrap();
+    app.run_frames(1);
+
+    let mut continued = TestApp::with_setup(SEED + 1, |app| {
+        with_save_file(app);
+        app.insert_resource(ContinueRound);
+    });
+    fs::remove_file(&save_file).unwrap();
+
+    let (didi, continued_didi) = (app.didi(), continued.didi());
+    assert_eq!(
+        continued.get::<Position>(continued_didi),
+        app.get::<Position>(didi)
+    );
+    assert_eq!(carried_item(&mut continued), Some(Item::WaterGlass));
+    assert_eq!(asked_item(&mut continued), asked_item(&mut app));
+    assert_eq!(dropped_items(&mut continued), dropped_items(&mut app));
+    // The startup frame of the continued round may have decreased it once.
+    assert!((happiness(&mut continued) - happiness(&mut app)).abs() < 0.06);
+    assert_eq!(continued.resource_mut::<Score>().deliveries, 1);
+    let continued_baobei = continued.baobei();
+    assert!(continued.get::<Napping>(continued_baobei).is_some());
+    assert!(continued
+        .get::<Modifiers>(continued_baobei)
+        .unwrap()
+        .contains(ModifierKind::Buff(Item::IceCream)));
+    // The round continues with its own random generator, not the new one.
+    assert_eq!(
+        continued.resource_mut::<GameRng>().seed(),
+        app.resource_mut::<GameRng>().seed()
+    );
+}
+
+/// Returns the ingredients deposited in the workstation.
+fn workstation_ingredients(app: &mut TestApp) -> Vec<Item> {
+    let world = app.world();
+    world
+        .query::<&Workstation>()
+        .iter(world)
+        .flat_map(|workstation| workstation.ingredients.iter().map(|(item, _)| *item))
+        .collect()
+}
+
+#[test]
+fn test_continue_saved_round_contents() {
+    let save_file = env::temp_dir().join(format!(
+        "baobei-needs-contents-{}.scn.ron",
+        std::process::id()
+    ));
+    let with_save_file = |app: &mut AppBuilder| {
+        app.insert_resource(RoundSaveFile(save_file.clone()));
+    };
+
+    let mut app = TestApp::with_setup(SEED, with_save_file);
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+    walk_to(&mut app, near_table());
+    interact(&mut app);
+    walk_to(&mut app, near_producer(Item::Bread));
+    interact(&mut app);
+    walk_to(&mut app, near_workstation());
+    interact(&mut app);
+    walk_to(&mut app, near_producer(Item::IceCream));
+    interact(&mut app);
+    walk_to(&mut app, near_producer(Item::WaterGlass));
+    interact(&mut app);
+    app.world()
+        .spawn()
+        .insert(Mess {
+            kind: MessKind::Crumbs,
+            cleaning: 0.0,
+        })
+        .insert(Position(Vec3::new(640.0, 100.0, 0.0)))
+        .insert(TriggerArea::new(80.0, 60.0));
+    let baobei = app.baobei();
+    app.world().get_mut::<Rest>(baobei).unwrap().0 = 0.5;
+    app.run_seconds(3.0);
+
+    app.resource_mut::<State<GameState>>()
+        .set(GameState::Menu)
+        .unwrap();
+    app.run_frames(1);
+
+    let mut continued = TestApp::with_setup(SEED + 1, |app| {
+        with_save_file(app);
+        app.insert_resource(ContinueRound);
+    });
+    fs::remove_file(&save_file).unwrap();
+
+    assert_eq!(carried_item(&mut continued), Some(Item::WaterGlass));
+    assert_eq!(stored_items(&mut continued), vec![Item::IceCream]);
+    assert_eq!(freshness_on_table(&mut continued).len(), 1);
+    let (saved, continued_freshness) = (
+        freshness_on_table(&mut app)[0],
+        freshness_on_table(&mut continued)[0],
+    );
+    assert!(saved < 1.0);
+    assert!((continued_freshness - saved).abs() < 0.01);
+    assert_eq!(workstation_ingredients(&mut continued), vec![Item::Bread]);
+    assert_eq!(stock_left(&mut app, Item::IceCream), 2);
+    assert_eq!(stock_left(&mut continued, Item::IceCream), 2);
+    assert_eq!(mess_positions(&mut continued), mess_positions(&mut app));
+    let (baobei, continued_baobei) = (app.baobei(), continued.baobei());
+    let (saved, continued_rest) = (
+        app.get::<Rest>(baobei).unwrap().0,
+        continued.get::<Rest>(continued_baobei).unwrap().0,
+    );
+    assert!(saved < 0.5);
+    assert!((continued_rest - saved).abs() < 0.01);
+}
+
+/// Goes back to the menu, then plays again.
+fn play_again(app: &mut TestApp) {
+    for state in &[GameState::Menu, GameState::InGame] {
+        app.resource_mut::<State<GameState>>()
+            .set(state.clone())
+            .unwrap();
+        app.run_frames(1);
+    }
+}
+
+#[test]
+fn test_play_after_leaving_starts_a_new_round() {
+    let mut app = TestApp::new();
+    deliver_asked_items(&mut app, 1);
+    walk_to(&mut app, near_producer(Item::Chips));
+    interact(&mut app);
+
+    play_again(&mut app);
+    assert_eq!(app.resource_mut::<Score>().deliveries, 0);
+    assert_eq!(carried_item(&mut app), None);
+    let didi = app.didi();
+    assert_eq!(app.get::<Position>(didi), Some(&didi_start_position()));
+}
+
+#[test]
+fn test_round_starts_without_time_left() {
+    let mut app = TestApp::new();
+    app.resource_mut::<State<GameState>>()
+        .set(GameState::Menu)
+        .unwrap();
+    app.run_frame(STEP / 2);
+
+    app.resource_mut::<State<GameState>>()
+        .set(GameState::InGame)
+        .unwrap();
+    app.run_frame(Duration::default());
+    assert!(app.resource_mut::<GameClock>().overstep() < 1e-6);
+}
+
+#[test]
+fn test_round_ends_when_baobei_is_sad() {
+    let mut app = TestApp::new();
+    let first_asked_item = asked_item(&mut app);
+    deliver_asked_items(&mut app, 1);
+    let baobei = app.baobei();
+    app.world().get_mut::<Happiness>(baobei).unwrap().sub(0.99);
+
+    app.run_seconds(1.0);
+    assert_eq!(
+        app.resource_mut::<State<GameState>>().current(),
+        &GameState::RoundOver
+    );
+    assert_eq!(app.resource_mut::<Score>().deliveries, 1);
+
+    // The next round starts from scratch.
+    play_again(&mut app);
+    assert!((happiness(&mut app) - 1.0).abs() < 1e-4);
+    assert_eq!(app.resource_mut::<Score>().deliveries, 0);
+    assert_eq!(carried_item(&mut app), None);
+    // The round has its own seed, which reproduces it.
+    let round_seed = app.resource_mut::<GameRng>().seed();
+    assert_ne!(round_seed, SEED);
+    assert_eq!(
+        asked_item(&mut app),
+        asked_item(&mut TestApp::with_seed(round_seed))
+    );
+    assert_eq!(asked_item(&mut TestApp::with_seed(SEED)), first_asked_item);
+}
+
+/// Returns the volume of the music track, faded in or out.
+fn music_volume(app: &mut TestApp, music: Music) -> f32 {
+    let settings = app.resource_mut::<Settings>().clone();
+    app.resource_mut::<Mixer>().music_volume(music, &settings)
+}
+
+#[test]
+fn test_music_crossfades_between_states() {
+    let mut app = TestApp::new();
+    let volume = app.resource_mut::<Settings>().music_volume;
+    assert_eq!(app.resource_mut::<Mixer>().music(), Some(Music::Game));
+    app.run_seconds(2.0);
+    assert!((music_volume(&mut app, Music::Game) - volume).abs() < 1e-4);
+
+    // Leaving the round fades the game track out and the menu track in.
+    app.resource_mut::<State<GameState>>()
+        .set(GameState::Menu)
+        .unwrap();
+    app.run_seconds(1.0);
+    assert_eq!(app.resource_mut::<Mixer>().music(), Some(Music::Menu));
+    let game_volume = music_volume(&mut app, Music::Game);
+    let menu_volume = music_volume(&mut app, Music::Menu);
+    assert!(game_volume > 0.0 && game_volume < volume);
+    assert!(menu_volume > 0.0 && menu_volume < volume);
+
+    app.run_seconds(1.5);
+    assert!(music_volume(&mut app, Music::Game).abs() < 1e-4);
+    assert!((music_volume(&mut app, Music::Menu) - volume).abs() < 1e-4);
+}
+
+/// Builds a test app with the given difficulty, already configured when
+/// Didi is spawned.
+fn app_with_difficulty(difficulty: Difficulty) -> TestApp {
+    TestApp::with_setup(SEED, |app| {
+        let settings = Settings {
+            difficulty,
+            ..Settings::default()
+        };
+        app.insert_resource(DifficultyConfig::new(
+            settings.difficulty,
+            settings.difficulty_ramp,
+        ))
+        .insert_resource(settings);
+    })
+}
+
+#[test]
+fn test_difficulty_presets() {
+    let mut easy = app_with_difficulty(Difficulty::Easy);
+    let mut hard = app_with_difficulty(Difficulty::Hard);
+    easy.run_seconds(1.5);
+    hard.run_seconds(1.5);
+
+    assert!((happiness(&mut easy) - 0.98).abs() < 1e-4);
+    assert!((happiness(&mut hard) - 0.92).abs() < 1e-4);
+}
+
+#[test]
+fn test_inventory_slots_change_at_round_start() {
+    let mut app = app_with_difficulty(Difficulty::Easy);
+    for &item in &[Item::Chips, Item::WaterGlass, Item::IceCream] {
+        walk_to(&mut app, near_producer(item));
+        interact(&mut app);
+    }
+    assert_eq!(carried_item(&mut app), Some(Item::IceCream));
+    assert_eq!(stored_items(&mut app).len(), 2);
+
+    // Fewer slots in the settings don't lose the stored items in the round.
+    app.resource_mut::<Settings>().difficulty = Difficulty::Normal;
+    app.run_seconds(0.5);
+    let didi = app.didi();
+    assert_eq!(app.get::<Inventory>(didi).unwrap().capacity, 3);
+    assert_eq!(stored_items(&mut app).len(), 2);
+
+    // The next round has the slots of the difficulty and the upgrades.
+    app.resource_mut::<InventoryUpgrades>().extra_slots = 1;
+    play_again(&mut app);
+    assert_eq!(app.get::<Inventory>(didi).unwrap().capacity, 3);
+    app.resource_mut::<InventoryUpgrades>().extra_slots = 0;
+    play_again(&mut app);
+    assert_eq!(app.get::<Inventory>(didi).unwrap().capacity, 2);
+}
+
+#[test]
+fn test_baobei_changes_its_mind() {
+    let mut app = app_with_difficulty(Difficulty::Easy);
+    let item = asked_item(&mut app);
+
+    app.run_seconds(29.0);
+    assert_eq!(asked_item(&mut app), item);
+    let patient_happiness = happiness(&mut app);
+
+    // Losing patience makes Baobei sadder.
+    app.run_seconds(1.5);
+    assert_ne!(asked_item(&mut app), item);
+    assert!(patient_happiness - happiness(&mut app) > 0.1);
+    assert!(Item::pool(2).contains(&asked_item(&mut app)));
+}
+
+#[test]
+fn test_request_cooldown_finishes_with_an_event() {
+    let mut app = app_with_difficulty(Difficulty::Easy);
+    let baobei = app.baobei();
+    let item = asked_item(&mut app);
+
+    let mut frames: u16 = 0;
+    while asked_item(&mut app) == item {
+        assert!(frames < 1600, "Baobei never changes its mind");
+        app.run_frames(1);
+        frames += 1;
+    }
+    assert!((f32::from(frames) * 0.02 - 30.0).abs() < 0.1);
+
+    // The patience of Baobei is its cooldown, which finished and started over.
+    let world = app.world();
+    let events = world
+        .get_resource::<Events<CooldownFinishedEvent>>()
+        .unwrap();
+    assert!(events
+        .get_reader()
+        .iter(events)
+        .any(|event| *event == CooldownFinishedEvent::Component(baobei)));
+    assert!(app.get::<Cooldown>(baobei).unwrap().elapsed() < 0.1);
+}
+
+#[test]
+fn test_happiness_modifiers() {
+    let mut app = TestApp::new();
+    let baobei = app.baobei();
+    app.world().get_mut::<AskingItem>(baobei).unwrap().0 = Item::IceCream;
+    deliver_asked_items(&mut app, 1);
+
+    let modifiers = app.get::<Modifiers>(baobei).unwrap();
+    assert!(modifiers.contains(ModifierKind::Buff(Item::IceCream)));
+
+    // The buff slows the decay, then Baobei gets tired of waiting.
+    let happiness_before = happiness(&mut app);
+    app.run_seconds(2.0);
+    let buffed_decrease = happiness_before - happiness(&mut app);
+    assert!(buffed_decrease > 0.0 && buffed_decrease < 0.06);
+
+    app.run_seconds(14.0);
+    let modifiers = app.get::<Modifiers>(baobei).unwrap();
+    assert!(!modifiers.contains(ModifierKind::Buff(Item::IceCream)));
+    assert!(modifiers.contains(ModifierKind::WaitingTooLong));
+}
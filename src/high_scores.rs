@@ -0,0 +1,236 @@
+//! Best scores of the rounds, persisted in the user data directory.
+
+use std::{
+    cmp::Ordering,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{difficulty::Difficulty, storage};
+
+/// Name of the high scores file in the user data directory.
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+
+/// Number of scores kept for each level and difficulty.
+pub const MAX_ENTRIES: usize = 10;
+
+/// Plugin loading the high scores.
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(HighScores::load());
+    }
+}
+
+/// Score of a finished round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    /// Name entered by the player.
+    pub name: String,
+    /// Name of the level played.
+    pub level: String,
+    /// Difficulty of the round.
+    pub difficulty: Difficulty,
+    /// Day of the round, in days since the Unix epoch.
+    pub date: u64,
+    /// Time spent in the round, in seconds.
+    pub time_survived: f32,
+    /// Number of items given to Baobei as asked.
+    pub deliveries: u32,
+    /// Seed of the round, to replay it. Missing in the older scores.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl HighScore {
+    /// Orders the scores from the best to the worst: the most deliveries,
+    /// then the longest time survived.
+    fn rank(&self, other: &Self) -> Ordering {
+        other.deliveries.cmp(&self.deliveries).then_with(|| {
+            other
+                .time_survived
+                .partial_cmp(&self.time_survived)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// Returns true if the score belongs to the table of the level and difficulty.
+    fn is_in_table(&self, level: &str, difficulty: Difficulty) -> bool {
+        self.level == level && self.difficulty == difficulty
+    }
+}
+
+/// Best scores of all levels and difficulties, from the best to the worst.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    /// Scores kept, at most `MAX_ENTRIES` per level and difficulty.
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Loads the high scores file, or returns no scores if it is missing or
+    /// invalid.
+    pub fn load() -> Self {
+        storage::data_file(HIGH_SCORES_FILE)
+            .and_then(|path| storage::load(&path))
+            .unwrap_or_default()
+    }
+
+    /// Saves the high scores in the high scores file.
+    pub fn save(&self) {
+        let path = match storage::data_file(HIGH_SCORES_FILE) {
+            Some(path) => path,
+            None => {
+                warn!("No data directory to save the high scores");
+                return;
+            }
+        };
+
+        match storage::save(&path, self) {
+            Ok(()) => info!("High scores saved in {}", path.display()),
+            Err(error) => error!("Fail to save the high scores: {}", error),
+        }
+    }
+
+    /// Returns the best scores of the level and difficulty.
+    pub fn table<'a>(
+        &'a self,
+        level: &'a str,
+        difficulty: Difficulty,
+    ) -> impl Iterator<Item = &'a HighScore> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.is_in_table(level, difficulty))
+    }
+
+    /// Returns true if the score would enter the table of its level and difficulty.
+    pub fn qualifies(&self, score: &HighScore) -> bool {
+        let table: Vec<_> = self.table(&score.level, score.difficulty).collect();
+
+        table.len() < MAX_ENTRIES
+            || table
+                .last()
+                .map_or(true, |last| score.rank(last) == Ordering::Less)
+    }
+
+    /// Adds the score, removing the worst one of its table if it is full.
+    pub fn insert(&mut self, score: HighScore) {
+        let (level, difficulty) = (score.level.clone(), score.difficulty);
+
+        self.entries.push(score);
+        self.entries.sort_by(HighScore::rank);
+
+        let mut kept = 0;
+        self.entries.retain(|entry| {
+            if !entry.is_in_table(&level, difficulty) {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_ENTRIES
+        });
+    }
+}
+
+/// Returns the current day, in days since the Unix epoch. It is also the seed
+/// of the daily rounds.
+pub fn today() -> u64 {
+    /// Number of seconds in a day.
+    const DAY: u64 = 24 * 60 * 60;
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs() / DAY)
+        .unwrap_or_default()
+}
+
+/// Formats the day given in days since the Unix epoch as `YYYY-MM-DD`.
+pub fn format_date(days: u64) -> String {
+    // Conversion to the civil calendar, from http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_date, HighScore, HighScores, MAX_ENTRIES};
+    use crate::difficulty::Difficulty;
+
+    fn score(deliveries: u32, time_survived: f32, difficulty: Difficulty) -> HighScore {
+        HighScore {
+            name: "Didi".to_string(),
+            level: "Living room".to_string(),
+            difficulty,
+            date: 19_000,
+            time_survived,
+            deliveries,
+            seed: Some(42),
+        }
+    }
+
+    fn table(high_scores: &HighScores, difficulty: Difficulty) -> Vec<(u32, f32)> {
+        high_scores
+            .table("Living room", difficulty)
+            .map(|entry| (entry.deliveries, entry.time_survived))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_keeps_best_scores() {
+        let mut high_scores = HighScores::default();
+        high_scores.insert(score(3, 50.0, Difficulty::Normal));
+        high_scores.insert(score(5, 40.0, Difficulty::Normal));
+        high_scores.insert(score(3, 60.0, Difficulty::Normal));
+        high_scores.insert(score(1, 10.0, Difficulty::Hard));
+
+        assert_eq!(
+            table(&high_scores, Difficulty::Normal),
+            vec![(5, 40.0), (3, 60.0), (3, 50.0)]
+        );
+        assert_eq!(table(&high_scores, Difficulty::Hard), vec![(1, 10.0)]);
+
+        for deliveries in 10..20 {
+            high_scores.insert(score(deliveries, 100.0, Difficulty::Normal));
+        }
+        let normal_table = table(&high_scores, Difficulty::Normal);
+        assert_eq!(normal_table.len(), MAX_ENTRIES);
+        assert_eq!(normal_table.last(), Some(&(10, 100.0)));
+        assert!(!high_scores.qualifies(&score(9, 100.0, Difficulty::Normal)));
+        assert!(high_scores.qualifies(&score(9, 100.0, Difficulty::Easy)));
+        assert_eq!(table(&high_scores, Difficulty::Hard), vec![(1, 10.0)]);
+    }
+
+    #[test]
+    fn test_invalid_high_scores_file() {
+        assert!(ron::from_str::<HighScores>("corrupted").is_err());
+        assert_eq!(
+            ron::from_str::<HighScores>("()").unwrap(),
+            HighScores::default()
+        );
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(11_016), "2000-02-29");
+        assert_eq!(format_date(19_000), "2022-01-08");
+    }
+}
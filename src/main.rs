@@ -15,45 +15,108 @@
     clippy::module_name_repetitions
 )]
 
+mod animation;
+mod audio;
+mod cli;
+mod clock;
 mod collisions;
 mod constants;
 mod controllers;
 mod cooldown;
+mod difficulty;
 mod drawing;
 mod gameplay;
+mod high_scores;
 mod menu;
+mod replay;
+mod rng;
 mod scenes;
+mod settings;
+mod storage;
+#[cfg(test)]
+mod testing;
 
 use bevy::prelude::*;
 
+use animation::AnimationPlugin;
+use audio::{RodioAudioBackendPlugin, SoundPlugin};
+use cli::Args;
+use clock::{ClockPlugin, GameClock};
 use collisions::CollisionPlugin;
-use constants::{GameState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use constants::GameState;
 use controllers::ControllerPlugin;
+use cooldown::CooldownPlugin;
 use drawing::DrawingPlugin;
 use gameplay::GameplayPlugin;
+use high_scores::HighScoresPlugin;
 use menu::MenuPlugin;
+use replay::{Replay, ReplayPlugin};
+use rng::RngPlugin;
 use scenes::SceneLoaderPlugin;
+use settings::{Settings, SettingsPlugin};
 
 fn main() {
-    App::build()
-        .insert_resource(bevy::log::LogSettings {
-            level: bevy::log::Level::INFO,
-            filter: "wgpu=error,bevy_ecs=info".to_string(),
-        })
-        .insert_resource(WindowDescriptor {
-            title: "Baobei needs".to_string(),
-            width: WINDOW_WIDTH,
-            height: WINDOW_HEIGHT,
-            resizable: false,
-            ..WindowDescriptor::default()
-        })
-        .add_state(GameState::Menu)
-        .add_plugins(DefaultPlugins)
-        .add_plugin(ControllerPlugin)
-        .add_plugin(CollisionPlugin)
-        .add_plugin(SceneLoaderPlugin)
-        .add_plugin(MenuPlugin)
-        .add_plugin(GameplayPlugin)
-        .add_plugin(DrawingPlugin)
-        .run();
+    let args = Args::from_env();
+    let (replay, replay_error) = match args.replay.as_ref().map(|path| Replay::load(path)) {
+        Some(Ok(replay)) => (Some(replay), None),
+        Some(Err(error)) => (None, Some(error)),
+        None => (None, None),
+    };
+
+    let (settings, settings_error) = match Settings::load() {
+        Ok(settings) => (settings, None),
+        Err(error) => (Settings::default(), Some(error)),
+    };
+
+    // A replay starts directly in game with the recorded seed.
+    let (seed, initial_state) = match &replay {
+        Some(replay) => (replay.seed, GameState::InGame),
+        None => (args.round_seed(settings.seed), GameState::Menu),
+    };
+
+    let mut app = App::build();
+    app.insert_resource(bevy::log::LogSettings {
+        level: bevy::log::Level::INFO,
+        filter: "wgpu=error,bevy_ecs=info".to_string(),
+    })
+    .insert_resource(settings.window_descriptor())
+    .insert_resource(settings)
+    .insert_resource(ClearColor(Color::BLACK))
+    .insert_resource(GameClock::with_time_scale(args.speed.unwrap_or(1.0)))
+    .add_state(initial_state)
+    .add_plugins(DefaultPlugins)
+    .add_plugin(SettingsPlugin)
+    .add_plugin(HighScoresPlugin)
+    .add_plugin(AnimationPlugin)
+    .add_plugin(SoundPlugin)
+    .add_plugin(RodioAudioBackendPlugin)
+    .add_plugin(ClockPlugin)
+    .add_plugin(CooldownPlugin)
+    .add_plugin(RngPlugin { seed })
+    .add_plugin(ControllerPlugin)
+    .add_plugin(CollisionPlugin)
+    .add_plugin(SceneLoaderPlugin)
+    .add_plugin(MenuPlugin)
+    .add_plugin(GameplayPlugin)
+    .add_plugin(DrawingPlugin);
+
+    // The logs are set up by the default plugins.
+    args.warn_unknown();
+    if let Some(error) = settings_error {
+        warn!(
+            "Fail to load the settings, using the default ones: {}",
+            error
+        );
+    }
+    if let Some(error) = replay_error {
+        error!("Fail to load the replay, starting normally: {}", error);
+    }
+
+    if let Some(replay) = replay {
+        app.add_plugin(ReplayPlugin::Play(replay));
+    } else if let Some(path) = args.record {
+        app.add_plugin(ReplayPlugin::Record(path));
+    }
+
+    app.run();
 }
@@ -0,0 +1,148 @@
+//! Screen showing the best scores of the level.
+
+use bevy::prelude::*;
+
+use crate::{
+    constants::{GameState, LEVEL_NAME},
+    high_scores::{format_date, HighScores},
+    settings::Settings,
+};
+
+use super::{spawn_button, text_bundle, MenuMaterials, UpdatedButton};
+
+/// Plugin managing the high scores screen.
+pub struct HighScoresScreenPlugin;
+
+impl Plugin for HighScoresScreenPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::HighScores)
+                .with_system(setup_high_scores_screen.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::HighScores).with_system(back_button_system.system()),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::HighScores)
+                .with_system(cleanup_high_scores_screen.system()),
+        );
+    }
+}
+
+/// Stores entities of the high scores screen.
+struct HighScoresScreenData {
+    /// Entity wrapping all the entities of the screen
+    node_wrapper: Entity,
+}
+
+/// Button going back to the main menu.
+#[derive(Debug, Clone, Copy)]
+struct BackButton;
+
+/// The back button interacted by the player.
+type UpdatedBackButton = (UpdatedButton, With<BackButton>);
+
+/// Goes back to the main menu when the back button is clicked.
+fn back_button_system(
+    materials: Res<MenuMaterials>,
+    mut state: ResMut<State<GameState>>,
+    mut interaction_query: Query<(&Interaction, &mut Handle<ColorMaterial>), UpdatedBackButton>,
+) {
+    for (interaction, mut material) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => state.set(GameState::Menu).unwrap(),
+            Interaction::Hovered => *material = materials.hovered_button.clone(),
+            Interaction::None => *material = materials.normal_button.clone(),
+        }
+    }
+}
+
+/// Setup the table of the best scores for the current difficulty.
+fn setup_high_scores_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    settings: Res<Settings>,
+    high_scores: Res<HighScores>,
+) {
+    let font = asset_server.load("FiraSans-Bold.ttf");
+    let translate = |text| settings.language.translate(text);
+
+    let rows: Vec<String> = high_scores
+        .table(LEVEL_NAME, settings.difficulty)
+        .enumerate()
+        .map(|(index, entry)| {
+            format!(
+                "{}. {}   {} {}   {:.0}s   {}",
+                index + 1,
+                entry.name,
+                entry.deliveries,
+                translate("deliveries"),
+                entry.time_survived,
+                format_date(entry.date)
+            )
+        })
+        .collect();
+
+    let node_wrapper = commands
+        .spawn()
+        .insert_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                padding: Rect::all(Val::Px(30.0)),
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                ..Style::default()
+            },
+            material: materials.none.clone(),
+            ..NodeBundle::default()
+        })
+        .with_children(|parent| {
+            parent.spawn().insert_bundle(text_bundle(
+                translate("High scores").to_string(),
+                font.clone(),
+                80.0,
+            ));
+            parent.spawn().insert_bundle(text_bundle(
+                format!(
+                    "{} - {}",
+                    translate(LEVEL_NAME),
+                    translate(settings.difficulty.name())
+                ),
+                font.clone(),
+                40.0,
+            ));
+
+            if rows.is_empty() {
+                parent.spawn().insert_bundle(text_bundle(
+                    translate("No score yet").to_string(),
+                    font.clone(),
+                    30.0,
+                ));
+            }
+            for row in rows {
+                parent
+                    .spawn()
+                    .insert_bundle(text_bundle(row, font.clone(), 30.0));
+            }
+
+            spawn_button(
+                parent,
+                &materials,
+                font.clone(),
+                Size::new(Val::Px(250.0), Val::Px(65.0)),
+                translate("Back").to_string(),
+                BackButton,
+            );
+        })
+        .id();
+
+    commands.insert_resource(HighScoresScreenData { node_wrapper });
+}
+
+/// Removes all entities of the high scores screen.
+fn cleanup_high_scores_screen(mut commands: Commands, screen_data: Res<HighScoresScreenData>) {
+    commands
+        .entity(screen_data.node_wrapper)
+        .despawn_recursive();
+}
@@ -0,0 +1,233 @@
+//! Systems of the menu phase.
+
+use bevy::{ecs::component::Component, input::system::exit_on_esc_system, prelude::*};
+
+use crate::{
+    constants::GameState,
+    gameplay::{ContinueRound, RoundSaveFile},
+    settings::Settings,
+};
+
+use self::{
+    high_scores_screen::HighScoresScreenPlugin, round_over_screen::RoundOverScreenPlugin,
+    settings_screen::SettingsScreenPlugin,
+};
+
+mod high_scores_screen;
+mod round_over_screen;
+mod settings_screen;
+
+/// Plugin managing contact collisions
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<MenuMaterials>()
+            .add_startup_system(setup_ui_camera.system())
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(setup_menu.system()))
+            .add_system_set(
+                SystemSet::on_update(GameState::Menu)
+                    .with_system(button_system.system())
+                    .with_system(play_on_space_system.system())
+                    .with_system(exit_on_esc_system.system()),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Menu).with_system(cleanup_menu.system()))
+            .add_plugin(SettingsScreenPlugin)
+            .add_plugin(RoundOverScreenPlugin)
+            .add_plugin(HighScoresScreenPlugin);
+    }
+}
+
+/// Stores entities in the menu phase
+struct MenuData {
+    /// Entity wrapping all menu entities (title, buttons)
+    node_wrapper: Entity,
+}
+
+/// Colors of the button.
+struct MenuMaterials {
+    /// Transparent color
+    none: Handle<ColorMaterial>,
+    /// Default style of a button
+    normal_button: Handle<ColorMaterial>,
+    /// Hovered style of a button
+    hovered_button: Handle<ColorMaterial>,
+}
+
+impl FromWorld for MenuMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.get_resource_mut::<Assets<ColorMaterial>>().unwrap();
+
+        Self {
+            none: materials.add(Color::NONE.into()),
+            normal_button: materials.add(Color::rgb(0.15, 0.15, 0.15).into()),
+            hovered_button: materials.add(Color::rgb(0.25, 0.25, 0.25).into()),
+        }
+    }
+}
+
+/// A button interacted by the player.
+type UpdatedButton = (Changed<Interaction>, With<Button>);
+
+/// Buttons of the main menu.
+#[derive(Debug, Clone, Copy)]
+enum MenuButton {
+    /// Continues the saved round.
+    Continue,
+    /// Starts the game.
+    Play,
+    /// Opens the table of the best scores.
+    HighScores,
+    /// Opens the settings screen.
+    Settings,
+}
+
+/// Handles clicks on the buttons of the main menu.
+fn button_system(
+    mut commands: Commands,
+    materials: Res<MenuMaterials>,
+    mut interaction_query: Query<
+        (&Interaction, &MenuButton, &mut Handle<ColorMaterial>),
+        UpdatedButton,
+    >,
+    mut state: ResMut<State<GameState>>,
+) {
+    for (interaction, button, mut material) in interaction_query.iter_mut() {
+        match (*interaction, button) {
+            (Interaction::Clicked, MenuButton::Continue) => {
+                commands.insert_resource(ContinueRound);
+                state.set(GameState::InGame).unwrap();
+            }
+            (Interaction::Clicked, MenuButton::Play) => state.set(GameState::InGame).unwrap(),
+            (Interaction::Clicked, MenuButton::HighScores) => {
+                state.set(GameState::HighScores).unwrap();
+            }
+            (Interaction::Clicked, MenuButton::Settings) => {
+                state.set(GameState::Settings).unwrap();
+            }
+            (Interaction::Hovered, _) => *material = materials.hovered_button.clone(),
+            (Interaction::None, _) => *material = materials.normal_button.clone(),
+        }
+    }
+}
+
+/// Spawns a button with the given label, tagging the button and its text
+/// with the given component.
+fn spawn_button<T: Component + Clone>(
+    parent: &mut ChildBuilder,
+    materials: &MenuMaterials,
+    font: Handle<Font>,
+    size: Size<Val>,
+    label: String,
+    tag: T,
+) {
+    parent
+        .spawn()
+        .insert(tag.clone())
+        .insert_bundle(ButtonBundle {
+            style: Style {
+                margin: Rect::all(Val::Px(10.0)),
+                size,
+                justify_content: JustifyContent::Center, // horizontally center child text
+                align_items: AlignItems::Center,         // vertically center child text
+                ..Style::default()
+            },
+            material: materials.normal_button.clone(),
+            ..ButtonBundle::default()
+        })
+        .with_children(|parent| {
+            parent
+                .spawn()
+                .insert(tag)
+                .insert_bundle(text_bundle(label, font, 40.0));
+        });
+}
+
+/// Returns a white text with the given font and size.
+fn text_bundle(value: String, font: Handle<Font>, font_size: f32) -> TextBundle {
+    TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font,
+                font_size,
+                color: Color::WHITE,
+            },
+            TextAlignment::default(),
+        ),
+        ..TextBundle::default()
+    }
+}
+
+/// Spawn the camera of the user interface, shared by all the screens.
+fn setup_ui_camera(mut commands: Commands) {
+    commands.spawn().insert_bundle(UiCameraBundle::default());
+}
+
+/// Setup the title, `Play`, `High scores` and `Settings` buttons in the main menu, with a
+/// `Continue` button when a round is saved.
+fn setup_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    settings: Res<Settings>,
+    save_file: Option<Res<RoundSaveFile>>,
+) {
+    let saved_round = save_file.map_or(false, |save_file| save_file.0.exists());
+
+    let font = asset_server.load("FiraSans-Bold.ttf");
+
+    let node_wrapper = commands
+        .spawn()
+        .insert_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                padding: Rect::all(Val::Px(50.0)),
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                ..Style::default()
+            },
+            material: materials.none.clone(),
+            ..NodeBundle::default()
+        })
+        .with_children(|parent| {
+            parent.spawn().insert_bundle(text_bundle(
+                "Baobei needs".to_string(),
+                font.clone(),
+                125.0,
+            ));
+            let buttons = [
+                ("Continue", MenuButton::Continue),
+                ("Play", MenuButton::Play),
+                ("High scores", MenuButton::HighScores),
+                ("Settings", MenuButton::Settings),
+            ];
+            let skipped = usize::from(!saved_round);
+
+            for (label, button) in buttons.iter().skip(skipped) {
+                spawn_button(
+                    parent,
+                    &materials,
+                    font.clone(),
+                    Size::new(Val::Px(250.0), Val::Px(65.0)),
+                    settings.language.translate(label).to_string(),
+                    *button,
+                );
+            }
+        })
+        .id();
+
+    commands.insert_resource(MenuData { node_wrapper });
+}
+
+/// Removes all entities of the menu.
+fn cleanup_menu(mut commands: Commands, menu_data: Res<MenuData>) {
+    commands.entity(menu_data.node_wrapper).despawn_recursive();
+}
+
+/// Start the game play when the player press `Space`.
+fn play_on_space_system(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        state.set(GameState::InGame).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Screen showing the result of the round, asking the name of the player
+//! when it is a high score.
+
+use bevy::{prelude::*, window::ReceivedCharacter};
+
+use crate::{
+    constants::{GameState, LEVEL_NAME},
+    gameplay::Score,
+    high_scores::{today, HighScore, HighScores},
+    rng::GameRng,
+    settings::Settings,
+};
+
+use super::{text_bundle, MenuMaterials};
+
+/// Maximum number of characters of the name of the player.
+const MAX_NAME_LENGTH: usize = 12;
+
+/// Name given to the player who did not enter one.
+const DEFAULT_NAME: &str = "Didi";
+
+/// Plugin managing the round over screen.
+pub struct RoundOverScreenPlugin;
+
+impl Plugin for RoundOverScreenPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::RoundOver).with_system(setup_round_over_screen.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::RoundOver).with_system(name_entry_system.system()),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::RoundOver)
+                .with_system(cleanup_round_over_screen.system()),
+        );
+    }
+}
+
+/// Stores entities of the round over screen.
+struct RoundOverScreenData {
+    /// Entity wrapping all the entities of the screen
+    node_wrapper: Entity,
+}
+
+/// Score of the round, waiting for the name of the player.
+struct PendingScore {
+    /// The score, without name yet.
+    score: HighScore,
+    /// Whether the score enters the high scores table.
+    qualifies: bool,
+}
+
+/// Tag the text displaying the name entered by the player.
+struct NameText;
+
+/// Returns the text of the name entry.
+fn name_prompt(settings: &Settings, name: &str) -> String {
+    format!("{}: {}_", settings.language.translate("Name"), name)
+}
+
+/// Setup the result of the round, with the name entry for high scores.
+fn setup_round_over_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    settings: Res<Settings>,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    rng: Res<GameRng>,
+) {
+    let font = asset_server.load("FiraSans-Bold.ttf");
+    let translate = |text| settings.language.translate(text);
+
+    let score = HighScore {
+        name: String::new(),
+        level: LEVEL_NAME.to_string(),
+        difficulty: settings.difficulty,
+        date: today(),
+        time_survived: score.time_survived,
+        deliveries: score.deliveries,
+        seed: Some(rng.seed()),
+    };
+    let qualifies = high_scores.qualifies(&score);
+
+    let node_wrapper = commands
+        .spawn()
+        .insert_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                padding: Rect::all(Val::Px(50.0)),
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                ..Style::default()
+            },
+            material: materials.none.clone(),
+            ..NodeBundle::default()
+        })
+        .with_children(|parent| {
+            parent.spawn().insert_bundle(text_bundle(
+                translate("Round over").to_string(),
+                font.clone(),
+                100.0,
+            ));
+            parent.spawn().insert_bundle(text_bundle(
+                format!(
+                    "{} {}   {:.0}s",
+                    score.deliveries,
+                    translate("deliveries"),
+                    score.time_survived
+                ),
+                font.clone(),
+                50.0,
+            ));
+
+            if qualifies {
+                parent.spawn().insert_bundle(text_bundle(
+                    translate("New high score!").to_string(),
+                    font.clone(),
+                    50.0,
+                ));
+                parent.spawn().insert(NameText).insert_bundle(text_bundle(
+                    name_prompt(&settings, ""),
+                    font.clone(),
+                    50.0,
+                ));
+            }
+
+            parent.spawn().insert_bundle(text_bundle(
+                translate("Press Enter").to_string(),
+                font.clone(),
+                30.0,
+            ));
+        })
+        .id();
+
+    commands.insert_resource(RoundOverScreenData { node_wrapper });
+    commands.insert_resource(PendingScore { score, qualifies });
+}
+
+/// Types the name of the player, then records the score when pressing `Enter`,
+/// without the spaces around the name.
+fn name_entry_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut pending: ResMut<PendingScore>,
+    mut high_scores: ResMut<HighScores>,
+    mut state: ResMut<State<GameState>>,
+    mut name_texts: Query<&mut Text, With<NameText>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        if pending.qualifies {
+            let mut score = pending.score.clone();
+            score.name = score.name.trim().to_string();
+            if score.name.is_empty() {
+                score.name = DEFAULT_NAME.to_string();
+            }
+            high_scores.insert(score);
+            high_scores.save();
+        }
+        state.set(GameState::HighScores).unwrap();
+        return;
+    }
+    if !pending.qualifies {
+        return;
+    }
+
+    let name = &mut pending.score.name;
+    let previous_name = name.clone();
+    // A space typed while playing the end of the round may be read here.
+    for event in characters.iter() {
+        let typed = event.char.is_alphanumeric() || (event.char == ' ' && !name.is_empty());
+        if typed && name.chars().count() < MAX_NAME_LENGTH {
+            name.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        name.pop();
+    }
+
+    if *name != previous_name {
+        for mut text in name_texts.iter_mut() {
+            text.sections[0].value = name_prompt(&settings, name);
+        }
+    }
+}
+
+/// Removes all entities of the round over screen.
+fn cleanup_round_over_screen(mut commands: Commands, screen_data: Res<RoundOverScreenData>) {
+    commands
+        .entity(screen_data.node_wrapper)
+        .despawn_recursive();
+    commands.remove_resource::<PendingScore>();
+}
@@ -0,0 +1,324 @@
+//! Screen changing the settings of the game.
+
+use bevy::prelude::*;
+
+use crate::{
+    constants::GameState,
+    settings::{step_volume, Settings},
+};
+
+use super::{spawn_button, text_bundle, MenuMaterials, UpdatedButton};
+
+/// Plugin managing the settings screen.
+pub struct SettingsScreenPlugin;
+
+impl Plugin for SettingsScreenPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Settings).with_system(setup_settings_screen.system()),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::Settings)
+                .with_system(settings_button_system.system())
+                .with_system(volume_button_system.system())
+                .with_system(update_labels_system.system())
+                .with_system(update_volume_labels_system.system()),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::Settings).with_system(cleanup_settings_screen.system()),
+        );
+    }
+}
+
+/// Stores entities of the settings screen.
+struct SettingsScreenData {
+    /// Entity wrapping all the entities of the screen
+    node_wrapper: Entity,
+}
+
+/// A row of the settings screen.
+#[derive(Debug, Clone, Copy)]
+enum SettingRow {
+    /// A button changing a setting.
+    Button(SettingButton),
+    /// A volume with buttons to lower and raise it.
+    Volume(Volume),
+}
+
+/// All the rows, in the display order.
+const ROWS: [SettingRow; 8] = [
+    SettingRow::Button(SettingButton::DisplayMode),
+    SettingRow::Button(SettingButton::Vsync),
+    SettingRow::Volume(Volume::Music),
+    SettingRow::Volume(Volume::Effects),
+    SettingRow::Button(SettingButton::Language),
+    SettingRow::Button(SettingButton::Difficulty),
+    SettingRow::Button(SettingButton::DifficultyRamp),
+    SettingRow::Button(SettingButton::Back),
+];
+
+/// Buttons of the settings screen, each one changing a setting.
+#[derive(Debug, Clone, Copy)]
+enum SettingButton {
+    /// Toggles the fullscreen mode.
+    DisplayMode,
+    /// Toggles the vertical synchronization.
+    Vsync,
+    /// Changes the language.
+    Language,
+    /// Changes the difficulty.
+    Difficulty,
+    /// Toggles the difficulty ramp.
+    DifficultyRamp,
+    /// Goes back to the main menu.
+    Back,
+}
+
+impl SettingButton {
+    /// Returns the label of the button, showing the current value of its setting.
+    fn label(self, settings: &Settings) -> String {
+        let translate = |text| settings.language.translate(text);
+        let on_off = |enabled| translate(if enabled { "On" } else { "Off" });
+
+        match self {
+            Self::DisplayMode => format!(
+                "{}: {}",
+                translate("Display"),
+                translate(settings.display_mode.name())
+            ),
+            Self::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+            Self::Language => format!("{}: {}", translate("Language"), settings.language.name()),
+            Self::Difficulty => format!(
+                "{}: {}",
+                translate("Difficulty"),
+                translate(settings.difficulty.name())
+            ),
+            Self::DifficultyRamp => {
+                format!(
+                    "{}: {}",
+                    translate("Ramp"),
+                    on_off(settings.difficulty_ramp)
+                )
+            }
+            Self::Back => translate("Back").to_string(),
+        }
+    }
+
+    /// Changes the setting of the button to its next value.
+    fn change(self, settings: &mut Settings) {
+        match self {
+            Self::DisplayMode => settings.display_mode = settings.display_mode.toggled(),
+            Self::Vsync => settings.vsync = !settings.vsync,
+            Self::Language => settings.language = settings.language.next(),
+            Self::Difficulty => settings.difficulty = settings.difficulty.next(),
+            Self::DifficultyRamp => settings.difficulty_ramp = !settings.difficulty_ramp,
+            Self::Back => (),
+        }
+    }
+}
+
+/// A volume of the settings, tagging the label of its row.
+#[derive(Debug, Clone, Copy)]
+enum Volume {
+    /// Volume of the music.
+    Music,
+    /// Volume of the sound effects.
+    Effects,
+}
+
+impl Volume {
+    /// Returns the label of the volume, in percent.
+    fn label(self, settings: &Settings) -> String {
+        let (name, volume) = match self {
+            Self::Music => ("Music", settings.music_volume),
+            Self::Effects => ("Effects", settings.effects_volume),
+        };
+        format!(
+            "{}: {}%",
+            settings.language.translate(name),
+            (volume * 100.0).round()
+        )
+    }
+
+    /// Returns the volume in the settings.
+    fn volume_mut(self, settings: &mut Settings) -> &mut f32 {
+        match self {
+            Self::Music => &mut settings.music_volume,
+            Self::Effects => &mut settings.effects_volume,
+        }
+    }
+}
+
+/// Button lowering or raising a volume by the given number of steps.
+#[derive(Debug, Clone, Copy)]
+struct VolumeButton {
+    /// The volume changed by the button.
+    volume: Volume,
+    /// Steps of 10% added to the volume.
+    steps: f32,
+}
+
+/// Handles clicks on the buttons of the settings screen.
+fn settings_button_system(
+    materials: Res<MenuMaterials>,
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<State<GameState>>,
+    mut interaction_query: Query<
+        (&Interaction, &SettingButton, &mut Handle<ColorMaterial>),
+        UpdatedButton,
+    >,
+) {
+    for (interaction, button, mut material) in interaction_query.iter_mut() {
+        match (*interaction, button) {
+            (Interaction::Clicked, SettingButton::Back) => state.set(GameState::Menu).unwrap(),
+            (Interaction::Clicked, button) => button.change(&mut settings),
+            (Interaction::Hovered, _) => *material = materials.hovered_button.clone(),
+            (Interaction::None, _) => *material = materials.normal_button.clone(),
+        }
+    }
+}
+
+/// Handles clicks on the buttons changing the volumes, applied live by the
+/// audio backend.
+fn volume_button_system(
+    materials: Res<MenuMaterials>,
+    mut settings: ResMut<Settings>,
+    mut interaction_query: Query<
+        (&Interaction, &VolumeButton, &mut Handle<ColorMaterial>),
+        UpdatedButton,
+    >,
+) {
+    for (interaction, button, mut material) in interaction_query.iter_mut() {
+        match *interaction {
+            Interaction::Clicked => {
+                let volume = button.volume.volume_mut(&mut settings);
+                *volume = step_volume(*volume, button.steps);
+            }
+            Interaction::Hovered => *material = materials.hovered_button.clone(),
+            Interaction::None => *material = materials.normal_button.clone(),
+        }
+    }
+}
+
+/// Updates the labels of the buttons when the settings change.
+fn update_labels_system(settings: Res<Settings>, mut labels: Query<(&mut Text, &SettingButton)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, button) in labels.iter_mut() {
+        text.sections[0].value = button.label(&settings);
+    }
+}
+
+/// Updates the labels of the volumes when the settings change.
+fn update_volume_labels_system(settings: Res<Settings>, mut labels: Query<(&mut Text, &Volume)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, volume) in labels.iter_mut() {
+        text.sections[0].value = volume.label(&settings);
+    }
+}
+
+/// Setup a button for each setting, and a row with `-` and `+` buttons for
+/// each volume.
+fn setup_settings_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<MenuMaterials>,
+    settings: Res<Settings>,
+) {
+    let font = asset_server.load("FiraSans-Bold.ttf");
+
+    let node_wrapper = commands
+        .spawn()
+        .insert_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                padding: Rect::all(Val::Px(30.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::ColumnReverse,
+                ..Style::default()
+            },
+            material: materials.none.clone(),
+            ..NodeBundle::default()
+        })
+        .with_children(|parent| {
+            for row in ROWS {
+                match row {
+                    SettingRow::Button(button) => spawn_button(
+                        parent,
+                        &materials,
+                        font.clone(),
+                        Size::new(Val::Px(500.0), Val::Px(55.0)),
+                        button.label(&settings),
+                        button,
+                    ),
+                    SettingRow::Volume(volume) => {
+                        spawn_volume_row(parent, &materials, font.clone(), volume, &settings);
+                    }
+                }
+            }
+        })
+        .id();
+
+    commands.insert_resource(SettingsScreenData { node_wrapper });
+}
+
+/// Spawns the label of the volume between the buttons lowering and raising it.
+fn spawn_volume_row(
+    parent: &mut ChildBuilder,
+    materials: &MenuMaterials,
+    font: Handle<Font>,
+    volume: Volume,
+    settings: &Settings,
+) {
+    let button_size = Size::new(Val::Px(55.0), Val::Px(55.0));
+
+    parent
+        .spawn()
+        .insert_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(500.0), Val::Px(75.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceBetween,
+                ..Style::default()
+            },
+            material: materials.none.clone(),
+            ..NodeBundle::default()
+        })
+        .with_children(|parent| {
+            spawn_button(
+                parent,
+                materials,
+                font.clone(),
+                button_size,
+                "-".to_string(),
+                VolumeButton {
+                    volume,
+                    steps: -1.0,
+                },
+            );
+            parent.spawn().insert(volume).insert_bundle(text_bundle(
+                volume.label(settings),
+                font.clone(),
+                40.0,
+            ));
+            spawn_button(
+                parent,
+                materials,
+                font,
+                button_size,
+                "+".to_string(),
+                VolumeButton { volume, steps: 1.0 },
+            );
+        });
+}
+
+/// Removes all entities of the settings screen.
+fn cleanup_settings_screen(mut commands: Commands, screen_data: Res<SettingsScreenData>) {
+    commands
+        .entity(screen_data.node_wrapper)
+        .despawn_recursive();
+}
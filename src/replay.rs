@@ -0,0 +1,486 @@
+//! Recording of the inputs of a round and deterministic replay.
+//!
+//! A replay stores the seed of the round and the resolved inputs of every
+//! frame. Feeding them back through the controllers reproduces the same
+//! actions and the same happiness.
+
+use std::{
+    fmt, fs, io, iter,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{app::AppExit, prelude::*};
+use itertools::Itertools;
+
+use crate::{
+    clock::{ClockSystems, GameClock},
+    constants::GameState,
+    controllers::{ControllerSystems, DirectionEvent, InputSource, InteractInput},
+    gameplay::Happiness,
+    rng::GameRng,
+};
+
+/// First line of replay files, with the version of the format.
+const HEADER: &str = "baobei-replay 1";
+
+/// Plugin recording the inputs of the round, or replaying recorded ones.
+pub enum ReplayPlugin {
+    /// Records the inputs of the first round and saves them in the file when
+    /// leaving it or when the game exits.
+    Record(PathBuf),
+    /// Feeds the recorded inputs to the game instead of live devices.
+    Play(Replay),
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        match self {
+            Self::Record(path) => {
+                // A replay without the seed of its round could not be played.
+                let seed = match app.world().get_resource::<GameRng>() {
+                    Some(rng) => rng.seed(),
+                    None => {
+                        error!("No seeded round to record in {}", path.display());
+                        return;
+                    }
+                };
+
+                app.insert_resource(ReplayRecorder {
+                    path: path.clone(),
+                    replay: Replay {
+                        seed,
+                        ..Replay::default()
+                    },
+                })
+                .add_system_set(
+                    SystemSet::on_update(GameState::InGame)
+                        .with_system(record_frame_system.system().after(ControllerSystems)),
+                )
+                .add_system_set(
+                    SystemSet::on_exit(GameState::InGame).with_system(save_replay_system.system()),
+                )
+                .add_system_to_stage(CoreStage::Last, save_replay_on_exit_system.system());
+            }
+            Self::Play(replay) => {
+                app.insert_resource(InputSource::Scripted)
+                    .insert_resource(ReplayPlayer {
+                        replay: replay.clone(),
+                        next_frame: 0,
+                        finished: false,
+                    })
+                    .add_system_to_stage(
+                        CoreStage::PreUpdate,
+                        play_frame_system.system().after(ClockSystems),
+                    );
+            }
+        }
+    }
+}
+
+/// Inputs of a frame, as resolved by the controllers.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplayFrame {
+    /// Duration of the frame.
+    pub delta: Duration,
+    /// Direction chosen by the player, if any.
+    pub direction: Option<Vec2>,
+    /// Whether the interact button is pressed.
+    pub interact: bool,
+    /// Whether the button cycling the inventory slots is pressed.
+    pub cycle: bool,
+}
+
+/// Recorded inputs of a round.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Replay {
+    /// Seed of the recorded round.
+    pub seed: u64,
+    /// Inputs of each frame of the round.
+    pub frames: Vec<ReplayFrame>,
+    /// Happiness of Baobei at the end of the recording, checked by the replay.
+    pub final_happiness: Option<f32>,
+}
+
+impl Replay {
+    /// Loads the replay stored in the file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Saves the replay in the file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Parses a replay written by `Display`.
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let mut lines = content.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid_data("missing header"));
+        }
+
+        let seed = lines
+            .next()
+            .and_then(|line| line.strip_prefix("seed "))
+            .and_then(|seed| seed.parse().ok())
+            .ok_or_else(|| invalid_data("missing seed"))?;
+
+        let mut replay = Self {
+            seed,
+            ..Self::default()
+        };
+
+        for line in lines {
+            if let Some(happiness) = line.strip_prefix("end ") {
+                let happiness = happiness.parse().map_err(|_| invalid_data(line))?;
+                replay.final_happiness = Some(happiness);
+            } else {
+                let (count, frame) = parse_frames(line).ok_or_else(|| invalid_data(line))?;
+                replay.frames.extend(iter::repeat(frame).take(count));
+            }
+        }
+        Ok(replay)
+    }
+}
+
+/// Writes the replay in a compact text format, where identical consecutive
+/// frames are written once with their count:
+///
+/// ```text
+/// baobei-replay 1
+/// seed <seed>
+/// <count> <delta in ns> <buttons> [<direction x> <direction y>]
+/// end <final happiness>
+/// ```
+///
+/// The buttons are the sum of 1 when interact is pressed and 2 when cycle is
+/// pressed, so replays recorded before the cycle button still parse.
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "seed {}", self.seed)?;
+
+        for (count, frame) in self.frames.iter().dedup_with_count() {
+            let buttons = u8::from(frame.interact) | (u8::from(frame.cycle) << 1);
+            write!(f, "{} {} {}", count, frame.delta.as_nanos(), buttons)?;
+
+            if let Some(direction) = frame.direction {
+                write!(f, " {} {}", direction.x, direction.y)?;
+            }
+            writeln!(f)?;
+        }
+
+        if let Some(happiness) = self.final_happiness {
+            writeln!(f, "end {}", happiness)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a line of identical frames, returning the count and the frame.
+fn parse_frames(line: &str) -> Option<(usize, ReplayFrame)> {
+    let mut fields = line.split_whitespace();
+
+    let count = fields.next()?.parse().ok()?;
+    let delta = Duration::from_nanos(fields.next()?.parse().ok()?);
+    let buttons: u8 = fields.next()?.parse().ok()?;
+    let direction = match (fields.next(), fields.next()) {
+        (Some(x), Some(y)) => Some(Vec2::new(x.parse().ok()?, y.parse().ok()?)),
+        _ => None,
+    };
+
+    Some((
+        count,
+        ReplayFrame {
+            delta,
+            direction,
+            interact: buttons & 1 != 0,
+            cycle: buttons & 2 != 0,
+        },
+    ))
+}
+
+/// Returns an error about invalid content in a replay file.
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid replay: {}", message),
+    )
+}
+
+/// Stores the inputs recorded since the start of the round.
+struct ReplayRecorder {
+    /// File where the replay is saved.
+    path: PathBuf,
+    /// The replay being recorded.
+    replay: Replay,
+}
+
+/// Stores the replay being played.
+struct ReplayPlayer {
+    /// The replay being played.
+    replay: Replay,
+    /// Index of the next frame to play.
+    next_frame: usize,
+    /// Whether all the frames have been played.
+    finished: bool,
+}
+
+/// Records the inputs of the frame resolved by the controllers.
+fn record_frame_system(
+    clock: Res<GameClock>,
+    interact: Res<InteractInput>,
+    mut direction_events: EventReader<DirectionEvent>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+) {
+    let mut recorder = match recorder {
+        Some(recorder) => recorder,
+        None => return,
+    };
+
+    // Only the last direction is used by the movement.
+    let direction = direction_events
+        .iter()
+        .last()
+        .map(|event| event.direction.truncate());
+
+    recorder.replay.frames.push(ReplayFrame {
+        delta: clock.delta(),
+        direction,
+        interact: interact.interact.pressed,
+        cycle: interact.cycle.pressed,
+    });
+}
+
+/// Saves the recorded replay when leaving the round, the next rounds are not
+/// recorded.
+fn save_replay_system(
+    mut commands: Commands,
+    recorder: Option<Res<ReplayRecorder>>,
+    happiness_values: Query<&Happiness>,
+) {
+    if let Some(recorder) = recorder {
+        save_replay(&recorder, &happiness_values);
+        commands.remove_resource::<ReplayRecorder>();
+    }
+}
+
+/// Saves the recorded replay when the game exits during the round.
+fn save_replay_on_exit_system(
+    mut exit_events: EventReader<AppExit>,
+    recorder: Option<Res<ReplayRecorder>>,
+    happiness_values: Query<&Happiness>,
+) {
+    if exit_events.iter().next().is_none() {
+        return;
+    }
+    if let Some(recorder) = recorder {
+        save_replay(&recorder, &happiness_values);
+    }
+}
+
+/// Saves the recorded replay with the current happiness as the final one.
+fn save_replay(recorder: &ReplayRecorder, happiness_values: &Query<&Happiness>) {
+    let mut replay = recorder.replay.clone();
+    replay.final_happiness = happiness_values
+        .iter()
+        .next()
+        .copied()
+        .map(Happiness::value);
+
+    match replay.save(&recorder.path) {
+        Ok(()) => info!("Replay saved in {}", recorder.path.display()),
+        Err(error) => error!("Fail to save the replay: {}", error),
+    }
+}
+
+/// Feeds the inputs of the next recorded frame, then quits at the end of the
+/// replay after checking the final happiness.
+fn play_frame_system(
+    mut player: ResMut<ReplayPlayer>,
+    mut clock: ResMut<GameClock>,
+    mut interact: ResMut<InteractInput>,
+    mut direction_events: EventWriter<DirectionEvent>,
+    mut exit_events: EventWriter<AppExit>,
+    happiness_values: Query<&Happiness>,
+) {
+    if let Some(&frame) = player.replay.frames.get(player.next_frame) {
+        player.next_frame += 1;
+
+        clock.advance(frame.delta);
+        interact.interact.set_pressed(frame.interact);
+        interact.cycle.set_pressed(frame.cycle);
+        if let Some(direction) = frame.direction {
+            direction_events.send(DirectionEvent {
+                direction: direction.extend(0.0),
+            });
+        }
+        return;
+    }
+
+    // Freeze the game at the end of the replay.
+    clock.advance(Duration::default());
+    interact.interact.set_pressed(false);
+    interact.cycle.set_pressed(false);
+    if player.finished {
+        return;
+    }
+    player.finished = true;
+
+    let happiness = happiness_values
+        .iter()
+        .next()
+        .copied()
+        .map(Happiness::value);
+    match (player.replay.final_happiness, happiness) {
+        (Some(expected), Some(actual)) if (expected - actual).abs() > f32::EPSILON => warn!(
+            "Replay diverged: final happiness is {} instead of {}",
+            actual, expected
+        ),
+        _ => info!("Replay finished with happiness {:?}", happiness),
+    }
+    exit_events.send(AppExit);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::TryFrom, env, fs};
+
+    use bevy::{
+        app::{AppExit, Events},
+        prelude::*,
+    };
+
+    use super::{Replay, ReplayFrame, ReplayPlugin, ReplayRecorder};
+    use crate::{
+        collisions::Position,
+        constants::GameState,
+        gameplay::{ActionEvent, Happiness},
+        testing::{TestApp, FRAME},
+    };
+
+    /// Stores the actions made during the test.
+    #[derive(Default)]
+    struct ActionLog(Vec<ActionEvent>);
+
+    fn log_actions_system(mut action_events: EventReader<ActionEvent>, mut log: ResMut<ActionLog>) {
+        log.0.extend(action_events.iter().copied());
+    }
+
+    /// Builds a test app logging actions, with the given replay plugin.
+    fn app_with(plugin: ReplayPlugin) -> TestApp {
+        TestApp::with_setup(7, |app| {
+            app.init_resource::<ActionLog>()
+                .add_system(log_actions_system.system())
+                .add_plugin(plugin);
+        })
+    }
+
+    /// Moves Didi in the direction during the given number of frames.
+    fn walk(app: &mut TestApp, direction: Vec3, frames: u32) {
+        for _ in 0..frames {
+            app.send_direction(direction);
+            app.run_frames(1);
+        }
+    }
+
+    /// Returns the actions, position of Didi and happiness of Baobei.
+    fn outcome(app: &mut TestApp) -> (Vec<ActionEvent>, Vec3, f32) {
+        let (didi, baobei) = (app.didi(), app.baobei());
+        (
+            app.resource_mut::<ActionLog>().0.clone(),
+            app.get::<Position>(didi).unwrap().0,
+            app.get::<Happiness>(baobei).unwrap().value(),
+        )
+    }
+
+    #[test]
+    fn test_replay_format() {
+        let frame = ReplayFrame {
+            delta: FRAME,
+            direction: None,
+            interact: false,
+            cycle: false,
+        };
+        let moving_frame = ReplayFrame {
+            direction: Some(Vec2::new(0.6, -0.8)),
+            interact: true,
+            ..frame
+        };
+        let replay = Replay {
+            seed: 42,
+            frames: vec![frame, frame, moving_frame, frame],
+            final_happiness: Some(0.85),
+        };
+
+        let content = replay.to_string();
+        assert_eq!(
+            content,
+            "baobei-replay 1\nseed 42\n2 20000000 0\n1 20000000 1 0.6 -0.8\n1 20000000 0\nend 0.85\n"
+        );
+        assert_eq!(Replay::parse(&content).unwrap(), replay);
+        assert!(Replay::parse("seed 42\n").is_err());
+        assert!(Replay::parse("baobei-replay 1\n2 20000000 0\n").is_err());
+    }
+
+    #[test]
+    fn test_record_first_round_only() {
+        let path = env::temp_dir().join(format!("baobei-needs-{}.replay", std::process::id()));
+        let mut recording = app_with(ReplayPlugin::Record(path.clone()));
+        walk(&mut recording, Vec3::Y, 20);
+
+        // Leaving the round saves its replay.
+        for state in &[GameState::Menu, GameState::InGame] {
+            recording
+                .resource_mut::<State<GameState>>()
+                .set(state.clone())
+                .unwrap();
+            recording.run_frames(1);
+        }
+        let replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(replay.seed, 7);
+        assert!(replay.frames.len() >= 20);
+        assert!(replay.final_happiness.is_some());
+
+        // The next round is not recorded.
+        walk(&mut recording, Vec3::Y, 20);
+        recording.resource_mut::<Events<AppExit>>().send(AppExit);
+        recording.run_frames(1);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_replay_reproduces_round() {
+        let mut recording = app_with(ReplayPlugin::Record("unused.replay".into()));
+        walk(&mut recording, Vec3::Y, 20); // In front of the fridge
+        recording.tap(KeyCode::Space); // Take an ice cream
+        walk(&mut recording, Vec3::new(1.0, -1.0, 0.0), 10);
+        recording.tap(KeyCode::Space); // Drop it
+        recording.run_seconds(1.5);
+
+        let replay = recording.resource_mut::<ReplayRecorder>().replay.clone();
+        let frames = replay.frames.len();
+        assert_eq!(replay.seed, 7);
+
+        // The startup frame plays the first recorded frame.
+        let mut replaying = app_with(ReplayPlugin::Play(replay));
+        replaying.run_frames(u32::try_from(frames).unwrap() - 1);
+
+        let (actions, position, happiness) = outcome(&mut recording);
+        let (replayed_actions, replayed_position, replayed_happiness) = outcome(&mut replaying);
+        // The entities differ between the apps, so are the targets of the
+        // actions.
+        for actions in &[actions, replayed_actions] {
+            assert!(matches!(
+                actions[..],
+                [ActionEvent::Take(_, _), ActionEvent::Drop(_)]
+            ));
+        }
+        assert_eq!(
+            (replayed_position, replayed_happiness),
+            (position, happiness)
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Seeded random generator shared by all the gameplay randomness.
+
+use bevy::prelude::*;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::{constants::GameState, gameplay::NewRoundSystems};
+
+/// Plugin providing the random generator of the rounds.
+pub struct RngPlugin {
+    /// Seed of the first round, the next ones being derived from it.
+    pub seed: u64,
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(GameRng::from_seed(self.seed))
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .with_system(log_seed_system.system().after(NewRoundSystems)),
+            );
+    }
+}
+
+/// Random generator of the round.
+///
+/// All the gameplay randomness comes from this generator, so that a round
+/// played with the same seed and inputs is reproduced exactly. Each round has
+/// its own seed, derived from the seed of the previous round.
+pub struct GameRng {
+    /// Seed of the current round, or of the first one before it starts.
+    seed: u64,
+    /// Seed of the next round.
+    next_seed: u64,
+    /// The seeded generator, the same as the standard one but able to tell
+    /// its position.
+    rng: ChaCha12Rng,
+}
+
+impl GameRng {
+    /// Creates a generator from the given seed, which is also the seed of the
+    /// first round.
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            next_seed: seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Starts the generator of a new round from the seed of the next round,
+    /// so that the rounds differ while each one is reproduced from its seed.
+    pub fn reseed(&mut self) {
+        self.seed = self.next_seed;
+        self.next_seed = mix_seed(self.seed);
+        self.rng = ChaCha12Rng::seed_from_u64(self.seed);
+    }
+
+    /// Continues the round of the given seed from the given position, as
+    /// returned by `position`.
+    pub fn restore(&mut self, seed: u64, position: u128) {
+        *self = Self::from_seed(seed);
+        self.reseed();
+        self.rng.set_word_pos(position);
+    }
+
+    /// Returns the position of the generator in the current round.
+    pub fn position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+
+    /// Returns the seed of the current round.
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+/// Derives a well distributed seed from the given one, with the finalizer of
+/// SplitMix64.
+const fn mix_seed(seed: u64) -> u64 {
+    let mut mixed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    mixed ^ (mixed >> 31)
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Logs the seed of the round, to be given in bug reports.
+fn log_seed_system(rng: Res<GameRng>) {
+    info!(
+        "Round seed: {} (replay it with `--seed {}`)",
+        rng.seed(),
+        rng.seed()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::GameRng;
+
+    #[test]
+    fn test_same_seed_same_values() {
+        let mut rng_1 = GameRng::from_seed(42);
+        let mut rng_2 = GameRng::from_seed(42);
+
+        let values_1: Vec<u32> = (0..10).map(|_| rng_1.gen()).collect();
+        let values_2: Vec<u32> = (0..10).map(|_| rng_2.gen()).collect();
+        assert_eq!(values_1, values_2);
+        assert_eq!(rng_1.seed(), 42);
+
+        // The first round is seeded with the given seed.
+        rng_1.reseed();
+        assert_eq!(rng_1.seed(), 42);
+        let values_3: Vec<u32> = (0..10).map(|_| rng_1.gen()).collect();
+        assert_eq!(values_1, values_3);
+
+        // The next round has its own seed, reproducing it.
+        rng_1.reseed();
+        let round_seed = rng_1.seed();
+        assert_ne!(round_seed, 42);
+        let values_4: Vec<u32> = (0..10).map(|_| rng_1.gen()).collect();
+        assert_ne!(values_4, values_1);
+        let mut rng_3 = GameRng::from_seed(round_seed);
+        rng_3.reseed();
+        assert_eq!((0..10).map(|_| rng_3.gen()).collect::<Vec<u32>>(), values_4);
+
+        // A restored round continues where it was.
+        let mut rng_4 = GameRng::from_seed(7);
+        rng_4.restore(rng_1.seed(), rng_1.position());
+        assert_eq!(rng_4.seed(), rng_1.seed());
+        assert_eq!(rng_4.gen::<u64>(), rng_1.gen::<u64>());
+        rng_4.reseed();
+        rng_1.reseed();
+        assert_eq!(rng_4.seed(), rng_1.seed());
+    }
+}
@@ -0,0 +1,302 @@
+//! Settings of the game, persisted in the user config directory.
+
+use std::io;
+
+use bevy::{prelude::*, window::WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{GameState, WINDOW_HEIGHT, WINDOW_WIDTH},
+    difficulty::Difficulty,
+    storage,
+};
+
+/// Name of the settings file in the user config directory.
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Plugin applying the settings and saving them when changed.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<Settings>()
+            .add_system(apply_window_settings_system.system())
+            .add_system(toggle_fullscreen_system.system())
+            .add_system_set(
+                SystemSet::on_exit(GameState::Settings).with_system(save_settings_system.system()),
+            );
+    }
+}
+
+/// Settings chosen by the player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Whether the game is displayed in a window or in fullscreen.
+    pub display_mode: DisplayMode,
+    /// Whether the frame rate is synchronized with the screen.
+    pub vsync: bool,
+    /// Volume of the music, between 0 and 1.
+    pub music_volume: f32,
+    /// Volume of the sound effects, between 0 and 1.
+    pub effects_volume: f32,
+    /// Language of the texts.
+    pub language: Language,
+    /// Difficulty of the game.
+    pub difficulty: Difficulty,
+    /// Whether Baobei gets more demanding over the round.
+    pub difficulty_ramp: bool,
+    /// Seed of the rounds, random when not set.
+    pub seed: Option<u64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            display_mode: DisplayMode::Windowed,
+            vsync: true,
+            music_volume: 0.8,
+            effects_volume: 0.8,
+            language: Language::English,
+            difficulty: Difficulty::Normal,
+            difficulty_ramp: false,
+            seed: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings file, or returns the default settings if it is
+    /// missing.
+    ///
+    /// The settings are loaded before the logs are set up, so the error of an
+    /// unreadable or invalid file is returned for the caller to log it later.
+    pub fn load() -> io::Result<Self> {
+        let settings = match storage::config_file(SETTINGS_FILE) {
+            Some(path) => storage::read(&path)?,
+            None => None,
+        };
+        Ok(settings.unwrap_or_default())
+    }
+
+    /// Saves the settings in the settings file.
+    pub fn save(&self) {
+        let path = match storage::config_file(SETTINGS_FILE) {
+            Some(path) => path,
+            None => {
+                warn!("No config directory to save the settings");
+                return;
+            }
+        };
+
+        match storage::save(&path, self) {
+            Ok(()) => info!("Settings saved in {}", path.display()),
+            Err(error) => error!("Fail to save the settings: {}", error),
+        }
+    }
+
+    /// Returns the descriptor of the window following the settings.
+    pub fn window_descriptor(&self) -> WindowDescriptor {
+        WindowDescriptor {
+            title: "Baobei needs".to_string(),
+            width: WINDOW_WIDTH,
+            height: WINDOW_HEIGHT,
+            resizable: true,
+            vsync: self.vsync,
+            mode: self.display_mode.into(),
+            ..WindowDescriptor::default()
+        }
+    }
+}
+
+/// Whether the game is displayed in a window or in fullscreen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    /// Displayed in a window.
+    Windowed,
+    /// Displayed in the whole screen.
+    Fullscreen,
+}
+
+impl DisplayMode {
+    /// Returns the other display mode.
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Windowed => Self::Fullscreen,
+            Self::Fullscreen => Self::Windowed,
+        }
+    }
+
+    /// Returns the name of the display mode.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Windowed => "Windowed",
+            Self::Fullscreen => "Fullscreen",
+        }
+    }
+}
+
+impl From<DisplayMode> for WindowMode {
+    fn from(display_mode: DisplayMode) -> Self {
+        match display_mode {
+            DisplayMode::Windowed => Self::Windowed,
+            DisplayMode::Fullscreen => Self::BorderlessFullscreen,
+        }
+    }
+}
+
+/// Language of the texts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    /// English, the language texts are written in.
+    English,
+    /// French
+    French,
+}
+
+impl Language {
+    /// Returns the next language.
+    pub const fn next(self) -> Self {
+        match self {
+            Self::English => Self::French,
+            Self::French => Self::English,
+        }
+    }
+
+    /// Returns the name of the language, in this language.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::French => "Français",
+        }
+    }
+
+    /// Translates the english text in this language.
+    pub fn translate(self, text: &'static str) -> &'static str {
+        match (self, text) {
+            (Self::French, "Continue") => "Continuer",
+            (Self::French, "Play") => "Jouer",
+            (Self::French, "Settings") => "Paramètres",
+            (Self::French, "High scores") => "Meilleurs scores",
+            (Self::French, "Living room") => "Salon",
+            (Self::French, "No score yet") => "Aucun score",
+            (Self::French, "deliveries") => "livraisons",
+            (Self::French, "Round over") => "Partie terminée",
+            (Self::French, "New high score!") => "Nouveau record !",
+            (Self::French, "Name") => "Nom",
+            (Self::French, "Press Enter") => "Appuyez sur Entrée",
+            (Self::French, "Back") => "Retour",
+            (Self::French, "Display") => "Affichage",
+            (Self::French, "Windowed") => "Fenêtré",
+            (Self::French, "Fullscreen") => "Plein écran",
+            (Self::French, "On") => "Activée",
+            (Self::French, "Off") => "Désactivée",
+            (Self::French, "Music") => "Musique",
+            (Self::French, "Effects") => "Effets",
+            (Self::French, "Language") => "Langue",
+            (Self::French, "Difficulty") => "Difficulté",
+            (Self::French, "Ramp") => "Progression",
+            (Self::French, "Easy") => "Facile",
+            (Self::French, "Normal") => "Normale",
+            (Self::French, "Hard") => "Difficile",
+            (Self::French, "Space") => "Espace",
+            (Self::French, "Hold") => "Maintenir",
+            (Self::French, "Take") => "Prendre",
+            (Self::French, "Put away") => "Ranger",
+            (Self::French, "Drop") => "Poser",
+            (Self::French, "Pick up") => "Ramasser",
+            (Self::French, "Keep") => "Garder",
+            (Self::French, "Give") => "Donner",
+            (Self::French, "Add") => "Ajouter",
+            (Self::French, "Collect") => "Récupérer",
+            (Self::French, "Prepare") => "Préparer",
+            (Self::French, "Throw away") => "Jeter",
+            (Self::French, "Clean") => "Nettoyer",
+            (Self::French, "Restock") => "Remplir",
+            (Self::French, "Leave") => "Laisser",
+            (Self::French, "on the table") => "sur la table",
+            (Self::French, "Take from the table") => "Prendre sur la table",
+            (Self::French, "Nap") => "Sieste",
+            (Self::French, "Wash") => "Laver",
+            (Self::French, "Ice Cream") => "Glace",
+            (Self::French, "Water Glass") => "Verre d'eau",
+            (Self::French, "Tea") => "Thé",
+            (Self::French, "Bread") => "Pain",
+            (Self::French, "Trash") => "Déchet",
+            (Self::French, "Sponge") => "Éponge",
+            (Self::French, "Dirty Glass") => "Verre sale",
+            _ => text,
+        }
+    }
+}
+
+/// Returns the volume changed by the given number of steps of 10%, between
+/// 0 and 100%.
+pub fn step_volume(volume: f32, steps: f32) -> f32 {
+    ((volume * 10.0).round() + steps).clamp(0.0, 10.0) / 10.0
+}
+
+/// Applies the display mode and vsync settings to the window when changed.
+fn apply_window_settings_system(settings: Res<Settings>, mut windows: ResMut<Windows>) {
+    if !settings.is_changed() {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        let mode: WindowMode = settings.display_mode.into();
+        if window.mode() != mode {
+            window.set_mode(mode);
+        }
+        if window.vsync() != settings.vsync {
+            window.set_vsync(settings.vsync);
+        }
+    }
+}
+
+/// Toggles the fullscreen mode when the player press `F11`.
+fn toggle_fullscreen_system(keyboard_input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keyboard_input.just_pressed(KeyCode::F11) {
+        settings.display_mode = settings.display_mode.toggled();
+        settings.save();
+    }
+}
+
+/// Saves the settings when leaving the settings screen.
+fn save_settings_system(settings: Res<Settings>) {
+    settings.save();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{step_volume, Settings};
+
+    #[test]
+    fn test_step_volume() {
+        let mut volume = 0.0;
+        for _ in 0..7 {
+            volume = step_volume(volume, 1.0);
+        }
+        assert!((volume - 0.7).abs() < 1e-6);
+        assert!((step_volume(0.75, -1.0) - 0.7).abs() < 1e-6);
+        assert!((step_volume(1.0, 1.0) - 1.0).abs() < 1e-6);
+        assert!(step_volume(0.0, -1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_partial_settings_file() {
+        let settings: Settings =
+            ron::from_str("(vsync: false, music_volume: 0.5, language: French, seed: Some(42))")
+                .unwrap();
+
+        assert_eq!(
+            settings,
+            Settings {
+                vsync: false,
+                music_volume: 0.5,
+                language: super::Language::French,
+                seed: Some(42),
+                ..Settings::default()
+            }
+        );
+        assert!(ron::from_str::<Settings>("not settings").is_err());
+    }
+}
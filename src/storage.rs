@@ -0,0 +1,66 @@
+//! Persistence of RON files in the user directories.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Name of the directory of the game in the user directories.
+const GAME_DIRECTORY: &str = "baobei-needs";
+
+/// Returns the path of the file in the user config directory.
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(GAME_DIRECTORY).join(name))
+}
+
+/// Returns the path of the file in the user data directory.
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(GAME_DIRECTORY).join(name))
+}
+
+/// Loads the value stored in the RON file.
+///
+/// Returns `None` when the file is missing or invalid, so that the game can
+/// fall back to a default value.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    read(path).unwrap_or_else(|error| {
+        warn!("Fail to load {}: {}", path.display(), error);
+        None
+    })
+}
+
+/// Reads the value stored in the RON file, `None` when the file is missing.
+///
+/// Unlike `load`, the errors are returned rather than logged, for the values
+/// read before the logs are set up.
+pub fn read<T: DeserializeOwned>(path: &Path) -> io::Result<Option<T>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    ron::from_str(&content)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Saves the value in the RON file, creating its directory if needed.
+pub fn save<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let content = ron::ser::to_string_pretty(value, PrettyConfig::default())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    write(path, &content)
+}
+
+/// Writes the content in the file, creating its directory if needed.
+pub fn write(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    fs::write(path, content)
+}
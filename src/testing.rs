@@ -0,0 +1,197 @@
+//! Test support: runs the gameplay in a headless `App` driven frame by frame.
+
+use std::time::Duration;
+
+use bevy::{
+    app::Events,
+    asset::AssetPlugin,
+    ecs::component::Component,
+    input::{keyboard::KeyboardInput, ElementState, InputPlugin},
+    prelude::*,
+    text::Font,
+};
+
+use crate::{
+    animation::AnimationPlugin,
+    audio::{NullAudioBackendPlugin, SoundPlugin},
+    clock::{ClockPlugin, GameClock},
+    collisions::{CollisionPlugin, Contact, Position},
+    constants::GameState,
+    controllers::{ControllerPlugin, DirectionEvent},
+    cooldown::CooldownPlugin,
+    gameplay::{Baobei, Didi, GameplayPlugin, RoundSaveFile},
+    rng::GameRng,
+    settings::Settings,
+};
+
+/// Seed of the random generator used by default in tests.
+pub const SEED: u64 = 42;
+
+/// Duration of a frame simulated by the test app (50 FPS).
+pub const FRAME: Duration = Duration::from_millis(20);
+
+/// A headless app running the gameplay, whose inputs and time are scripted.
+pub struct TestApp {
+    /// The wrapped bevy app.
+    app: App,
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestApp {
+    /// Builds an app with the gameplay plugins in the `InGame` state and runs
+    /// the startup frame.
+    pub fn new() -> Self {
+        Self::with_seed(SEED)
+    }
+
+    /// Builds the app with the given seed for the random generator.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_setup(seed, |_| {})
+    }
+
+    /// Builds the app with the given seed, letting the test add its own
+    /// plugins and systems before the startup frame.
+    pub fn with_setup(seed: u64, setup: impl FnOnce(&mut AppBuilder)) -> Self {
+        let mut builder = App::build();
+        builder
+            .add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_plugin(InputPlugin)
+            .add_asset::<Texture>()
+            .add_asset::<ColorMaterial>()
+            .add_asset::<TextureAtlas>()
+            .add_asset::<Font>()
+            .insert_resource(GameClock::manual())
+            .insert_resource(GameRng::from_seed(seed))
+            .init_resource::<Settings>()
+            .add_state(GameState::InGame)
+            .add_plugin(AnimationPlugin)
+            .add_plugin(SoundPlugin)
+            .add_plugin(NullAudioBackendPlugin)
+            .add_plugin(ClockPlugin)
+            .add_plugin(CooldownPlugin)
+            .add_plugin(ControllerPlugin)
+            .add_plugin(CollisionPlugin)
+            .add_plugin(GameplayPlugin);
+        // Tests never touch the save of the player.
+        builder.world_mut().remove_resource::<RoundSaveFile>();
+        setup(&mut builder);
+
+        let mut test_app = Self { app: builder.app };
+        test_app.run_frames(1);
+        test_app
+    }
+
+    /// Runs the given number of frames, each one lasting `FRAME`.
+    pub fn run_frames(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.run_frame(FRAME);
+        }
+    }
+
+    /// Runs a frame lasting the given duration.
+    pub fn run_frame(&mut self, duration: Duration) {
+        self.resource_mut::<GameClock>().advance(duration);
+        self.app.update();
+    }
+
+    /// Runs as many frames as needed to simulate the given duration.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn run_seconds(&mut self, seconds: f32) {
+        let frames = (seconds / FRAME.as_secs_f32()).ceil() as u32;
+        self.run_frames(frames);
+    }
+
+    /// Presses the key, it stays pressed until released.
+    pub fn press(&mut self, key_code: KeyCode) {
+        self.send_key(key_code, ElementState::Pressed);
+    }
+
+    /// Releases the key.
+    pub fn release(&mut self, key_code: KeyCode) {
+        self.send_key(key_code, ElementState::Released);
+    }
+
+    /// Presses the key during one frame, then releases it.
+    pub fn tap(&mut self, key_code: KeyCode) {
+        self.press(key_code);
+        self.run_frames(1);
+        self.release(key_code);
+    }
+
+    /// Sends a keyboard event, applied by the input plugin in the next frame.
+    fn send_key(&mut self, key_code: KeyCode, state: ElementState) {
+        self.resource_mut::<Events<KeyboardInput>>()
+            .send(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key_code),
+                state,
+            });
+    }
+
+    /// Sends a direction event read in the next frame.
+    pub fn send_direction(&mut self, direction: Vec3) {
+        self.resource_mut::<Events<DirectionEvent>>()
+            .send(DirectionEvent {
+                direction: direction.normalize(),
+            });
+    }
+
+    /// Returns the entity of Didi.
+    pub fn didi(&mut self) -> Entity {
+        self.single_entity::<Didi>()
+    }
+
+    /// Returns the entity of Baobei.
+    pub fn baobei(&mut self) -> Entity {
+        self.single_entity::<Baobei>()
+    }
+
+    /// Returns the only entity tagged with the component `T`.
+    fn single_entity<T: Component>(&mut self) -> Entity {
+        let world = &mut self.app.world;
+        world
+            .query_filtered::<Entity, With<T>>()
+            .iter(world)
+            .next()
+            .expect("No entity with this component")
+    }
+
+    /// Moves the entity to the given position.
+    pub fn teleport(&mut self, entity: Entity, position: Vec3) {
+        *self.world().get_mut::<Position>(entity).unwrap() = Position(position);
+    }
+
+    /// Returns the component `T` of the entity, if any.
+    pub fn get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.app.world.get::<T>(entity)
+    }
+
+    /// Returns the entities in contact with the given one.
+    pub fn contacts_of(&mut self, entity: Entity) -> Vec<Entity> {
+        let world = &mut self.app.world;
+        world
+            .query::<&Contact>()
+            .iter(world)
+            .filter(|contact| contact.0 == entity)
+            .map(|contact| contact.1)
+            .collect()
+    }
+
+    /// Returns the resource `T`, panicking if it does not exist.
+    pub fn resource_mut<T: Component>(&mut self) -> Mut<'_, T> {
+        self.world()
+            .get_resource_mut::<T>()
+            .expect("Missing resource")
+    }
+
+    /// Returns the world of the app.
+    pub fn world(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+}